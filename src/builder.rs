@@ -1,31 +1,155 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use crate::network::{WiFiNetwork, NetworkData};
+use crate::crypto::ByteString;
+use crate::network::{ssid_text, FrameSource, NetworkData, SsidReveal, WiFiNetwork};
+use crate::security::SecurityProfile;
+use crate::station::{Station, StationObservation, StationTracker};
 
 /// Network builder for managing discovered networks
 pub struct NetworkBuilder {
     networks: HashMap<String, WiFiNetwork>,
+    /// SSIDs seen in frames other than beacons, keyed by BSSID
+    names: HashMap<String, (Vec<u8>, FrameSource)>,
+    stations: StationTracker,
 }
 
 impl NetworkBuilder {
     pub fn new() -> Self {
         Self {
             networks: HashMap::new(),
+            names: HashMap::new(),
+            stations: StationTracker::new(),
         }
     }
 
-    pub fn add_network(&mut self, data: NetworkData) {
-        let network = WiFiNetwork::new(
-            data.ssid,
-            data.bssid.clone(),
-            data.channel,
-            data.rssi,
-            data.encryption,
-        );
-        
-        self.networks.insert(data.bssid, network);
+    /// Merge a beacon or probe response into the record for its BSSID
+    pub fn add_network(&mut self, data: NetworkData, timestamp: f64) {
+        if !data.hidden && data.source != FrameSource::Beacon {
+            self.names
+                .entry(data.bssid.clone())
+                .or_insert_with(|| (data.ssid.clone(), data.source));
+        }
+
+        let bssid = data.bssid.clone();
+        let network = match self.networks.entry(bssid) {
+            Entry::Occupied(entry) => {
+                let network = entry.into_mut();
+                merge(network, data, timestamp);
+                network
+            }
+            Entry::Vacant(entry) => {
+                let mut network = WiFiNetwork::new(
+                    ByteString::Bytes(data.ssid),
+                    data.bssid,
+                    data.channel,
+                    data.rssi,
+                    data.encryption,
+                    data.frequency,
+                );
+                network.security = data.security;
+                network.was_hidden = data.hidden;
+                network.record_seen(timestamp);
+                if data.source == FrameSource::Beacon {
+                    network.beacon_count = 1;
+                }
+                entry.insert(network)
+            }
+        };
+
+        if network.was_hidden {
+            resolve(&self.names, network);
+        }
+    }
+
+    /// Record an SSID named by a client frame, decloaking the BSS if it is hidden
+    pub fn add_reveal(&mut self, reveal: SsidReveal) {
+        self.names
+            .entry(reveal.bssid.clone())
+            .or_insert((reveal.ssid, reveal.source));
+
+        if let Some(network) = self.networks.get_mut(&reveal.bssid) {
+            if network.was_hidden {
+                resolve(&self.names, network);
+            }
+        }
+    }
+
+    /// Record a frame exchanged with a client station
+    pub fn add_station(&mut self, observation: StationObservation, timestamp: f64) {
+        self.stations.add_observation(observation, timestamp);
     }
 
     pub fn get_networks(self) -> Vec<WiFiNetwork> {
-        self.networks.into_values().collect()
+        let stations = self.stations;
+        self.networks
+            .into_values()
+            .map(|mut network| {
+                network.clients = stations.clients_of(&network.bssid);
+                network
+            })
+            .collect()
+    }
+
+    pub fn get_stations(self) -> Vec<Station> {
+        self.stations.get_stations()
+    }
+}
+
+/// Apply the first known name to a hidden network
+fn resolve(names: &HashMap<String, (Vec<u8>, FrameSource)>, network: &mut WiFiNetwork) {
+    if network.revealed_by.is_some() {
+        return;
+    }
+    if let Some((ssid, source)) = names.get(&network.bssid) {
+        network.essid.clone_from(ssid);
+        network.revealed_by = Some(source.as_str().to_string());
+    }
+}
+
+/// Fold a new observation into an existing network record, recording attribute changes
+fn merge(network: &mut WiFiNetwork, data: NetworkData, timestamp: f64) {
+    network.record_seen(timestamp);
+    if data.source == FrameSource::Beacon {
+        network.beacon_count += 1;
+    }
+    network.record_rssi(data.rssi);
+    if data.frequency.is_some() {
+        network.frequency = data.frequency;
+    }
+
+    // Hiding is sticky, and a hidden beacon never overwrites a known name
+    if data.hidden {
+        network.was_hidden = true;
+    } else if network.essid != data.ssid {
+        if network.was_hidden && network.revealed_by.is_none() {
+            network.revealed_by = Some(data.source.as_str().to_string());
+        } else {
+            network.record_change(timestamp, "ssid", ssid_text(&network.essid), ssid_text(&data.ssid));
+        }
+        network.essid = data.ssid;
+    }
+
+    // Channel 0 means the frame carried no channel information
+    if data.channel != 0 && data.channel != network.channel {
+        if network.channel != 0 {
+            network.record_change(timestamp, "channel", network.channel.to_string(), data.channel.to_string());
+        }
+        network.channel = data.channel;
+    }
+    if data.channel != 0 && !network.channels.contains(&data.channel) {
+        network.channels.push(data.channel);
+    }
+
+    // A changed encryption family is reported on its own; otherwise report changed security labels
+    if data.encryption == network.encryption {
+        let label = |security: Option<&SecurityProfile>| security.map_or_else(|| "Open".to_string(), |profile| profile.label.clone());
+        let (old_label, new_label) = (label(network.security.as_ref()), label(data.security.as_ref()));
+        if old_label != new_label {
+            network.record_change(timestamp, "security", old_label, new_label);
+        }
+    } else {
+        network.record_change(timestamp, "encryption", network.encryption.clone(), data.encryption.clone());
+        network.encryption = data.encryption;
     }
+    network.security = data.security;
 }
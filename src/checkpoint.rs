@@ -0,0 +1,298 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use crate::cracker::{CrackProgress, CrackedHash, SourcePosition};
+use crate::hashline::HashLine;
+use crate::mask::Mask;
+use crate::rules::Rule;
+use crate::utils::{from_hex, to_hex};
+
+/// Format version written to new checkpoints
+const CHECKPOINT_VERSION: u32 = 1;
+/// Default seconds between checkpoint writes
+pub const DEFAULT_CHECKPOINT_INTERVAL: f64 = 60.0;
+
+/// Errors loading a checkpoint
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("Checkpoint {path} is malformed: {message}")]
+    Malformed { path: String, message: String },
+    #[error("Unsupported checkpoint version {0}; expected {CHECKPOINT_VERSION}")]
+    Version(u32),
+    #[error("Checkpoint {path} does not match the current {changed}; refusing to resume")]
+    Changed { path: String, changed: String },
+}
+
+impl From<CheckpointError> for PyErr {
+    fn from(error: CheckpointError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// SHA-256 digests of everything that decides which candidates a job tries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputHashes {
+    /// "wordlist" or "mask"
+    pub mode: String,
+    pub targets: String,
+    /// Wordlist contents, or mask charsets, lengths and keyspace range
+    pub candidates: String,
+    pub rules: String,
+}
+
+impl InputHashes {
+    /// Digests for a wordlist job
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wordlist cannot be read.
+    pub fn wordlist(targets: &[HashLine], wordlist: &str, rules: &[Rule]) -> io::Result<Self> {
+        let mut file = File::open(wordlist)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 1 << 20];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        let mut rule_hasher = Sha256::new();
+        for rule in rules {
+            rule_hasher.update(rule.to_string());
+            rule_hasher.update(b"\n");
+        }
+
+        Ok(Self {
+            mode: "wordlist".to_string(),
+            targets: targets_digest(targets),
+            candidates: to_hex(&hasher.finalize()),
+            rules: to_hex(&rule_hasher.finalize()),
+        })
+    }
+
+    /// Digests for a mask job over the keyspace range `start..end`
+    #[must_use]
+    pub fn mask(targets: &[HashLine], mask: &Mask, start: u64, end: u64) -> Self {
+        let mut hasher = Sha256::new();
+        for charset in mask.charsets() {
+            hasher.update((charset.len() as u64).to_le_bytes());
+            hasher.update(charset);
+        }
+        for len in mask.lengths() {
+            hasher.update((*len as u64).to_le_bytes());
+        }
+        hasher.update(start.to_le_bytes());
+        hasher.update(end.to_le_bytes());
+
+        Self {
+            mode: "mask".to_string(),
+            targets: targets_digest(targets),
+            candidates: to_hex(&hasher.finalize()),
+            rules: to_hex(&Sha256::digest(b"")),
+        }
+    }
+
+    /// Names of the inputs that differ from `other`
+    fn changes(&self, other: &Self) -> Vec<&'static str> {
+        if self.mode != other.mode {
+            return vec!["attack mode"];
+        }
+        let mut changed = Vec::new();
+        if self.targets != other.targets {
+            changed.push("targets");
+        }
+        if self.candidates != other.candidates {
+            changed.push(if self.mode == "mask" { "mask" } else { "wordlist" });
+        }
+        if self.rules != other.rules {
+            changed.push("rules");
+        }
+        changed
+    }
+}
+
+fn targets_digest(targets: &[HashLine]) -> String {
+    let mut hasher = Sha256::new();
+    for target in targets {
+        hasher.update(target.to_string());
+        hasher.update(b"\n");
+    }
+    to_hex(&hasher.finalize())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CrackedRecord {
+    target: String,
+    /// Hex encoded, as passphrases need not be UTF-8
+    passphrase: String,
+    pmk: String,
+}
+
+/// Checkpoint file contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    version: u32,
+    inputs: InputHashes,
+    position: SourcePosition,
+    tested: u64,
+    skipped: u64,
+    elapsed: f64,
+    open: Vec<String>,
+    cracked: Vec<CrackedRecord>,
+}
+
+/// Progress restored from a checkpoint
+#[derive(Debug, Clone, Default)]
+pub struct Resume {
+    pub position: SourcePosition,
+    pub tested: u64,
+    pub skipped: u64,
+    pub elapsed: f64,
+    /// Hits with the index of the target each one cracked
+    pub found: Vec<(usize, CrackedHash)>,
+}
+
+/// Periodically records a job's progress to a JSON file, and restores it
+pub struct Checkpointer {
+    path: String,
+    interval: Duration,
+    inputs: InputHashes,
+    last_save: Instant,
+    resume: Option<Resume>,
+}
+
+impl Checkpointer {
+    /// Prepare checkpoints at `path`, loading the one already there
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing checkpoint cannot be read, is malformed or
+    /// was written for different inputs.
+    pub fn open(path: &str, interval: Duration, inputs: InputHashes, targets: &[HashLine]) -> PyResult<Self> {
+        let resume = if Path::new(path).exists() {
+            Some(load(path, &inputs, targets)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            path: path.to_string(),
+            interval,
+            inputs,
+            last_save: Instant::now(),
+            resume,
+        })
+    }
+
+    /// Progress to continue from, once
+    pub fn take_resume(&mut self) -> Option<Resume> {
+        self.resume.take()
+    }
+
+    /// Whether the checkpoint interval has passed since the last write
+    #[must_use]
+    pub fn due(&self) -> bool {
+        self.last_save.elapsed() >= self.interval
+    }
+
+    /// Replace the checkpoint file with the current progress
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(
+        &mut self,
+        targets: &[HashLine],
+        cracked: &[bool],
+        found: &[CrackedHash],
+        position: SourcePosition,
+        progress: &CrackProgress,
+    ) -> io::Result<()> {
+        let record = Record {
+            version: CHECKPOINT_VERSION,
+            inputs: self.inputs.clone(),
+            position,
+            tested: progress.tested,
+            skipped: progress.skipped,
+            elapsed: progress.elapsed,
+            open: targets
+                .iter()
+                .zip(cracked)
+                .filter(|(_, cracked)| !**cracked)
+                .map(|(target, _)| target.to_string())
+                .collect(),
+            cracked: found
+                .iter()
+                .map(|hit| CrackedRecord {
+                    target: hit.target.to_string(),
+                    passphrase: to_hex(&hit.passphrase),
+                    pmk: hit.pmk.clone(),
+                })
+                .collect(),
+        };
+
+        // Write aside and rename, so an interruption never leaves a torn checkpoint
+        let temporary = format!("{}.tmp", self.path);
+        fs::write(&temporary, serde_json::to_vec_pretty(&record)?)?;
+        fs::rename(&temporary, &self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+}
+
+fn load(path: &str, inputs: &InputHashes, targets: &[HashLine]) -> PyResult<Resume> {
+    let malformed = |message: String| CheckpointError::Malformed {
+        path: path.to_string(),
+        message,
+    };
+    let record: Record = serde_json::from_slice(&fs::read(path)?).map_err(|error| malformed(error.to_string()))?;
+
+    if record.version != CHECKPOINT_VERSION {
+        return Err(CheckpointError::Version(record.version).into());
+    }
+    let changed = inputs.changes(&record.inputs);
+    if !changed.is_empty() {
+        return Err(CheckpointError::Changed {
+            path: path.to_string(),
+            changed: changed.join(", "),
+        }
+        .into());
+    }
+
+    // Targets are unchanged, so each hit maps back to the first matching target not yet cracked
+    let lines: Vec<String> = targets.iter().map(ToString::to_string).collect();
+    let mut taken = vec![false; targets.len()];
+    let mut found = Vec::new();
+    for hit in record.cracked {
+        let index = lines
+            .iter()
+            .zip(&taken)
+            .position(|(line, taken)| !taken && *line == hit.target)
+            .ok_or_else(|| malformed(format!("cracked hash {} is not a target", hit.target)))?;
+        let passphrase = from_hex(&hit.passphrase).ok_or_else(|| malformed(format!("bad passphrase {}", hit.passphrase)))?;
+        taken[index] = true;
+        found.push((
+            index,
+            CrackedHash {
+                target: targets[index].clone(),
+                passphrase,
+                pmk: hit.pmk,
+            },
+        ));
+    }
+
+    Ok(Resume {
+        position: record.position,
+        tested: record.tested,
+        skipped: record.skipped,
+        elapsed: record.elapsed,
+        found,
+    })
+}
@@ -0,0 +1,624 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::fs::File;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::checkpoint::{Checkpointer, InputHashes, DEFAULT_CHECKPOINT_INTERVAL};
+use crate::crypto;
+use crate::hashfile::HashSource;
+use crate::hashline::HashLine;
+use crate::mask::{Mask, MaskSource};
+use crate::rules::{RuleSet, RuleSource};
+use crate::utils::to_hex;
+
+/// Shortest WPA passphrase
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+/// Longest WPA passphrase; 64 characters would be a hex PSK
+pub const MAX_PASSPHRASE_LEN: usize = 63;
+/// Default seconds between progress reports
+pub const DEFAULT_PROGRESS_INTERVAL: f64 = 1.0;
+/// Candidates per worker thread handed to rayon at once
+const CANDIDATES_PER_THREAD: usize = 256;
+
+/// Flag a Python thread can set to stop a running attack
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl CancellationToken {
+    #[new]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the attack to stop after the current batch
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress snapshot passed to the progress callback
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CrackProgress {
+    /// Candidates checked so far
+    #[pyo3(get)]
+    pub tested: u64,
+    /// Candidates skipped for being shorter than 8 or longer than 63 bytes
+    #[pyo3(get)]
+    pub skipped: u64,
+    /// Candidates checked per second
+    #[pyo3(get)]
+    pub rate: f64,
+    /// Share of the candidate source consumed, if its size is known
+    #[pyo3(get)]
+    pub percent: Option<f64>,
+    /// Seconds spent on the attack, including runs it was resumed from
+    #[pyo3(get)]
+    pub elapsed: f64,
+    /// Targets cracked so far
+    #[pyo3(get)]
+    pub found: usize,
+}
+
+#[pymethods]
+impl CrackProgress {
+    /// Get a summary of the progress
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        let percent = self.percent.map_or_else(String::new, |percent| format!("{percent:.1}%, "));
+        format!(
+            "{}{} tested, {:.0} c/s, {} found",
+            percent, self.tested, self.rate, self.found
+        )
+    }
+}
+
+/// Target whose passphrase was found
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CrackedHash {
+    #[pyo3(get)]
+    pub target: HashLine,
+    pub passphrase: Vec<u8>,
+    /// PMK derived from the passphrase, hex encoded
+    #[pyo3(get)]
+    pub pmk: String,
+}
+
+#[pymethods]
+impl CrackedHash {
+    /// Passphrase decoded as UTF-8, with invalid sequences replaced
+    #[getter]
+    #[must_use]
+    pub fn passphrase(&self) -> String {
+        String::from_utf8_lossy(&self.passphrase).into_owned()
+    }
+
+    /// Passphrase as raw bytes
+    #[getter]
+    #[must_use]
+    pub fn passphrase_bytes(&self) -> Vec<u8> {
+        self.passphrase.clone()
+    }
+
+    /// Get a summary of the result
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!("{} ({}): {}", self.target.essid_text(), self.target.bssid(), self.passphrase())
+    }
+}
+
+/// Outcome of an attack
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CrackResult {
+    #[pyo3(get)]
+    pub found: Vec<CrackedHash>,
+    #[pyo3(get)]
+    pub tested: u64,
+    #[pyo3(get)]
+    pub skipped: u64,
+    /// Seconds the attack ran, including runs it was resumed from
+    #[pyo3(get)]
+    pub elapsed: f64,
+    /// Candidates checked per second
+    #[pyo3(get)]
+    pub rate: f64,
+    /// The attack stopped on its cancellation token
+    #[pyo3(get)]
+    pub cancelled: bool,
+    /// Every candidate was checked
+    #[pyo3(get)]
+    pub exhausted: bool,
+    /// The attack continued from a checkpoint; counts and hits include earlier runs
+    #[pyo3(get)]
+    pub resumed: bool,
+    /// Distinct ESSIDs among the targets; each candidate costs one PBKDF2 run per ESSID
+    #[pyo3(get)]
+    pub essid_groups: usize,
+    /// PBKDF2 runs performed
+    #[pyo3(get)]
+    pub pmks_computed: u64,
+    /// Candidate/target checks performed; without PMK reuse each needs its own PBKDF2 run
+    #[pyo3(get)]
+    pub targets_checked: u64,
+    /// Candidate/target checks served per PBKDF2 run, `targets_checked / pmks_computed`;
+    /// a count ratio bounding the gain of PMK reuse, not a timing
+    #[pyo3(get)]
+    pub pmk_reuse_factor: f64,
+}
+
+/// Where a candidate source stands, enough to continue with its next candidate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePosition {
+    /// Wordlist byte offset or mask keyspace index
+    pub offset: u64,
+    /// Index of the next rule to apply to the word at `offset`
+    pub rule: usize,
+}
+
+/// Stream of passphrase candidates
+pub trait CandidateSource: Send {
+    /// Append up to `limit` candidates to `batch`, returning `false` once exhausted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying input cannot be read.
+    fn fill(&mut self, batch: &mut Vec<Vec<u8>>, limit: usize) -> io::Result<bool>;
+
+    /// Share of the source consumed so far, between 0 and 1, if known
+    fn fraction(&self) -> Option<f64>;
+
+    /// Position of the next candidate
+    fn position(&self) -> SourcePosition;
+
+    /// Continue from a position returned by `position`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the position lies outside the source.
+    fn seek(&mut self, position: SourcePosition) -> io::Result<()>;
+}
+
+/// Wordlist read line by line, with `\n` or `\r\n` endings
+pub struct WordlistSource {
+    reader: BufReader<File>,
+    read: u64,
+    size: u64,
+}
+
+impl WordlistSource {
+    /// Open a wordlist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            reader: BufReader::new(file),
+            read: 0,
+            size,
+        })
+    }
+}
+
+impl CandidateSource for WordlistSource {
+    fn fill(&mut self, batch: &mut Vec<Vec<u8>>, limit: usize) -> io::Result<bool> {
+        for _ in 0..limit {
+            let mut line = Vec::new();
+            let read = self.reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                return Ok(false);
+            }
+            self.read += read as u64;
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            batch.push(line);
+        }
+        Ok(true)
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        (self.size > 0).then(|| self.read as f64 / self.size as f64)
+    }
+
+    fn position(&self) -> SourcePosition {
+        SourcePosition {
+            offset: self.read,
+            rule: 0,
+        }
+    }
+
+    fn seek(&mut self, position: SourcePosition) -> io::Result<()> {
+        if position.offset > self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Offset {} is past the end of the wordlist", position.offset),
+            ));
+        }
+        self.reader.seek(SeekFrom::Start(position.offset))?;
+        self.read = position.offset;
+        Ok(())
+    }
+}
+
+/// Progress callback: receives a snapshot and may raise to abort the attack
+pub type ProgressCallback<'a> = dyn FnMut(&CrackProgress) -> PyResult<()> + Send + 'a;
+
+/// Check candidates from `source` against `targets` until all are cracked, the
+/// source runs out or `cancel` is set
+///
+/// The PMK depends only on passphrase and ESSID, so targets sharing an ESSID are
+/// checked against a single PBKDF2 run per candidate. With a `checkpoint`, the
+/// attack continues from the checkpoint's progress and records its own every
+/// checkpoint interval, at the end and when the progress callback fails.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be read or sought, the checkpoint cannot
+/// be written or the progress callback fails.
+pub fn crack(
+    targets: &[HashLine],
+    source: &mut dyn CandidateSource,
+    cancel: &CancellationToken,
+    progress: &mut ProgressCallback,
+    interval: Duration,
+    mut checkpoint: Option<&mut Checkpointer>,
+) -> PyResult<CrackResult> {
+    let started = Instant::now();
+    let batch_size = rayon::current_num_threads() * CANDIDATES_PER_THREAD;
+    let groups = group_by_essid(targets);
+    let mut cracked = vec![false; targets.len()];
+    let mut found = Vec::new();
+    let mut tested = 0u64;
+    let mut skipped = 0u64;
+    let mut earlier = 0.0;
+    let mut pmks_computed = 0u64;
+    let mut targets_checked = 0u64;
+    let mut last_report = started;
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut exhausted = false;
+
+    let resume = checkpoint.as_deref_mut().and_then(Checkpointer::take_resume);
+    let resumed = resume.is_some();
+    if let Some(resume) = resume {
+        source.seek(resume.position)?;
+        tested = resume.tested;
+        skipped = resume.skipped;
+        earlier = resume.elapsed;
+        for (index, hit) in resume.found {
+            cracked[index] = true;
+            found.push(hit);
+        }
+    }
+    let resumed_tested = tested;
+
+    // Totals include earlier sessions; the rate covers this one only
+    let snapshot = |tested: u64, skipped: u64, fraction: Option<f64>, found: usize| {
+        let session = started.elapsed().as_secs_f64();
+        #[allow(clippy::cast_precision_loss)]
+        let rate = if session > 0.0 { (tested - resumed_tested) as f64 / session } else { 0.0 };
+        CrackProgress {
+            tested,
+            skipped,
+            rate,
+            percent: fraction.map(|fraction| (fraction * 100.0).min(100.0)),
+            elapsed: earlier + session,
+            found,
+        }
+    };
+
+    while !cancel.cancelled() && cracked.iter().any(|cracked| !cracked) {
+        batch.clear();
+        exhausted = !source.fill(&mut batch, batch_size)?;
+
+        let before = batch.len();
+        batch.retain(|candidate| (MIN_PASSPHRASE_LEN..=MAX_PASSPHRASE_LEN).contains(&candidate.len()));
+        skipped += (before - batch.len()) as u64;
+        tested += batch.len() as u64;
+
+        let (pmks, checks) = check_batch(targets, &groups, &batch, &mut cracked, &mut found);
+        pmks_computed += pmks;
+        targets_checked += checks;
+
+        if exhausted {
+            break;
+        }
+        let report_due = last_report.elapsed() >= interval;
+        let checkpoint_due = checkpoint.as_deref().is_some_and(Checkpointer::due);
+        if report_due || checkpoint_due {
+            let current = snapshot(tested, skipped, source.fraction(), found.len());
+            if let Some(checkpoint) = checkpoint.as_deref_mut().filter(|_| checkpoint_due) {
+                checkpoint.save(targets, &cracked, &found, source.position(), &current)?;
+            }
+            if report_due {
+                last_report = Instant::now();
+                if let Err(error) = progress(&current) {
+                    if let Some(checkpoint) = checkpoint.as_deref_mut() {
+                        checkpoint.save(targets, &cracked, &found, source.position(), &current)?;
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    let last = snapshot(tested, skipped, source.fraction(), found.len());
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.save(targets, &cracked, &found, source.position(), &last)?;
+    }
+    progress(&last)?;
+
+    #[allow(clippy::cast_precision_loss)]
+    let pmk_reuse_factor = if pmks_computed > 0 {
+        targets_checked as f64 / pmks_computed as f64
+    } else {
+        1.0
+    };
+
+    Ok(CrackResult {
+        found,
+        tested,
+        skipped,
+        elapsed: last.elapsed,
+        rate: last.rate,
+        cancelled: cancel.cancelled(),
+        exhausted,
+        resumed,
+        essid_groups: groups.len(),
+        pmks_computed,
+        targets_checked,
+        pmk_reuse_factor,
+    })
+}
+
+/// Check a batch against every uncracked target, recording hits, and return the
+/// PBKDF2 runs performed and the candidate/target checks they served
+fn check_batch(
+    targets: &[HashLine],
+    groups: &[(Vec<u8>, Vec<usize>)],
+    batch: &[Vec<u8>],
+    cracked: &mut [bool],
+    found: &mut Vec<CrackedHash>,
+) -> (u64, u64) {
+    // Only targets still uncracked are worth a PBKDF2 run
+    let active: Vec<(&[u8], Vec<usize>)> = groups
+        .iter()
+        .map(|(essid, members)| {
+            let members: Vec<usize> = members.iter().copied().filter(|index| !cracked[*index]).collect();
+            (essid.as_slice(), members)
+        })
+        .filter(|(_, members)| !members.is_empty())
+        .collect();
+    let active_targets: usize = active.iter().map(|(_, members)| members.len()).sum();
+
+    let hits: Vec<(usize, &Vec<u8>, [u8; 32])> = batch
+        .par_iter()
+        .flat_map_iter(|candidate| {
+            active.iter().flat_map(move |(essid, members)| {
+                let pmk = crypto::pmk(candidate, essid);
+                members
+                    .iter()
+                    .filter(move |index| targets[**index].verify_pmk(&pmk))
+                    .map(move |index| (*index, candidate, pmk))
+            })
+        })
+        .collect();
+    for (index, candidate, pmk) in hits {
+        if !cracked[index] {
+            cracked[index] = true;
+            found.push(CrackedHash {
+                target: targets[index].clone(),
+                passphrase: candidate.clone(),
+                pmk: to_hex(&pmk),
+            });
+        }
+    }
+
+    ((batch.len() * active.len()) as u64, (batch.len() * active_targets) as u64)
+}
+
+/// Target indices grouped by ESSID, in order of first appearance
+pub fn group_by_essid(targets: &[HashLine]) -> Vec<(Vec<u8>, Vec<usize>)> {
+    let mut groups: Vec<(Vec<u8>, Vec<usize>)> = Vec::new();
+    for (index, target) in targets.iter().enumerate() {
+        match groups.iter_mut().find(|(essid, _)| *essid == target.essid) {
+            Some((_, members)) => members.push(index),
+            None => groups.push((target.essid.clone(), vec![index])),
+        }
+    }
+    groups
+}
+
+/// CPU dictionary attack against PMKID and handshake hashes
+#[pyclass]
+pub struct DictionaryAttack {
+    targets: Vec<HashLine>,
+}
+
+#[pymethods]
+impl DictionaryAttack {
+    /// Create an attack on hash lines, captures or line strings
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if a target is malformed or no target is given.
+    #[new]
+    pub fn new(targets: Vec<HashSource>) -> PyResult<Self> {
+        let targets = targets
+            .into_iter()
+            .map(HashSource::into_line)
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.is_empty() {
+            return Err(PyValueError::new_err("At least one target is required"));
+        }
+        Ok(Self { targets })
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn targets(&self) -> Vec<HashLine> {
+        self.targets.clone()
+    }
+
+    /// Run the wordlist against the targets on all CPU cores
+    ///
+    /// `progress` is called with a `CrackProgress` every `progress_interval`
+    /// seconds and once at the end; `cancel` stops the attack after the current batch.
+    /// With `rules`, every rule is applied to every word and only the mutations are tried.
+    ///
+    /// With `checkpoint`, progress is written to that file every
+    /// `checkpoint_interval` seconds and when the attack stops; if the file
+    /// exists, the attack continues where it left off.
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the wordlist cannot be read or the checkpoint written,
+    /// `ValueError` if an interval is too large or the checkpoint was written for
+    /// other targets, wordlist or rules, or any exception raised by the progress
+    /// callback or a pending signal such as `KeyboardInterrupt`.
+    #[pyo3(signature = (
+        wordlist,
+        progress=None,
+        cancel=None,
+        progress_interval=DEFAULT_PROGRESS_INTERVAL,
+        rules=None,
+        checkpoint=None,
+        checkpoint_interval=DEFAULT_CHECKPOINT_INTERVAL,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        &self,
+        py: Python<'_>,
+        wordlist: &str,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+        rules: Option<RuleSet>,
+        checkpoint: Option<&str>,
+        checkpoint_interval: f64,
+    ) -> PyResult<CrackResult> {
+        let rules = rules.map(|set| set.rules).unwrap_or_default();
+        let checkpoint = checkpoint
+            .map(|path| {
+                let inputs = py.detach(|| InputHashes::wordlist(&self.targets, wordlist, &rules))?;
+                Checkpointer::open(path, seconds(checkpoint_interval)?, inputs, &self.targets)
+            })
+            .transpose()?;
+        let mut words = WordlistSource::open(wordlist)?;
+        let mut source = RuleSource::new(&mut words, rules);
+        self.execute(py, &mut source, progress, cancel, progress_interval, checkpoint)
+    }
+
+    /// Run a mask against the targets
+    ///
+    /// `range` is a `(start, end)` slice of the keyspace, such as one returned by
+    /// `Mask.split`, and defaults to all of it. Progress, cancellation and
+    /// checkpoints work as in `run`.
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the range lies outside the keyspace, an interval is
+    /// too large or the checkpoint was written for other targets, mask or range,
+    /// `OSError` if the checkpoint cannot be written, or any exception raised by
+    /// the progress callback or a pending signal such as `KeyboardInterrupt`.
+    #[pyo3(signature = (
+        mask,
+        range=None,
+        progress=None,
+        cancel=None,
+        progress_interval=DEFAULT_PROGRESS_INTERVAL,
+        checkpoint=None,
+        checkpoint_interval=DEFAULT_CHECKPOINT_INTERVAL,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_mask(
+        &self,
+        py: Python<'_>,
+        mask: Mask,
+        range: Option<(u64, u64)>,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+        checkpoint: Option<&str>,
+        checkpoint_interval: f64,
+    ) -> PyResult<CrackResult> {
+        let (start, end) = range.unwrap_or((0, mask.keyspace()));
+        let checkpoint = checkpoint
+            .map(|path| {
+                let inputs = InputHashes::mask(&self.targets, &mask, start, end);
+                Checkpointer::open(path, seconds(checkpoint_interval)?, inputs, &self.targets)
+            })
+            .transpose()?;
+        let mut source = MaskSource::new(mask, start, end)?;
+        self.execute(py, &mut source, progress, cancel, progress_interval, checkpoint)
+    }
+}
+
+impl DictionaryAttack {
+    /// Run the engine with the GIL released, reacquiring it for progress reports
+    #[allow(clippy::needless_pass_by_value)]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        source: &mut dyn CandidateSource,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+        mut checkpoint: Option<Checkpointer>,
+    ) -> PyResult<CrackResult> {
+        let cancel = cancel.unwrap_or_default();
+        let interval = seconds(progress_interval)?;
+
+        py.detach(|| {
+            let mut report = python_progress(progress.as_ref());
+            crack(&self.targets, source, &cancel, &mut report, interval, checkpoint.as_mut())
+        })
+    }
+}
+
+/// Progress callback for use with the GIL released: reacquires it to check for
+/// pending signals and pass each snapshot to the optional Python callable
+pub fn python_progress(progress: Option<&Py<PyAny>>) -> impl FnMut(&CrackProgress) -> PyResult<()> + Send + '_ {
+    move |snapshot| {
+        Python::attach(|py| {
+            py.check_signals()?;
+            if let Some(callback) = progress {
+                callback.call1(py, (snapshot.clone(),))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Interval of a Python float of seconds, negative values meaning zero
+///
+/// # Errors
+///
+/// Raises `ValueError` if the interval is too large for a duration.
+pub fn seconds(interval: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(interval.max(0.0))
+        .map_err(|_| PyValueError::new_err(format!("Invalid interval: {interval} seconds")))
+}
@@ -0,0 +1,278 @@
+use aes::Aes128;
+use cmac::Cmac;
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use sha1::Sha1;
+use sha2::Sha256;
+use thiserror::Error;
+
+/// PBKDF2 iterations of the WPA passphrase-to-PMK mapping
+pub const PMK_ITERATIONS: u32 = 4096;
+/// Label of the pairwise key expansion
+const PTK_LABEL: &[u8] = b"Pairwise key expansion";
+/// Label prepended to the MAC addresses when naming a PMK
+const PMKID_LABEL: &[u8] = b"PMK Name";
+
+/// Errors raised for malformed crypto inputs
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    #[error("{field} must be {expected} bytes, got {actual}")]
+    Length {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Unsupported PTK length {0} bits; expected 384 or 512")]
+    PtkLength(usize),
+    #[error("PRF length must be a positive multiple of 8 bits, got {0}")]
+    PrfLength(usize),
+    #[error("Unsupported key descriptor version {0}; expected 1, 2 or 3")]
+    KeyVersion(u8),
+    #[error("Pass either a passphrase or a PMK")]
+    Credentials,
+}
+
+impl From<CryptoError> for PyErr {
+    fn from(error: CryptoError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// Derive the PMK from a passphrase: PBKDF2-HMAC-SHA1 over the SSID, 4096 iterations
+#[must_use]
+pub fn pmk(passphrase: &[u8], ssid: &[u8]) -> [u8; 32] {
+    let mut pmk = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha1>(passphrase, ssid, PMK_ITERATIONS, &mut pmk);
+    pmk
+}
+
+/// IEEE 802.11 PRF-`bits`: HMAC-SHA1 over `label || 0 || data || counter`
+#[must_use]
+pub fn prf(key: &[u8], label: &[u8], data: &[u8], bits: usize) -> Vec<u8> {
+    let len = bits / 8;
+    let mut output = Vec::with_capacity(len + 20);
+    for counter in 0..=u8::MAX {
+        if output.len() >= len {
+            break;
+        }
+        output.extend_from_slice(&hmac::<Hmac<Sha1>>(key, &[label, &[0], data, &[counter]]));
+    }
+    output.truncate(len);
+    output
+}
+
+/// IEEE 802.11 KDF-SHA256-`bits`: HMAC-SHA256 over `counter || label || context || bits`,
+/// counter and length little endian
+#[must_use]
+pub fn kdf_sha256(key: &[u8], label: &[u8], context: &[u8], bits: usize) -> Vec<u8> {
+    let len = bits / 8;
+    let length = u16::try_from(bits).unwrap_or(u16::MAX).to_le_bytes();
+    let mut output = Vec::with_capacity(len + 32);
+    for counter in 1..=u16::MAX {
+        if output.len() >= len {
+            break;
+        }
+        output.extend_from_slice(&hmac::<Hmac<Sha256>>(key, &[&counter.to_le_bytes(), label, context, &length]));
+    }
+    output.truncate(len);
+    output
+}
+
+/// `min(AA, SPA) || max(AA, SPA) || min(ANonce, SNonce) || max(ANonce, SNonce)`
+fn ptk_context(aa: [u8; 6], spa: [u8; 6], anonce: &[u8; 32], snonce: &[u8; 32]) -> Vec<u8> {
+    let (mac_low, mac_high) = if aa <= spa { (aa, spa) } else { (spa, aa) };
+    let (nonce_low, nonce_high) = if anonce <= snonce { (anonce, snonce) } else { (snonce, anonce) };
+    [&mac_low[..], &mac_high, nonce_low, nonce_high].concat()
+}
+
+/// Derive the PTK with the SHA1 PRF (key descriptor versions 1 and 2)
+#[must_use]
+pub fn ptk(pmk: &[u8; 32], aa: [u8; 6], spa: [u8; 6], anonce: &[u8; 32], snonce: &[u8; 32], bits: usize) -> Vec<u8> {
+    prf(pmk, PTK_LABEL, &ptk_context(aa, spa, anonce, snonce), bits)
+}
+
+/// Derive the PTK with KDF-SHA256 (key descriptor version 3)
+#[must_use]
+pub fn ptk_sha256(pmk: &[u8; 32], aa: [u8; 6], spa: [u8; 6], anonce: &[u8; 32], snonce: &[u8; 32], bits: usize) -> Vec<u8> {
+    kdf_sha256(pmk, PTK_LABEL, &ptk_context(aa, spa, anonce, snonce), bits)
+}
+
+/// PMKID: HMAC-SHA1-128(PMK, "PMK Name" || AA || SPA)
+#[must_use]
+pub fn pmkid(pmk: &[u8; 32], aa: [u8; 6], spa: [u8; 6]) -> [u8; 16] {
+    truncate(&hmac::<Hmac<Sha1>>(pmk, &[PMKID_LABEL, &aa, &spa]))
+}
+
+/// PMKID for SHA256 AKMs: HMAC-SHA256-128(PMK, "PMK Name" || AA || SPA)
+#[must_use]
+pub fn pmkid_sha256(pmk: &[u8; 32], aa: [u8; 6], spa: [u8; 6]) -> [u8; 16] {
+    truncate(&hmac::<Hmac<Sha256>>(pmk, &[PMKID_LABEL, &aa, &spa]))
+}
+
+/// MIC of an EAPOL frame whose MIC field is zeroed, keyed with the KCK
+///
+/// Key descriptor version 1 uses HMAC-MD5, 2 HMAC-SHA1-128 and 3 AES-128-CMAC.
+///
+/// # Errors
+///
+/// Returns an error for other key descriptor versions.
+pub fn mic(key_version: u8, kck: &[u8; 16], eapol: &[u8]) -> Result<[u8; 16], CryptoError> {
+    match key_version {
+        1 => Ok(truncate(&hmac::<Hmac<Md5>>(kck, &[eapol]))),
+        2 => Ok(truncate(&hmac::<Hmac<Sha1>>(kck, &[eapol]))),
+        3 => {
+            let mut cmac = <Cmac<Aes128> as cmac::digest::KeyInit>::new(kck.into());
+            cmac.update(eapol);
+            Ok(cmac.finalize().into_bytes().into())
+        }
+        version => Err(CryptoError::KeyVersion(version)),
+    }
+}
+
+/// HMAC of the concatenated `parts`
+fn hmac<M: Mac + hmac::digest::KeyInit>(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).expect("HMAC takes keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn truncate(digest: &[u8]) -> [u8; 16] {
+    let mut truncated = [0; 16];
+    truncated.copy_from_slice(&digest[..16]);
+    truncated
+}
+
+/// Check that `bytes` has the fixed length of `field`
+///
+/// # Errors
+///
+/// Returns an error naming `field` if the length differs.
+pub fn fixed<const N: usize>(field: &'static str, bytes: &[u8]) -> Result<[u8; N], CryptoError> {
+    bytes.try_into().map_err(|_| CryptoError::Length {
+        field,
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+fn check_ptk_bits(bits: usize) -> Result<usize, CryptoError> {
+    match bits {
+        384 | 512 => Ok(bits),
+        _ => Err(CryptoError::PtkLength(bits)),
+    }
+}
+
+/// Text or binary argument, with text encoded as UTF-8
+#[derive(FromPyObject)]
+pub enum ByteString {
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+impl ByteString {
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            Self::Text(text) => text.as_bytes(),
+        }
+    }
+
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            Self::Text(text) => text.into_bytes(),
+        }
+    }
+}
+
+/// PMK to verify a hash with: derived from `passphrase` over `essid`, or given directly
+///
+/// # Errors
+///
+/// Returns an error unless exactly one of `passphrase` and `pmk` is given, or if
+/// the PMK is not 32 bytes.
+pub fn candidate_pmk(passphrase: Option<&ByteString>, pmk: Option<&[u8]>, essid: &[u8]) -> Result<[u8; 32], CryptoError> {
+    match (passphrase, pmk) {
+        (Some(passphrase), None) => Ok(self::pmk(passphrase.as_bytes(), essid)),
+        (None, Some(pmk)) => fixed("PMK", pmk),
+        _ => Err(CryptoError::Credentials),
+    }
+}
+
+/// Derive the 32-byte PMK from a passphrase and SSID
+#[pyfunction(name = "derive_pmk")]
+#[must_use]
+#[allow(clippy::needless_pass_by_value)]
+pub fn py_derive_pmk(passphrase: ByteString, ssid: ByteString) -> Vec<u8> {
+    pmk(passphrase.as_bytes(), ssid.as_bytes()).to_vec()
+}
+
+/// IEEE 802.11 SHA1 PRF producing `bits` bits
+///
+/// # Errors
+///
+/// Raises `ValueError` unless `bits` is a positive multiple of 8.
+#[pyfunction(name = "prf")]
+pub fn py_prf(key: &[u8], label: &[u8], data: &[u8], bits: usize) -> PyResult<Vec<u8>> {
+    if bits == 0 || !bits.is_multiple_of(8) || bits > 160 * 256 {
+        return Err(CryptoError::PrfLength(bits).into());
+    }
+    Ok(prf(key, label, data, bits))
+}
+
+/// Derive the PTK from the PMK, MAC addresses and nonces
+///
+/// `sha256` selects KDF-SHA256 (key descriptor version 3) instead of the SHA1 PRF.
+///
+/// # Errors
+///
+/// Raises `ValueError` if an input has the wrong length or `bits` is not 384 or 512.
+#[pyfunction(name = "derive_ptk")]
+#[pyo3(signature = (pmk, aa, spa, anonce, snonce, bits=384, sha256=false))]
+pub fn py_derive_ptk(
+    pmk: &[u8],
+    aa: &[u8],
+    spa: &[u8],
+    anonce: &[u8],
+    snonce: &[u8],
+    bits: usize,
+    sha256: bool,
+) -> PyResult<Vec<u8>> {
+    let derive = if sha256 { ptk_sha256 } else { ptk };
+    Ok(derive(
+        &fixed("PMK", pmk)?,
+        fixed("AA", aa)?,
+        fixed("SPA", spa)?,
+        &fixed("ANonce", anonce)?,
+        &fixed("SNonce", snonce)?,
+        check_ptk_bits(bits)?,
+    ))
+}
+
+/// Compute the PMKID an access point advertises for `pmk`
+///
+/// # Errors
+///
+/// Raises `ValueError` if an input has the wrong length.
+#[pyfunction(name = "compute_pmkid")]
+#[pyo3(signature = (pmk, aa, spa, sha256=false))]
+pub fn py_compute_pmkid(pmk: &[u8], aa: &[u8], spa: &[u8], sha256: bool) -> PyResult<Vec<u8>> {
+    let compute = if sha256 { pmkid_sha256 } else { pmkid };
+    Ok(compute(&fixed("PMK", pmk)?, fixed("AA", aa)?, fixed("SPA", spa)?).to_vec())
+}
+
+/// Compute the MIC of an EAPOL frame (MIC field zeroed) with the 16-byte KCK
+///
+/// # Errors
+///
+/// Raises `ValueError` if the KCK is not 16 bytes or the key version is not 1, 2 or 3.
+#[pyfunction(name = "compute_mic")]
+pub fn py_compute_mic(kck: &[u8], eapol: &[u8], key_version: u8) -> PyResult<Vec<u8>> {
+    Ok(mic(key_version, &fixed("KCK", kck)?, eapol)?.to_vec())
+}
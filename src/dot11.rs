@@ -0,0 +1,252 @@
+/// 802.11 frame types
+pub const TYPE_MANAGEMENT: u8 = 0;
+pub const TYPE_DATA: u8 = 2;
+
+/// Management frame subtypes
+pub const SUBTYPE_ASSOCIATION_REQUEST: u8 = 0;
+pub const SUBTYPE_ASSOCIATION_RESPONSE: u8 = 1;
+pub const SUBTYPE_REASSOCIATION_REQUEST: u8 = 2;
+pub const SUBTYPE_REASSOCIATION_RESPONSE: u8 = 3;
+pub const SUBTYPE_PROBE_REQUEST: u8 = 4;
+pub const SUBTYPE_PROBE_RESPONSE: u8 = 5;
+pub const SUBTYPE_BEACON: u8 = 8;
+
+/// Length of the management frame header without HT Control
+pub const MANAGEMENT_HEADER_LEN: usize = 24;
+
+/// Length of the three-address data frame header
+pub const DATA_HEADER_LEN: usize = 24;
+
+/// LLC/SNAP header announcing an encapsulated ethertype (RFC 1042 and 802.1H bridge tunnel)
+const LLC_SNAP_RFC1042: [u8; 6] = [0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00];
+const LLC_SNAP_BRIDGE_TUNNEL: [u8; 6] = [0xAA, 0xAA, 0x03, 0x00, 0x00, 0xF8];
+
+/// Frame control field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameControl(pub u16);
+
+impl FrameControl {
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        data.get(..2).map(|fc| Self(u16::from_le_bytes([fc[0], fc[1]])))
+    }
+
+    #[must_use]
+    pub fn protocol_version(self) -> u8 {
+        self.0.to_le_bytes()[0] & 0x03
+    }
+
+    #[must_use]
+    pub fn frame_type(self) -> u8 {
+        (self.0.to_le_bytes()[0] >> 2) & 0x03
+    }
+
+    #[must_use]
+    pub fn subtype(self) -> u8 {
+        self.0.to_le_bytes()[0] >> 4
+    }
+
+    #[must_use]
+    pub fn is_to_ds(self) -> bool {
+        self.0 & 0x0100 != 0
+    }
+
+    #[must_use]
+    pub fn is_from_ds(self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+
+    #[must_use]
+    pub fn is_protected(self) -> bool {
+        self.0 & 0x4000 != 0
+    }
+
+    /// Order bit: an HT Control field follows the header of `QoS` data and management frames
+    #[must_use]
+    pub fn order(self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+}
+
+/// Header of a management frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManagementHeader {
+    pub frame_control: FrameControl,
+    /// Destination address
+    pub addr1: [u8; 6],
+    /// Source address
+    pub addr2: [u8; 6],
+    /// BSSID
+    pub addr3: [u8; 6],
+    /// Offset of the frame body
+    pub body_offset: usize,
+}
+
+impl ManagementHeader {
+    /// Parse the header of a management frame
+    ///
+    /// Returns `None` for truncated frames and frames of other types.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let frame_control = FrameControl::parse(data)?;
+        if frame_control.protocol_version() != 0 || frame_control.frame_type() != TYPE_MANAGEMENT {
+            return None;
+        }
+
+        let body_offset = MANAGEMENT_HEADER_LEN + if frame_control.order() { 4 } else { 0 };
+        if data.len() < body_offset {
+            return None;
+        }
+
+        Some(Self {
+            frame_control,
+            addr1: mac_at(data, 4)?,
+            addr2: mac_at(data, 10)?,
+            addr3: mac_at(data, 16)?,
+            body_offset,
+        })
+    }
+
+    #[must_use]
+    pub fn subtype(&self) -> u8 {
+        self.frame_control.subtype()
+    }
+}
+
+/// Header of a data frame (including null-data and `QoS` subtypes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataHeader {
+    pub frame_control: FrameControl,
+    /// Receiver address
+    pub addr1: [u8; 6],
+    /// Transmitter address
+    pub addr2: [u8; 6],
+    pub addr3: [u8; 6],
+    /// Offset of the frame body, after the optional fourth address, `QoS` Control and HT Control fields
+    pub body_offset: usize,
+}
+
+impl DataHeader {
+    /// Parse the header of a data frame
+    ///
+    /// Returns `None` for truncated frames and frames of other types.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let frame_control = FrameControl::parse(data)?;
+        if frame_control.protocol_version() != 0 || frame_control.frame_type() != TYPE_DATA {
+            return None;
+        }
+
+        // Four-address frames carry addr4; QoS subtypes add QoS Control, which may be followed by HT Control
+        let is_qos = frame_control.subtype() & 0x08 != 0;
+        let mut body_offset = DATA_HEADER_LEN;
+        if frame_control.is_to_ds() && frame_control.is_from_ds() {
+            body_offset += 6;
+        }
+        if is_qos {
+            body_offset += 2;
+            if frame_control.order() {
+                body_offset += 4;
+            }
+        }
+        if data.len() < body_offset {
+            return None;
+        }
+
+        Some(Self {
+            frame_control,
+            addr1: mac_at(data, 4)?,
+            addr2: mac_at(data, 10)?,
+            addr3: mac_at(data, 16)?,
+            body_offset,
+        })
+    }
+
+    /// Whether the frame carries no payload (null-data and `QoS` null subtypes)
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        self.frame_control.subtype() & 0x04 != 0
+    }
+
+    /// BSSID according to the `ToDS`/`FromDS` bits
+    ///
+    /// Returns `None` for four-address (WDS/mesh) frames, which do not name a BSS.
+    #[must_use]
+    pub fn bssid(&self) -> Option<[u8; 6]> {
+        match (self.frame_control.is_to_ds(), self.frame_control.is_from_ds()) {
+            (false, false) => Some(self.addr3),
+            (true, false) => Some(self.addr1),
+            (false, true) => Some(self.addr2),
+            (true, true) => None,
+        }
+    }
+}
+
+/// Ethertype and payload of an LLC/SNAP encapsulated frame body
+#[must_use]
+pub fn llc_snap_payload(body: &[u8]) -> Option<(u16, &[u8])> {
+    let header = body.get(..6)?;
+    if header != LLC_SNAP_RFC1042 && header != LLC_SNAP_BRIDGE_TUNNEL {
+        return None;
+    }
+    let ethertype = u16::from_be_bytes([*body.get(6)?, *body.get(7)?]);
+    Some((ethertype, &body[8..]))
+}
+
+/// Read a MAC address at `offset`
+#[must_use]
+pub fn mac_at(data: &[u8], offset: usize) -> Option<[u8; 6]> {
+    data.get(offset..offset + 6)?.try_into().ok()
+}
+
+/// Format a MAC address as colon separated lowercase hex
+#[must_use]
+pub fn format_mac(mac: [u8; 6]) -> String {
+    format!(
+        "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+        mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
+    )
+}
+
+/// Whether an address is a group (multicast or broadcast) address
+#[must_use]
+pub fn is_group_address(mac: [u8; 6]) -> bool {
+    mac[0] & 0x01 != 0
+}
+
+/// Raw bytes of an SSID element, or `None` for hidden (empty or zero-filled) SSIDs
+///
+/// SSIDs need not be UTF-8; the bytes are kept as sent, since they salt the PMK.
+#[must_use]
+pub fn decode_ssid(payload: &[u8]) -> Option<Vec<u8>> {
+    if payload.iter().all(|byte| *byte == 0) {
+        return None;
+    }
+    Some(payload.to_vec())
+}
+
+/// Iterator over the tagged information elements of a frame body
+pub struct InformationElements<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> InformationElements<'a> {
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+}
+
+impl<'a> Iterator for InformationElements<'a> {
+    /// Element ID and payload
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element_id = *self.data.get(self.offset)?;
+        let element_len = usize::from(*self.data.get(self.offset + 1)?);
+        let payload = self.data.get(self.offset + 2..self.offset + 2 + element_len)?;
+        self.offset += 2 + element_len;
+        Some((element_id, payload))
+    }
+}
@@ -0,0 +1,513 @@
+use pyo3::prelude::*;
+use crate::dot11::{format_mac, llc_snap_payload, mac_at, DataHeader, InformationElements};
+use crate::frame::Frame;
+use crate::security::{RsnElement, IEEE80211_OUI};
+
+/// Ethertype of 802.1X authentication (EAPOL)
+pub const ETHERTYPE_EAPOL: u16 = 0x888E;
+
+/// EAPOL packet type of key frames
+pub const EAPOL_KEY: u8 = 3;
+
+/// EAPOL-Key descriptor types
+pub const DESCRIPTOR_RSN: u8 = 2;
+pub const DESCRIPTOR_WPA: u8 = 254;
+
+/// Key Information: Key Ack, set on frames sent by the authenticator
+const KEY_INFO_ACK: u16 = 0x0080;
+
+/// Length of the EAPOL-Key fields before the MIC: descriptor type (1), key information (2),
+/// key length (2), replay counter (8), nonce (32), IV (16), RSC (8), reserved (8)
+const KEY_FIXED_LEN: usize = 77;
+
+/// Element ID of key data encapsulations (KDEs), shared with vendor specific elements
+const KDE_ELEMENT_ID: u8 = 0xDD;
+
+/// KDE data types under the IEEE 802.11 OUI
+const KDE_GTK: u8 = 1;
+const KDE_MAC_ADDRESS: u8 = 3;
+const KDE_PMKID: u8 = 4;
+const KDE_IGTK: u8 = 9;
+const KDE_BIGTK: u8 = 14;
+
+/// MIC lengths tried, in order, when the AKM of the network is not known
+const MIC_LENGTHS: [usize; 3] = [16, 24, 0];
+
+/// An EAPOL PDU with the addresses of the link it was exchanged on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EapolFrame<'a> {
+    /// Access point (authenticator) address
+    pub ap: [u8; 6],
+    /// Client station (supplicant) address
+    pub station: [u8; 6],
+    /// Whether the access point sent the frame
+    pub from_ap: bool,
+    /// EAPOL PDU, starting at the protocol version
+    pub payload: &'a [u8],
+}
+
+impl<'a> EapolFrame<'a> {
+    /// Extract an EAPOL PDU from a decoded frame
+    ///
+    /// 802.11 data frames are matched on their LLC/SNAP header and the AP/station
+    /// roles follow the `ToDS`/`FromDS` bits. Ethernet frames carry no such hint,
+    /// so key frames are oriented by the Key Ack bit instead.
+    #[must_use]
+    pub fn parse(frame: &Frame<'a>) -> Option<Self> {
+        match frame {
+            Frame::Ieee80211 { data, .. } => Self::parse_ieee80211(data),
+            Frame::Ethernet { data } => Self::parse_ethernet(data),
+        }
+    }
+
+    fn parse_ieee80211(data: &'a [u8]) -> Option<Self> {
+        let header = DataHeader::parse(data)?;
+        if header.is_null() || header.frame_control.is_protected() {
+            return None;
+        }
+
+        let (ethertype, payload) = llc_snap_payload(&data[header.body_offset..])?;
+        if ethertype != ETHERTYPE_EAPOL {
+            return None;
+        }
+
+        let (ap, station, from_ap) = match (header.frame_control.is_to_ds(), header.frame_control.is_from_ds()) {
+            (true, false) => (header.addr1, header.addr2, false),
+            (false, true) => (header.addr2, header.addr1, true),
+            _ => return None,
+        };
+
+        Some(Self {
+            ap,
+            station,
+            from_ap,
+            payload: trim_to_body_length(payload)?,
+        })
+    }
+
+    fn parse_ethernet(data: &'a [u8]) -> Option<Self> {
+        let destination = mac_at(data, 0)?;
+        let source = mac_at(data, 6)?;
+        let ethertype = u16::from_be_bytes([*data.get(12)?, *data.get(13)?]);
+        if ethertype != ETHERTYPE_EAPOL {
+            return None;
+        }
+
+        let payload = trim_to_body_length(&data[14..])?;
+        let from_ap = payload.get(1) == Some(&EAPOL_KEY)
+            && payload.get(5..7).is_some_and(|info| u16::from_be_bytes([info[0], info[1]]) & KEY_INFO_ACK != 0);
+        let (ap, station) = if from_ap { (source, destination) } else { (destination, source) };
+
+        Some(Self {
+            ap,
+            station,
+            from_ap,
+            payload,
+        })
+    }
+
+    /// EAPOL packet type (0 = EAP, 1 = Start, 3 = Key, ...)
+    #[must_use]
+    pub fn packet_type(&self) -> u8 {
+        self.payload[1]
+    }
+
+    /// Packet body following the 4-byte EAPOL header
+    #[must_use]
+    pub fn body(&self) -> &'a [u8] {
+        &self.payload[4..]
+    }
+}
+
+/// Cut an EAPOL PDU to the length in its header, dropping link-layer padding
+fn trim_to_body_length(payload: &[u8]) -> Option<&[u8]> {
+    let body_length = usize::from(u16::from_be_bytes([*payload.get(2)?, *payload.get(3)?]));
+    payload.get(..4 + body_length)
+}
+
+/// Key data encapsulation carried in the key data of an EAPOL-Key frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kde<'a> {
+    /// Group temporal key
+    Gtk(&'a [u8]),
+    MacAddress([u8; 6]),
+    Pmkid([u8; 16]),
+    /// Integrity group temporal key (management frame protection)
+    Igtk(&'a [u8]),
+    /// Beacon integrity group temporal key
+    Bigtk(&'a [u8]),
+}
+
+impl<'a> Kde<'a> {
+    /// Decode the payload of a 0xDD element as an IEEE 802.11 KDE
+    ///
+    /// Returns `None` for vendor KDEs and KDE types we do not use.
+    fn parse(payload: &'a [u8]) -> Option<Self> {
+        if payload.get(..3)? != IEEE80211_OUI {
+            return None;
+        }
+        let data_type = *payload.get(3)?;
+        let data = &payload[4..];
+
+        // GTK: key ID/Tx (1), reserved (1); IGTK and BIGTK: key ID (2), packet number (6)
+        let kde = match data_type {
+            KDE_GTK => Self::Gtk(data.get(2..).filter(|key| !key.is_empty())?),
+            KDE_MAC_ADDRESS => Self::MacAddress(mac_at(data, 0)?),
+            KDE_PMKID => Self::Pmkid(data.get(..16)?.try_into().ok()?),
+            KDE_IGTK => Self::Igtk(data.get(8..).filter(|key| !key.is_empty())?),
+            KDE_BIGTK => Self::Bigtk(data.get(8..).filter(|key| !key.is_empty())?),
+            _ => return None,
+        };
+        Some(kde)
+    }
+}
+
+/// Key Information field of an EAPOL-Key frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyInformation(pub u16);
+
+impl KeyInformation {
+    /// Key descriptor version: 1 = HMAC-MD5/RC4, 2 = HMAC-SHA1/AES, 3 = AES-CMAC, 0 = AKM defined
+    #[must_use]
+    pub fn version(self) -> u8 {
+        self.0.to_le_bytes()[0] & 0x07
+    }
+
+    #[must_use]
+    pub fn is_pairwise(self) -> bool {
+        self.0 & 0x0008 != 0
+    }
+
+    #[must_use]
+    pub fn install(self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    #[must_use]
+    pub fn ack(self) -> bool {
+        self.0 & KEY_INFO_ACK != 0
+    }
+
+    #[must_use]
+    pub fn mic(self) -> bool {
+        self.0 & 0x0100 != 0
+    }
+
+    #[must_use]
+    pub fn secure(self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+
+    #[must_use]
+    pub fn error(self) -> bool {
+        self.0 & 0x0400 != 0
+    }
+
+    #[must_use]
+    pub fn request(self) -> bool {
+        self.0 & 0x0800 != 0
+    }
+
+    #[must_use]
+    pub fn encrypted_key_data(self) -> bool {
+        self.0 & 0x1000 != 0
+    }
+}
+
+/// Position of an EAPOL-Key frame in the 4-way or group key handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMessage {
+    M1,
+    M2,
+    M3,
+    M4,
+    Group,
+}
+
+impl KeyMessage {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::M1 => "M1",
+            Self::M2 => "M2",
+            Self::M3 => "M3",
+            Self::M4 => "M4",
+            Self::Group => "group",
+        }
+    }
+}
+
+/// Decoded EAPOL-Key frame
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct EapolKey {
+    /// Access point (authenticator) address
+    #[pyo3(get)]
+    pub ap: String,
+    /// Client station (supplicant) address
+    #[pyo3(get)]
+    pub station: String,
+    /// Whether the access point sent the frame
+    #[pyo3(get)]
+    pub from_ap: bool,
+    /// Descriptor type: 2 for RSN, 254 for legacy WPA
+    #[pyo3(get)]
+    pub descriptor_type: u8,
+    /// Raw Key Information field
+    #[pyo3(get)]
+    pub key_info: u16,
+    #[pyo3(get)]
+    pub key_length: u16,
+    #[pyo3(get)]
+    pub replay_counter: u64,
+    #[pyo3(get)]
+    pub nonce: Vec<u8>,
+    #[pyo3(get)]
+    pub iv: Vec<u8>,
+    #[pyo3(get)]
+    pub rsc: Vec<u8>,
+    /// MIC, 16 or 24 bytes depending on the AKM (empty for FILS)
+    #[pyo3(get)]
+    pub mic: Vec<u8>,
+    #[pyo3(get)]
+    pub key_data: Vec<u8>,
+    /// Complete EAPOL PDU, header included
+    #[pyo3(get)]
+    pub eapol: Vec<u8>,
+}
+
+impl EapolKey {
+    /// Decode the EAPOL-Key frame carried by an EAPOL PDU
+    ///
+    /// `mic_length` comes from the AKM of the network; when it is unknown, the
+    /// MIC lengths in use are tried until the key data length fits the frame.
+    #[must_use]
+    pub fn parse(eapol: &EapolFrame, mic_length: Option<usize>) -> Option<Self> {
+        if eapol.packet_type() != EAPOL_KEY {
+            return None;
+        }
+        let body = eapol.body();
+        if !matches!(body.first(), Some(&(DESCRIPTOR_RSN | DESCRIPTOR_WPA))) {
+            return None;
+        }
+
+        let (mic, key_data) = match mic_length {
+            Some(mic_length) => Self::split_mic(body, mic_length)?,
+            None => MIC_LENGTHS.iter().find_map(|mic_length| Self::split_mic(body, *mic_length))?,
+        };
+
+        Some(Self {
+            ap: format_mac(eapol.ap),
+            station: format_mac(eapol.station),
+            from_ap: eapol.from_ap,
+            descriptor_type: body[0],
+            key_info: u16::from_be_bytes([body[1], body[2]]),
+            key_length: u16::from_be_bytes([body[3], body[4]]),
+            replay_counter: u64::from_be_bytes(body[5..13].try_into().ok()?),
+            nonce: body[13..45].to_vec(),
+            iv: body[45..61].to_vec(),
+            rsc: body[61..69].to_vec(),
+            mic: mic.to_vec(),
+            key_data: key_data.to_vec(),
+            eapol: eapol.payload.to_vec(),
+        })
+    }
+
+    /// Split the MIC and key data, checking that the key data length matches the frame exactly
+    fn split_mic(body: &[u8], mic_length: usize) -> Option<(&[u8], &[u8])> {
+        let length_offset = KEY_FIXED_LEN + mic_length;
+        let key_data_length = usize::from(u16::from_be_bytes([*body.get(length_offset)?, *body.get(length_offset + 1)?]));
+        let key_data = &body[length_offset + 2..];
+        (key_data.len() == key_data_length).then(|| (&body[KEY_FIXED_LEN..length_offset], key_data))
+    }
+
+    /// EAPOL PDU with the MIC zeroed, as covered by the MIC computation
+    #[must_use]
+    pub fn eapol_without_mic(&self) -> Vec<u8> {
+        let mut eapol = self.eapol.clone();
+        let mic_offset = 4 + KEY_FIXED_LEN;
+        eapol[mic_offset..mic_offset + self.mic.len()].fill(0);
+        eapol
+    }
+
+    #[must_use]
+    pub fn info(&self) -> KeyInformation {
+        KeyInformation(self.key_info)
+    }
+
+    /// Classify the frame within the 4-way or group key handshake
+    #[must_use]
+    pub fn classify(&self) -> KeyMessage {
+        let info = self.info();
+        if !info.is_pairwise() {
+            return KeyMessage::Group;
+        }
+
+        match (info.ack(), info.mic()) {
+            (true, false) => KeyMessage::M1,
+            (true, true) => KeyMessage::M3,
+            // M2 always carries the station's RSN or WPA element, while M4 has no key data. The
+            // Secure bit cannot tell them apart: legacy WPA leaves it clear in M4 and PTK rekeys set it in M2.
+            _ if self.key_data.is_empty() => KeyMessage::M4,
+            _ => KeyMessage::M2,
+        }
+    }
+
+    /// KDEs in the key data; empty when the key data is encrypted
+    #[must_use]
+    pub fn kdes(&self) -> Vec<Kde<'_>> {
+        if self.info().encrypted_key_data() {
+            return Vec::new();
+        }
+
+        InformationElements::new(&self.key_data)
+            // A 0xDD element without payload starts the padding at the end of the key data
+            .take_while(|(element_id, payload)| *element_id != KDE_ELEMENT_ID || !payload.is_empty())
+            .filter(|(element_id, _)| *element_id == KDE_ELEMENT_ID)
+            .filter_map(|(_, payload)| Kde::parse(payload))
+            .collect()
+    }
+
+    /// PMKID from the PMKID KDE, ignoring the all-zero PMKIDs some access points send
+    #[must_use]
+    pub fn pmkid_kde(&self) -> Option<[u8; 16]> {
+        self.kdes().into_iter().find_map(|kde| match kde {
+            Kde::Pmkid(pmkid) if pmkid != [0; 16] => Some(pmkid),
+            _ => None,
+        })
+    }
+
+    /// First non-zero PMKID listed in an RSN element in the key data, as a station sends in M2
+    #[must_use]
+    pub fn rsn_pmkid(&self) -> Option<[u8; 16]> {
+        if self.info().encrypted_key_data() {
+            return None;
+        }
+
+        InformationElements::new(&self.key_data)
+            .find(|(element_id, _)| *element_id == 48)
+            .and_then(|(_, payload)| RsnElement::parse(payload))
+            .and_then(|rsn| rsn.pmkids.into_iter().find(|pmkid| *pmkid != [0; 16]))
+    }
+
+    /// Key carried by the first KDE `select` accepts
+    fn key_from_kde(&self, select: impl Fn(Kde<'_>) -> Option<&[u8]>) -> Option<Vec<u8>> {
+        self.kdes().into_iter().find_map(select).map(<[u8]>::to_vec)
+    }
+}
+
+#[pymethods]
+impl EapolKey {
+    /// Key descriptor version from the Key Information field
+    #[getter]
+    #[must_use]
+    pub fn key_version(&self) -> u8 {
+        self.info().version()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn pairwise(&self) -> bool {
+        self.info().is_pairwise()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn install(&self) -> bool {
+        self.info().install()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn ack(&self) -> bool {
+        self.info().ack()
+    }
+
+    /// Whether the MIC bit is set in the Key Information field
+    #[getter]
+    #[must_use]
+    pub fn has_mic(&self) -> bool {
+        self.info().mic()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn secure(&self) -> bool {
+        self.info().secure()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn error(&self) -> bool {
+        self.info().error()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn request(&self) -> bool {
+        self.info().request()
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn encrypted_key_data(&self) -> bool {
+        self.info().encrypted_key_data()
+    }
+
+    /// Handshake message: "M1" to "M4", or "group" for group key frames
+    #[getter]
+    #[must_use]
+    pub fn message(&self) -> &'static str {
+        self.classify().as_str()
+    }
+
+    /// PMKID from the PMKID KDE, if present and non-zero
+    #[getter]
+    #[must_use]
+    pub fn pmkid(&self) -> Option<Vec<u8>> {
+        self.pmkid_kde().map(|pmkid| pmkid.to_vec())
+    }
+
+    /// GTK from the GTK KDE of unencrypted key data
+    #[getter]
+    #[must_use]
+    pub fn gtk(&self) -> Option<Vec<u8>> {
+        self.key_from_kde(|kde| if let Kde::Gtk(key) = kde { Some(key) } else { None })
+    }
+
+    /// IGTK from the IGTK KDE of unencrypted key data
+    #[getter]
+    #[must_use]
+    pub fn igtk(&self) -> Option<Vec<u8>> {
+        self.key_from_kde(|kde| if let Kde::Igtk(key) = kde { Some(key) } else { None })
+    }
+
+    /// BIGTK from the BIGTK KDE of unencrypted key data
+    #[getter]
+    #[must_use]
+    pub fn bigtk(&self) -> Option<Vec<u8>> {
+        self.key_from_kde(|kde| if let Kde::Bigtk(key) = kde { Some(key) } else { None })
+    }
+
+    /// Address from the MAC address KDE
+    #[getter]
+    #[must_use]
+    pub fn kde_mac_address(&self) -> Option<String> {
+        self.kdes().into_iter().find_map(|kde| match kde {
+            Kde::MacAddress(mac) => Some(format_mac(mac)),
+            _ => None,
+        })
+    }
+
+    /// Get a summary of the key frame
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!(
+            "EAPOL-Key {}: {} -> {} (replay counter {})",
+            self.message(),
+            if self.from_ap { &self.ap } else { &self.station },
+            if self.from_ap { &self.station } else { &self.ap },
+            self.replay_counter
+        )
+    }
+}
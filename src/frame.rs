@@ -0,0 +1,284 @@
+use pcap::Linktype;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::PyErr;
+use thiserror::Error;
+use crate::radiotap::{RadiotapHeader, FLAG_BAD_FCS, FLAG_FCS};
+
+pyo3::create_exception!(
+    _wifite3,
+    UnsupportedLinkTypeError,
+    PyRuntimeError,
+    "Raised when a capture uses a link type the frame dispatcher cannot decode."
+);
+
+/// Errors raised while setting up frame decoding for a capture
+#[derive(Debug, Error)]
+pub enum FrameError {
+    #[error(
+        "Unsupported capture link type {0}; expected radiotap (127), raw 802.11 (105), \
+         PPI (192), Prism (119), AVS (163) or Ethernet (1)"
+    )]
+    UnsupportedLinkType(i32),
+}
+
+impl From<FrameError> for PyErr {
+    fn from(error: FrameError) -> Self {
+        match error {
+            FrameError::UnsupportedLinkType(_) => UnsupportedLinkTypeError::new_err(error.to_string()),
+        }
+    }
+}
+
+/// Link-layer encapsulations the dispatcher knows how to strip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkLayer {
+    /// DLT 127: radiotap header followed by an 802.11 frame
+    Radiotap,
+    /// DLT 105: bare 802.11 frame
+    Ieee80211,
+    /// DLT 192: Per-Packet Information header followed by an 802.11 frame
+    Ppi,
+    /// DLT 119: Prism monitor header (or AVS, which some drivers emit under this DLT)
+    Prism,
+    /// DLT 163: AVS monitor header followed by an 802.11 frame
+    Avs,
+    /// DLT 1: Ethernet II frame
+    Ethernet,
+}
+
+impl TryFrom<Linktype> for LinkLayer {
+    type Error = FrameError;
+
+    fn try_from(linktype: Linktype) -> Result<Self, Self::Error> {
+        match linktype {
+            Linktype::IEEE802_11_RADIOTAP => Ok(Self::Radiotap),
+            Linktype::IEEE802_11 => Ok(Self::Ieee80211),
+            Linktype::PPI => Ok(Self::Ppi),
+            Linktype::IEEE802_11_PRISM => Ok(Self::Prism),
+            Linktype::IEEE802_11_AVS => Ok(Self::Avs),
+            Linktype::ETHERNET => Ok(Self::Ethernet),
+            Linktype(other) => Err(FrameError::UnsupportedLinkType(other)),
+        }
+    }
+}
+
+/// A captured frame with its link-layer pseudo-header removed
+#[derive(Debug, Clone)]
+pub enum Frame<'a> {
+    /// 802.11 frame without FCS, plus any radio metadata from the pseudo-header
+    ///
+    /// PPI, Prism and AVS metadata is normalized into the radiotap representation.
+    Ieee80211 {
+        data: &'a [u8],
+        radio: Option<RadiotapHeader>,
+    },
+    /// Ethernet II frame
+    Ethernet { data: &'a [u8] },
+}
+
+/// Dispatcher that normalizes packets of one capture link type into frames
+#[derive(Debug, Clone, Copy)]
+pub struct FrameDecoder {
+    link: LinkLayer,
+}
+
+impl FrameDecoder {
+    /// Create a decoder for a capture's link type
+    ///
+    /// # Errors
+    ///
+    /// Returns `FrameError::UnsupportedLinkType` for link types without a decoder.
+    pub fn new(linktype: Linktype) -> Result<Self, FrameError> {
+        Ok(Self {
+            link: LinkLayer::try_from(linktype)?,
+        })
+    }
+
+    /// Strip the pseudo-header from a packet
+    ///
+    /// Returns `None` for truncated packets and for 802.11 frames that failed the FCS check.
+    #[must_use]
+    pub fn decode(self, packet: &[u8]) -> Option<Frame<'_>> {
+        let (data, radio) = match self.link {
+            LinkLayer::Ethernet => return Some(Frame::Ethernet { data: packet }),
+            LinkLayer::Ieee80211 => (packet, None),
+            LinkLayer::Radiotap => {
+                let radiotap = RadiotapHeader::parse(packet)?;
+                (&packet[radiotap.length..], Some(radiotap))
+            }
+            LinkLayer::Ppi => {
+                let radio = Self::parse_ppi(packet)?;
+                (&packet[radio.length..], Some(radio))
+            }
+            LinkLayer::Prism if Self::is_avs(packet) => {
+                let radio = Self::parse_avs(packet)?;
+                (&packet[radio.length..], Some(radio))
+            }
+            LinkLayer::Prism => {
+                let radio = Self::parse_prism(packet)?;
+                (&packet[radio.length..], Some(radio))
+            }
+            LinkLayer::Avs => {
+                let radio = Self::parse_avs(packet)?;
+                (&packet[radio.length..], Some(radio))
+            }
+        };
+
+        // Frames that failed the FCS check cannot be trusted
+        if radio.as_ref().is_some_and(RadiotapHeader::bad_fcs) {
+            return None;
+        }
+
+        // Drop the trailing FCS so it is not mistaken for frame body
+        let data = match &radio {
+            Some(radio) if radio.has_fcs() => &data[..data.len().checked_sub(4)?],
+            _ => data,
+        };
+
+        Some(Frame::Ieee80211 { data, radio })
+    }
+
+    /// Parse a PPI header, extracting the 802.11-Common field
+    fn parse_ppi(packet: &[u8]) -> Option<RadiotapHeader> {
+        if packet.len() < 8 || packet[0] != 0 {
+            return None;
+        }
+
+        let aligned = packet[1] & 0x01 != 0;
+        let length = usize::from(u16::from_le_bytes([packet[2], packet[3]]));
+        let dlt = i32::from_le_bytes(packet[4..8].try_into().ok()?);
+        if length < 8 || length > packet.len() || Linktype(dlt) != Linktype::IEEE802_11 {
+            return None;
+        }
+
+        let mut radio = RadiotapHeader {
+            length,
+            ..RadiotapHeader::default()
+        };
+
+        let mut offset = 8;
+        while offset + 4 <= length {
+            let field_type = u16::from_le_bytes([packet[offset], packet[offset + 1]]);
+            let field_len = usize::from(u16::from_le_bytes([packet[offset + 2], packet[offset + 3]]));
+            let field = packet.get(offset + 4..offset + 4 + field_len)?;
+
+            // 802.11-Common: TSF (8), flags (2), rate (2), frequency (2), channel flags (2),
+            // FHSS hopset (1), FHSS pattern (1), dBm signal (1), dBm noise (1)
+            if field_type == 2 && field.len() >= 20 {
+                let flags = u16::from_le_bytes([field[8], field[9]]);
+                let mut radiotap_flags = 0;
+                if flags & 0x0001 != 0 {
+                    radiotap_flags |= FLAG_FCS;
+                }
+                if flags & 0x0004 != 0 {
+                    radiotap_flags |= FLAG_BAD_FCS;
+                }
+
+                radio.tsft = Some(u64::from_le_bytes(field[0..8].try_into().ok()?));
+                radio.flags = Some(radiotap_flags);
+                radio.rate = u8::try_from(u16::from_le_bytes([field[10], field[11]])).ok();
+                radio.channel_frequency = Some(u16::from_le_bytes([field[12], field[13]])).filter(|f| *f != 0);
+                radio.channel_flags = Some(u16::from_le_bytes([field[14], field[15]]));
+                radio.antenna_signal = Some(i8::from_le_bytes([field[18]])).filter(|s| *s != -128);
+                radio.antenna_noise = Some(i8::from_le_bytes([field[19]])).filter(|s| *s != -128);
+            }
+
+            offset += 4 + field_len;
+            if aligned {
+                offset = offset.div_ceil(4) * 4;
+            }
+        }
+
+        Some(radio)
+    }
+
+    /// Parse a Prism monitor header (144 bytes of DID items, little-endian on the hosts we support)
+    fn parse_prism(packet: &[u8]) -> Option<RadiotapHeader> {
+        const ITEMS_START: usize = 24; // msgcode (4), msglen (4), devname (16)
+        const CHANNEL: usize = 2;
+        const SIGNAL: usize = 5;
+        const NOISE: usize = 6;
+        const RATE: usize = 7;
+
+        if packet.len() < 8 {
+            return None;
+        }
+
+        let length = usize::try_from(u32::from_le_bytes(packet[4..8].try_into().ok()?)).ok()?;
+        if length < ITEMS_START || length > packet.len() {
+            return None;
+        }
+
+        // Each item is did (4), status (2), len (2), data (4); status 0 means a value is present
+        let item = |index: usize| -> Option<u32> {
+            let start = ITEMS_START + index * 12;
+            let bytes = packet.get(start..start + 12).filter(|_| start + 12 <= length)?;
+            let status = u16::from_le_bytes([bytes[4], bytes[5]]);
+            (status == 0).then(|| u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]))
+        };
+
+        let dbm = |value: u32| i8::try_from(value.cast_signed()).ok();
+
+        Some(RadiotapHeader {
+            length,
+            rate: item(RATE).and_then(|rate| u8::try_from(rate).ok()),
+            channel_frequency: item(CHANNEL)
+                .and_then(|channel| u8::try_from(channel).ok())
+                .and_then(channel_to_frequency),
+            antenna_signal: item(SIGNAL).and_then(dbm),
+            antenna_noise: item(NOISE).and_then(dbm),
+            ..RadiotapHeader::default()
+        })
+    }
+
+    /// Whether a packet starts with an AVS capture header (version 1 or 2)
+    fn is_avs(packet: &[u8]) -> bool {
+        packet.len() >= 4 && u32::from_be_bytes([packet[0], packet[1], packet[2], packet[3]]) & 0xFFFF_FFF0 == 0x8021_1000
+    }
+
+    /// Parse an AVS capture header (big-endian)
+    fn parse_avs(packet: &[u8]) -> Option<RadiotapHeader> {
+        const SSI_TYPE_DBM: u32 = 2;
+
+        if !Self::is_avs(packet) || packet.len() < 64 {
+            return None;
+        }
+
+        let word = |offset: usize| u32::from_be_bytes([packet[offset], packet[offset + 1], packet[offset + 2], packet[offset + 3]]);
+
+        let length = usize::try_from(word(4)).ok()?;
+        if length < 64 || length > packet.len() {
+            return None;
+        }
+
+        let dbm = |value: u32| i8::try_from(value.cast_signed()).ok();
+        let (signal, noise) = if word(44) == SSI_TYPE_DBM {
+            (dbm(word(48)), dbm(word(52)))
+        } else {
+            (None, None)
+        };
+
+        Some(RadiotapHeader {
+            length,
+            tsft: Some(u64::from_be_bytes(packet[8..16].try_into().ok()?)),
+            // AVS reports the data rate in 100 kbps units
+            rate: u8::try_from(word(32) / 5).ok(),
+            channel_frequency: u8::try_from(word(28)).ok().and_then(channel_to_frequency),
+            antenna_signal: signal,
+            antenna_noise: noise,
+            antenna: u8::try_from(word(36)).ok(),
+            ..RadiotapHeader::default()
+        })
+    }
+}
+
+/// Convert a channel number to its centre frequency, assuming 2.4 GHz for 1-14 and 5 GHz otherwise
+fn channel_to_frequency(channel: u8) -> Option<u16> {
+    let frequency = match channel {
+        14 => 2484,
+        1..=13 => 2407 + 5 * u16::from(channel),
+        15..=196 => 5000 + 5 * u16::from(channel),
+        _ => return None,
+    };
+    Some(frequency)
+}
@@ -0,0 +1,260 @@
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use crate::eapol::{EapolKey, KeyMessage};
+use crate::crypto::ByteString;
+use crate::hashline::{parse_mac, HashKind, HashLine, HashLineError};
+use crate::utils::to_hex;
+
+/// Default time allowed between two messages of the same handshake, in seconds
+pub const DEFAULT_HANDSHAKE_WINDOW: f64 = 5.0;
+
+/// Message pair byte of a hashcat 22000 `WPA*02*` line: which two messages were
+/// combined and which of them supplied the EAPOL frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessagePair {
+    /// AP nonce from M1, EAPOL from M2 (challenge)
+    M1M2 = 0,
+    /// AP nonce from M3, EAPOL from M2 (authorized)
+    M2M3 = 2,
+    /// AP nonce from M3, EAPOL from M4 (authorized)
+    M3M4 = 5,
+}
+
+/// 4-way handshake capture ready for offline cracking
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct HandshakeCapture {
+    #[pyo3(get)]
+    pub ssid: String,
+    #[pyo3(get)]
+    pub bssid: String,
+    #[pyo3(get)]
+    pub client_mac: String,
+    #[pyo3(get)]
+    pub anonce: String,
+    #[pyo3(get)]
+    pub mic: String,
+    /// Station's EAPOL frame with the MIC zeroed, hex encoded
+    #[pyo3(get)]
+    pub eapol: String,
+    /// hashcat message pair byte (0 = M1+M2, 2 = M2+M3, 5 = M3+M4)
+    #[pyo3(get)]
+    pub message_pair: u8,
+    #[pyo3(get)]
+    pub replay_counter: u64,
+    /// Key descriptor version: 1 = HMAC-MD5, 2 = HMAC-SHA1, 3 = AES-CMAC
+    #[pyo3(get)]
+    pub key_version: u8,
+    /// Capture timestamp of the frame that completed the pair (seconds since the epoch);
+    /// unknown for handshakes loaded from a hash file
+    #[pyo3(get)]
+    pub timestamp: Option<f64>,
+    #[pyo3(get)]
+    pub hashcat_format: String,
+}
+
+/// Handshake message pair found by the tracker, before the SSID is known
+#[derive(Debug, Clone)]
+pub struct HandshakeData {
+    pub bssid: String,
+    pub client_mac: String,
+    pub anonce: Vec<u8>,
+    pub mic: Vec<u8>,
+    pub eapol: Vec<u8>,
+    pub message_pair: MessagePair,
+    pub replay_counter: u64,
+    pub key_version: u8,
+    pub timestamp: f64,
+}
+
+#[pymethods]
+impl HandshakeCapture {
+    /// Get a summary of the handshake capture
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!(
+            "Handshake: {} -> {} (Client: {}, message pair {})",
+            self.ssid, self.bssid, self.client_mac, self.message_pair
+        )
+    }
+
+    /// Get the hashcat 22000 line for this capture
+    #[must_use]
+    pub fn get_hashcat_format(&self) -> String {
+        self.hashcat_format.clone()
+    }
+
+    /// Decode this capture's hash line
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the hash line is invalid.
+    pub fn to_hash_line(&self) -> PyResult<HashLine> {
+        Ok(self.hashcat_format.parse()?)
+    }
+
+    /// Check a passphrase, or a raw 32-byte PMK, by recomputing the EAPOL MIC
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` unless exactly one of `passphrase` and `pmk` is given, or
+    /// if the PMK is not 32 bytes.
+    #[pyo3(signature = (passphrase=None, pmk=None))]
+    pub fn verify(&self, passphrase: Option<ByteString>, pmk: Option<&[u8]>) -> PyResult<bool> {
+        self.to_hash_line()?.verify(passphrase, pmk)
+    }
+}
+
+impl HandshakeCapture {
+    /// Create a capture from a message pair found by the tracker
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fields cannot form a valid `WPA*02*` line, e.g. the
+    /// SSID is longer than 32 bytes.
+    pub fn from_data(essid: &[u8], data: HandshakeData) -> Result<Self, HashLineError> {
+        let message_pair = data.message_pair as u8;
+        let line = HashLine::eapol(
+            &data.mic,
+            parse_mac(&data.bssid)?,
+            parse_mac(&data.client_mac)?,
+            essid,
+            &data.anonce,
+            &data.eapol,
+            message_pair,
+        )?;
+
+        Ok(Self {
+            ssid: line.essid_text(),
+            bssid: data.bssid,
+            client_mac: data.client_mac,
+            anonce: to_hex(&data.anonce),
+            mic: to_hex(&data.mic),
+            eapol: to_hex(&data.eapol),
+            message_pair,
+            replay_counter: data.replay_counter,
+            key_version: data.key_version,
+            timestamp: Some(data.timestamp),
+            hashcat_format: line.to_string(),
+        })
+    }
+
+    /// Create a capture from a decoded `WPA*02*` line, or `None` for other hash types
+    #[must_use]
+    pub fn from_line(line: &HashLine) -> Option<Self> {
+        if line.kind != HashKind::Eapol {
+            return None;
+        }
+
+        Some(Self {
+            ssid: line.essid_text(),
+            bssid: line.bssid(),
+            client_mac: line.client_mac(),
+            anonce: to_hex(&line.anonce),
+            mic: to_hex(&line.hash),
+            eapol: to_hex(&line.eapol),
+            message_pair: line.message_pair?,
+            replay_counter: line.replay_counter()?,
+            key_version: line.key_info()?.version(),
+            timestamp: None,
+            hashcat_format: line.to_string(),
+        })
+    }
+}
+
+/// Latest key frames of one AP/station link
+#[derive(Default)]
+struct HandshakeState {
+    m1: Option<(EapolKey, f64)>,
+    m2: Option<(EapolKey, f64)>,
+    m3: Option<(EapolKey, f64)>,
+}
+
+/// Per-(AP, station) 4-way handshake state machine
+///
+/// Messages are paired when their replay counters line up (M2 echoes M1, M3
+/// increments it, M4 echoes M3) and they arrive within the time window.
+pub struct HandshakeTracker {
+    window: f64,
+    links: HashMap<(String, String), HandshakeState>,
+    found: Vec<HandshakeData>,
+}
+
+impl HandshakeTracker {
+    pub fn new(window: f64) -> Self {
+        Self {
+            window,
+            links: HashMap::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Feed an EAPOL-Key frame captured at `timestamp`
+    pub fn add_key(&mut self, key: EapolKey, timestamp: f64) {
+        // hashcat mode 22000 covers the 16-byte MIC key versions only
+        if !(1..=3).contains(&key.info().version()) || key.mic.len() != 16 {
+            return;
+        }
+
+        let window = self.window;
+        let within = |earlier: &Option<(EapolKey, f64)>, replay_counter: u64| {
+            earlier
+                .as_ref()
+                .filter(|(earlier, seen)| earlier.replay_counter == replay_counter && (timestamp - seen).abs() <= window)
+                .map(|(earlier, _)| earlier.clone())
+        };
+
+        let state = self.links.entry((key.ap.clone(), key.station.clone())).or_default();
+        let pair = match key.classify() {
+            KeyMessage::M1 => {
+                state.m1 = Some((key, timestamp));
+                None
+            }
+            KeyMessage::M2 => {
+                let pair = within(&state.m1, key.replay_counter).map(|m1| (MessagePair::M1M2, m1.nonce, key.clone()));
+                state.m2 = Some((key, timestamp));
+                pair
+            }
+            KeyMessage::M3 => {
+                let pair = key
+                    .replay_counter
+                    .checked_sub(1)
+                    .and_then(|replay_counter| within(&state.m2, replay_counter))
+                    .map(|m2| (MessagePair::M2M3, key.nonce.clone(), m2));
+                state.m3 = Some((key, timestamp));
+                pair
+            }
+            // An M4 without SNonce leaves nothing to derive the PTK from
+            KeyMessage::M4 if key.nonce.iter().any(|byte| *byte != 0) => {
+                within(&state.m3, key.replay_counter).map(|m3| (MessagePair::M3M4, m3.nonce, key))
+            }
+            KeyMessage::M4 | KeyMessage::Group => None,
+        };
+
+        if let Some((message_pair, anonce, station_key)) = pair {
+            let data = HandshakeData {
+                bssid: station_key.ap.clone(),
+                client_mac: station_key.station.clone(),
+                eapol: station_key.eapol_without_mic(),
+                mic: station_key.mic.clone(),
+                anonce,
+                message_pair,
+                replay_counter: station_key.replay_counter,
+                key_version: station_key.info().version(),
+                timestamp,
+            };
+
+            // Retransmissions produce the same pair again
+            let duplicate = self.found.iter().any(|found| {
+                found.message_pair == data.message_pair && found.mic == data.mic && found.anonce == data.anonce
+            });
+            if !duplicate {
+                self.found.push(data);
+            }
+        }
+    }
+
+    pub fn get_handshakes(self) -> Vec<HandshakeData> {
+        self.found
+    }
+}
@@ -0,0 +1,493 @@
+use pyo3::exceptions::{PyFileNotFoundError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::env;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{self, Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use crate::cracker::{
+    group_by_essid, python_progress, seconds, CancellationToken, CrackProgress, CrackResult, ProgressCallback,
+    DEFAULT_PROGRESS_INTERVAL,
+};
+use crate::hashfile::HashSource;
+use crate::hashline::HashLine;
+use crate::mask::Mask;
+use crate::potfile::Potfile;
+use crate::rules::RuleSet;
+
+/// Executable run when no path is configured, looked up on `PATH`
+pub const DEFAULT_HASHCAT_BINARY: &str = "hashcat";
+/// hashcat mode for PMKID and EAPOL hash lines
+const HASH_MODE: &str = "22000";
+/// How often cancellation and pending signals are checked while hashcat runs
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// hashcat's exit code once every candidate was tried
+const EXIT_EXHAUSTED: i32 = 1;
+/// Highest exit code of a run that ended normally: 0 all cracked, 1 exhausted,
+/// 2 to 5 aborted by the user, a checkpoint, the runtime limit or `--stop`
+const EXIT_ABORTED_MAX: i32 = 5;
+/// Lines of hashcat's standard output quoted when it fails without an error
+const OUTPUT_CONTEXT_LINES: usize = 5;
+
+/// Job directories created by this process, keeping concurrent runs apart
+static JOBS: AtomicU64 = AtomicU64::new(0);
+
+/// Errors running hashcat
+#[derive(Debug, Error)]
+pub enum HashcatError {
+    #[error("Failed to start {binary}: {source}")]
+    Spawn { binary: String, source: io::Error },
+    #[error("hashcat failed ({status}): {message}")]
+    Failed { status: ExitStatus, message: String },
+}
+
+impl From<HashcatError> for PyErr {
+    fn from(error: HashcatError) -> Self {
+        match error {
+            HashcatError::Spawn { ref source, .. } if source.kind() == io::ErrorKind::NotFound => {
+                PyFileNotFoundError::new_err(error.to_string())
+            }
+            _ => PyRuntimeError::new_err(error.to_string()),
+        }
+    }
+}
+
+/// One line of `--status-json` output; fields not needed are ignored
+#[derive(Debug, Deserialize)]
+struct Status {
+    /// Candidate/salt pairs done and in total
+    progress: [u64; 2],
+    /// Hashes cracked and in total
+    recovered_hashes: [u64; 2],
+    /// Salts cracked and in total; mode 22000 salts by ESSID
+    recovered_salts: [u64; 2],
+    /// Candidate/salt pairs rejected for their length
+    #[serde(default)]
+    rejected: u64,
+    #[serde(default)]
+    devices: Vec<Device>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Device {
+    /// Hashes per second
+    speed: u64,
+}
+
+impl Status {
+    /// Progress counted in candidates, as the built-in engine reports it
+    #[allow(clippy::cast_precision_loss)]
+    fn snapshot(&self, elapsed: Duration) -> CrackProgress {
+        // hashcat counts each candidate once per salt
+        let salts = self.recovered_salts[1].max(1);
+        let [done, total] = self.progress;
+        let speed: u64 = self.devices.iter().map(|device| device.speed).sum();
+        CrackProgress {
+            tested: done / salts,
+            skipped: self.rejected / salts,
+            rate: speed as f64 / salts as f64,
+            percent: (total > 0).then(|| (done as f64 * 100.0 / total as f64).min(100.0)),
+            elapsed: elapsed.as_secs_f64(),
+            found: usize::try_from(self.recovered_hashes[0]).unwrap_or(usize::MAX),
+        }
+    }
+}
+
+/// Directory holding a run's hash file, rule file and hashcat's outfile
+struct Job {
+    dir: PathBuf,
+    temporary: bool,
+}
+
+impl Job {
+    /// Use `workdir`, or a fresh temporary directory removed when the job is dropped
+    fn create(workdir: Option<&str>) -> io::Result<Self> {
+        let job = match workdir {
+            Some(dir) => Self {
+                dir: PathBuf::from(dir),
+                temporary: false,
+            },
+            None => Self {
+                dir: env::temp_dir().join(format!(
+                    "wifite3-hashcat-{}-{}",
+                    process::id(),
+                    JOBS.fetch_add(1, Ordering::Relaxed)
+                )),
+                temporary: true,
+            },
+        };
+        fs::create_dir_all(&job.dir)?;
+        Ok(job)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// How a hashcat process ended
+struct Outcome {
+    status: ExitStatus,
+    last: Option<Status>,
+    killed: bool,
+}
+
+/// Runs hashcat on the targets as a subprocess
+///
+/// The targets are written to a mode 22000 hash file, progress is read from
+/// hashcat's `--status-json` lines and hits from its outfile, so results come
+/// back as the same `CrackProgress` and `CrackResult` as the built-in engine.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct HashcatRunner {
+    targets: Vec<HashLine>,
+    /// hashcat executable
+    #[pyo3(get)]
+    binary: String,
+    /// Arguments passed to hashcat before the hash file, such as `-w 3`
+    #[pyo3(get)]
+    extra_args: Vec<String>,
+    /// Directory keeping the files of the last run, if not a temporary one
+    #[pyo3(get)]
+    workdir: Option<String>,
+}
+
+#[pymethods]
+impl HashcatRunner {
+    /// Prepare hashcat runs against hash lines, captures or line strings
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if a target is malformed or no target is given.
+    #[new]
+    #[pyo3(signature = (targets, binary=DEFAULT_HASHCAT_BINARY.to_string(), extra_args=Vec::new(), workdir=None))]
+    pub fn new(
+        targets: Vec<HashSource>,
+        binary: String,
+        extra_args: Vec<String>,
+        workdir: Option<String>,
+    ) -> PyResult<Self> {
+        let targets = targets
+            .into_iter()
+            .map(HashSource::into_line)
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.is_empty() {
+            return Err(PyValueError::new_err("At least one target is required"));
+        }
+        Ok(Self {
+            targets,
+            binary,
+            extra_args,
+            workdir,
+        })
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn targets(&self) -> Vec<HashLine> {
+        self.targets.clone()
+    }
+
+    /// Run a hashcat dictionary attack with the wordlist
+    ///
+    /// `progress` is called with a `CrackProgress` for every hashcat status,
+    /// requested every `progress_interval` seconds rounded down to at least one,
+    /// and once at the end; `cancel` terminates hashcat. `rules` are written to
+    /// a rule file passed with `-r`.
+    ///
+    /// # Errors
+    ///
+    /// Raises `FileNotFoundError` if hashcat is not found, `RuntimeError` if it
+    /// fails, `ValueError` if `progress_interval` is too large, `OSError` if the
+    /// job files cannot be written, or any exception raised by the progress
+    /// callback or a pending signal.
+    #[pyo3(signature = (
+        wordlist,
+        progress=None,
+        cancel=None,
+        progress_interval=DEFAULT_PROGRESS_INTERVAL,
+        rules=None,
+    ))]
+    pub fn run(
+        &self,
+        py: Python<'_>,
+        wordlist: &str,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+        rules: Option<RuleSet>,
+    ) -> PyResult<CrackResult> {
+        let job = Job::create(self.workdir.as_deref())?;
+        let mut options = vec!["-a".to_string(), "0".to_string()];
+        if let Some(rules) = rules {
+            let path = job.path("rules.rule");
+            fs::write(&path, lines(&rules.rules))?;
+            options.push("-r".to_string());
+            options.push(path.to_string_lossy().into_owned());
+        }
+        self.execute(py, &job, options, wordlist, progress, cancel, progress_interval)
+    }
+
+    /// Run a hashcat brute-force attack with the mask, passing on its custom
+    /// charsets and increment range
+    ///
+    /// Progress and cancellation work as in `run`.
+    ///
+    /// # Errors
+    ///
+    /// Raises the same errors as `run`.
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature = (mask, progress=None, cancel=None, progress_interval=DEFAULT_PROGRESS_INTERVAL))]
+    pub fn run_mask(
+        &self,
+        py: Python<'_>,
+        mask: Mask,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+    ) -> PyResult<CrackResult> {
+        let job = Job::create(self.workdir.as_deref())?;
+        let mut options = vec!["-a".to_string(), "3".to_string()];
+        for (index, charset) in mask.custom_charsets().iter().enumerate() {
+            options.push(format!("-{}", index + 1));
+            options.push(charset.clone());
+        }
+        if let (Some(min), Some(max)) = (mask.lengths().first(), mask.lengths().last()) {
+            if *min < mask.length() {
+                options.extend([
+                    "--increment".to_string(),
+                    format!("--increment-min={min}"),
+                    format!("--increment-max={max}"),
+                ]);
+            }
+        }
+        self.execute(py, &job, options, mask.text(), progress, cancel, progress_interval)
+    }
+}
+
+impl HashcatRunner {
+    /// Full hashcat command line, binary first
+    fn arguments(&self, options: &[String], input: &str, hash_file: &str, outfile: &str, timer: u64) -> Vec<String> {
+        let mut arguments: Vec<String> = [
+            self.binary.as_str(),
+            "-m",
+            HASH_MODE,
+            "--quiet",
+            "--status",
+            "--status-json",
+            "--potfile-disable",
+            "--restore-disable",
+            "--logfile-disable",
+            "--outfile-format=1,2",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        arguments.push(format!("--status-timer={timer}"));
+        arguments.push(format!("--outfile={outfile}"));
+        arguments.extend(options.iter().cloned());
+        arguments.extend(self.extra_args.iter().cloned());
+        arguments.push(hash_file.to_string());
+        arguments.push(input.to_string());
+        arguments
+    }
+
+    /// Write the hash file, run hashcat with the GIL released and collect its hits
+    #[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        job: &Job,
+        options: Vec<String>,
+        input: &str,
+        progress: Option<Py<PyAny>>,
+        cancel: Option<CancellationToken>,
+        progress_interval: f64,
+    ) -> PyResult<CrackResult> {
+        // The status timer counts whole seconds
+        let timer = seconds(progress_interval)?.as_secs().max(1);
+        let hash_file = job.path("hashes.22000");
+        fs::write(&hash_file, lines(&self.targets))?;
+        // hashcat appends to an existing outfile, which a kept workdir may hold
+        let outfile = job.path("cracked.out");
+        if let Err(error) = fs::remove_file(&outfile) {
+            if error.kind() != io::ErrorKind::NotFound {
+                return Err(error.into());
+            }
+        }
+
+        let arguments = self.arguments(
+            &options,
+            input,
+            &hash_file.to_string_lossy(),
+            &outfile.to_string_lossy(),
+            timer,
+        );
+        let child = Command::new(&arguments[0])
+            .args(&arguments[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|source| HashcatError::Spawn {
+                binary: self.binary.clone(),
+                source,
+            })?;
+        let cancel = cancel.unwrap_or_default();
+
+        py.detach(|| {
+            let started = Instant::now();
+            let mut report = python_progress(progress.as_ref());
+            let outcome = supervise(child, &cancel, &mut report, started)?;
+            let exhausted = outcome.status.code() == Some(EXIT_EXHAUSTED);
+
+            let hits = Potfile::open(&outfile.to_string_lossy())?;
+            let found: Vec<_> = self.targets.iter().filter_map(|target| hits.find(target)).collect();
+
+            let mut last = outcome.last.as_ref().map_or_else(
+                || CrackProgress {
+                    tested: 0,
+                    skipped: 0,
+                    rate: 0.0,
+                    percent: None,
+                    elapsed: 0.0,
+                    found: 0,
+                },
+                |status| status.snapshot(started.elapsed()),
+            );
+            last.elapsed = started.elapsed().as_secs_f64();
+            last.found = found.len();
+            report(&last)?;
+
+            // hashcat runs PBKDF2 once per candidate and salt, and salts by ESSID
+            let pmks_computed = outcome.last.as_ref().map_or(0, |status| status.progress[0]);
+            let targets_checked = last.tested * self.targets.len() as u64;
+            #[allow(clippy::cast_precision_loss)]
+            let pmk_reuse_factor = if pmks_computed > 0 {
+                targets_checked as f64 / pmks_computed as f64
+            } else {
+                1.0
+            };
+
+            Ok(CrackResult {
+                found,
+                tested: last.tested,
+                skipped: last.skipped,
+                elapsed: last.elapsed,
+                rate: last.rate,
+                cancelled: outcome.killed,
+                exhausted,
+                resumed: false,
+                essid_groups: group_by_essid(&self.targets).len(),
+                pmks_computed,
+                targets_checked,
+                pmk_reuse_factor,
+            })
+        })
+    }
+}
+
+/// One item per line, each terminated by a newline
+fn lines<T: fmt::Display>(items: &[T]) -> String {
+    items.iter().fold(String::new(), |mut text, item| {
+        let _ = writeln!(text, "{item}");
+        text
+    })
+}
+
+/// Report hashcat's status lines until it exits, killing it on cancellation or
+/// when reporting fails
+fn supervise(
+    mut child: Child,
+    cancel: &CancellationToken,
+    progress: &mut ProgressCallback,
+    started: Instant,
+) -> PyResult<Outcome> {
+    let (Some(stdout), Some(mut stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(io::Error::other("hashcat output is not captured").into());
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let output = thread::spawn(move || {
+        let mut other = Vec::new();
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            match serde_json::from_str::<Status>(&line) {
+                Ok(status) => {
+                    let _ = sender.send(status);
+                }
+                Err(_) if !line.trim().is_empty() => other.push(line),
+                Err(_) => {}
+            }
+        }
+        other
+    });
+    let errors = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+
+    let mut last = None;
+    let followed = follow(&mut child, &receiver, cancel, progress, started, &mut last);
+    if followed.is_err() {
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    let output = output.join().unwrap_or_default();
+    let errors = errors.join().unwrap_or_default();
+    let killed = followed?;
+
+    if !killed && !status.code().is_some_and(|code| (0..=EXIT_ABORTED_MAX).contains(&code)) {
+        let message = if errors.trim().is_empty() {
+            output[output.len().saturating_sub(OUTPUT_CONTEXT_LINES)..].join("\n")
+        } else {
+            errors.trim().to_string()
+        };
+        return Err(HashcatError::Failed { status, message }.into());
+    }
+    Ok(Outcome { status, last, killed })
+}
+
+/// Pass each status to `progress` until hashcat closes its output, returning
+/// whether it was killed for `cancel`
+// The closure passed to `Python::attach` cannot be a method path, as it must accept any lifetime
+#[allow(clippy::redundant_closure_for_method_calls)]
+fn follow(
+    child: &mut Child,
+    statuses: &Receiver<Status>,
+    cancel: &CancellationToken,
+    progress: &mut ProgressCallback,
+    started: Instant,
+    last: &mut Option<Status>,
+) -> PyResult<bool> {
+    let mut killed = false;
+    loop {
+        match statuses.recv_timeout(POLL_INTERVAL) {
+            Ok(status) => {
+                progress(&status.snapshot(started.elapsed()))?;
+                *last = Some(status);
+            }
+            Err(RecvTimeoutError::Timeout) => Python::attach(|py| py.check_signals())?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(killed),
+        }
+        if cancel.cancelled() && !killed {
+            child.kill()?;
+            killed = true;
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use crate::handshake::HandshakeCapture;
+use crate::hccapx::{ConversionReport, EntryError, LegacyFormat};
+use crate::hashline::{parse_mac, HashKind, HashLine, HashLineError};
+use crate::pmkid::PMKIDCapture;
+use crate::utils::from_hex;
+
+/// Bits of the message pair byte naming the message pair; the rest are hcxtools flags
+pub const MESSAGE_PAIR_MASK: u8 = 0x07;
+
+/// Anything that can be added to a hash file
+#[derive(FromPyObject)]
+pub enum HashSource {
+    Line(HashLine),
+    Pmkid(PMKIDCapture),
+    Handshake(HandshakeCapture),
+    Text(String),
+}
+
+impl HashSource {
+    /// Convert to a validated hash line
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line string is malformed.
+    pub fn into_line(self) -> Result<HashLine, HashLineError> {
+        match self {
+            Self::Line(line) => Ok(line),
+            Self::Pmkid(capture) => capture.hashcat_format.parse(),
+            Self::Handshake(capture) => capture.hashcat_format.parse(),
+            Self::Text(text) => text.parse(),
+        }
+    }
+}
+
+/// Fields two lines must share to be duplicates
+type DedupeKey = (HashKind, [u8; 6], [u8; 6], Vec<u8>, [u8; 16]);
+
+/// Collection of hashcat 22000 lines
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct HashFile {
+    pub lines: Vec<HashLine>,
+}
+
+#[pymethods]
+impl HashFile {
+    /// Create a hash file from hash lines, captures or line strings
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if an item is not a valid hash line.
+    #[new]
+    #[pyo3(signature = (items=Vec::new()))]
+    pub fn new(items: Vec<HashSource>) -> PyResult<Self> {
+        let mut file = Self::default();
+        for item in items {
+            file.add(item)?;
+        }
+        Ok(file)
+    }
+
+    /// Read a 22000 file, skipping blank lines and `#` comments
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be read, or `ValueError` naming the
+    /// line number of the first malformed hash line.
+    #[staticmethod]
+    pub fn read(path: &str) -> PyResult<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut lines = Vec::new();
+
+        for (index, text) in reader.lines().enumerate() {
+            let text = text?;
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let line = text
+                .parse()
+                .map_err(|error: HashLineError| PyValueError::new_err(format!("{path}:{}: {error}", index + 1)))?;
+            lines.push(line);
+        }
+
+        Ok(Self { lines })
+    }
+
+    /// Write one line per hash, newline terminated
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be written.
+    pub fn write(&self, path: &str) -> PyResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for line in &self.lines {
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read an hccapx file (hashcat mode 2500)
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be read; malformed records are listed in
+    /// the report's `errors`.
+    #[staticmethod]
+    pub fn read_hccapx(path: &str) -> PyResult<ConversionReport> {
+        LegacyFormat::Hccapx.read(path)
+    }
+
+    /// Read a legacy hccap file
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be read; malformed records are listed in
+    /// the report's `errors`.
+    #[staticmethod]
+    pub fn read_hccap(path: &str) -> PyResult<ConversionReport> {
+        LegacyFormat::Hccap.read(path)
+    }
+
+    /// Write the handshake lines as an hccapx file, returning the skipped entries
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be written.
+    pub fn write_hccapx(&self, path: &str) -> PyResult<Vec<EntryError>> {
+        LegacyFormat::Hccapx.write(&self.lines, path)
+    }
+
+    /// Write the handshake lines as an hccap file, returning the skipped entries
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be written.
+    pub fn write_hccap(&self, path: &str) -> PyResult<Vec<EntryError>> {
+        LegacyFormat::Hccap.write(&self.lines, path)
+    }
+
+    /// Append a hash line, capture or line string
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the item is not a valid hash line.
+    pub fn add(&mut self, item: HashSource) -> PyResult<()> {
+        self.lines.push(item.into_line()?);
+        Ok(())
+    }
+
+    /// Append all lines of another hash file
+    pub fn merge(&mut self, other: &Self) {
+        self.lines.extend(other.lines.iter().cloned());
+    }
+
+    /// Drop lines repeating the hash type, AP, station, ESSID and PMKID/MIC of an
+    /// earlier line, returning the number removed
+    pub fn dedupe(&mut self) -> usize {
+        let before = self.lines.len();
+        let mut seen: HashSet<DedupeKey> = HashSet::new();
+        self.lines
+            .retain(|line| seen.insert((line.kind, line.ap, line.station, line.essid.clone(), line.hash)));
+        before - self.lines.len()
+    }
+
+    /// Lines matching every given criterion
+    ///
+    /// `oui` matches the first three octets of the BSSID; `message_pair` matches the
+    /// pair bits of `WPA*02*` lines, ignoring hcxtools' flag bits.
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if `bssid` or `oui` is not a valid address prefix.
+    #[pyo3(signature = (essid=None, bssid=None, oui=None, hash_type=None, message_pair=None))]
+    pub fn filter(
+        &self,
+        essid: Option<&str>,
+        bssid: Option<&str>,
+        oui: Option<&str>,
+        hash_type: Option<u8>,
+        message_pair: Option<u8>,
+    ) -> PyResult<Self> {
+        let bssid = bssid.map(parse_mac).transpose()?;
+        let oui = oui.map(parse_oui).transpose()?;
+
+        let lines = self
+            .lines
+            .iter()
+            .filter(|line| essid.is_none_or(|essid| line.essid == essid.as_bytes()))
+            .filter(|line| bssid.is_none_or(|bssid| line.ap == bssid))
+            .filter(|line| oui.is_none_or(|oui| line.ap[..3] == oui))
+            .filter(|line| hash_type.is_none_or(|hash_type| line.kind as u8 == hash_type))
+            .filter(|line| {
+                message_pair.is_none_or(|message_pair| {
+                    line.kind == HashKind::Eapol
+                        && line.message_pair.map(|pair| pair & MESSAGE_PAIR_MASK) == Some(message_pair)
+                })
+            })
+            .cloned()
+            .collect();
+
+        Ok(Self { lines })
+    }
+
+    /// Convert every line to a `PMKIDCapture` or `HandshakeCapture`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a capture object cannot be created.
+    pub fn to_captures(&self, py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+        self.lines.iter().map(|line| line.to_capture(py)).collect()
+    }
+
+    #[getter(lines)]
+    #[must_use]
+    pub fn get_lines(&self) -> Vec<HashLine> {
+        self.lines.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+/// Parse a vendor prefix written as `aa:bb:cc`, `aa-bb-cc` or `aabbcc`
+fn parse_oui(oui: &str) -> Result<[u8; 3], HashLineError> {
+    let hex: String = oui.chars().filter(|c| !matches!(c, ':' | '-')).collect();
+    from_hex(&hex)
+        .and_then(|bytes| <[u8; 3]>::try_from(bytes).ok())
+        .ok_or_else(|| HashLineError::Mac(oui.to_string()))
+}
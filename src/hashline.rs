@@ -0,0 +1,423 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use crate::crypto::{self, candidate_pmk, ByteString};
+use crate::dot11::format_mac;
+use crate::eapol::KeyInformation;
+use crate::handshake::HandshakeCapture;
+use crate::pmkid::PMKIDCapture;
+use crate::utils::{from_hex, to_hex};
+
+/// Length of a PMKID and of the 16-byte MICs hashcat mode 22000 supports
+pub const HASH_LENGTH: usize = 16;
+/// Length of the AP nonce
+pub const NONCE_LENGTH: usize = 32;
+/// Longest SSID an access point may advertise
+pub const MAX_ESSID_LENGTH: usize = 32;
+/// Shortest EAPOL-Key frame with a 16-byte MIC: header (4), fixed fields (77),
+/// MIC (16) and key data length (2)
+const MIN_EAPOL_LENGTH: usize = 99;
+/// Longest EAPOL frame hashcat accepts
+const MAX_EAPOL_LENGTH: usize = 256;
+/// Offsets of the key information, replay counter and key nonce within an EAPOL-Key frame
+const EAPOL_KEY_INFO_OFFSET: usize = 5;
+const EAPOL_REPLAY_COUNTER_OFFSET: usize = 9;
+const EAPOL_NONCE_OFFSET: usize = 17;
+/// Number of `*`-separated fields in a 22000 line
+const FIELD_COUNT: usize = 9;
+
+/// Errors raised while encoding or decoding a hashcat 22000 line
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HashLineError {
+    #[error("Hash line must start with \"WPA*\"")]
+    Signature,
+    #[error("Hash line has {0} fields; expected {FIELD_COUNT}")]
+    FieldCount(usize),
+    #[error("Unsupported hash type {0:?}; expected 01 (PMKID) or 02 (EAPOL)")]
+    HashType(String),
+    #[error("Invalid hex in {0} field")]
+    Hex(&'static str),
+    #[error("{field} must be {expected} bytes, got {actual}")]
+    Length {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Invalid MAC address {0:?}")]
+    Mac(String),
+    #[error("ESSID must be 1 to {MAX_ESSID_LENGTH} bytes, got {0}")]
+    EssidLength(usize),
+    #[error("EAPOL frame must be {MIN_EAPOL_LENGTH} to {MAX_EAPOL_LENGTH} bytes, got {0}")]
+    EapolLength(usize),
+    #[error("PMKID lines carry no AP nonce or EAPOL frame")]
+    UnexpectedEapol,
+    #[error("Invalid message pair {0:?}")]
+    MessagePair(String),
+}
+
+impl From<HashLineError> for PyErr {
+    fn from(error: HashLineError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// Hash type of a 22000 line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashKind {
+    /// `WPA*01*`: PMKID
+    Pmkid = 1,
+    /// `WPA*02*`: MIC of an EAPOL message pair
+    Eapol = 2,
+}
+
+/// Hashcat mode 22000 line
+///
+/// `WPA*TYPE*PMKID|MIC*MAC_AP*MAC_STA*ESSID*ANONCE*EAPOL*MESSAGEPAIR`, with every
+/// field hex encoded; PMKID lines leave the AP nonce and EAPOL fields empty.
+#[pyclass(eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashLine {
+    pub kind: HashKind,
+    /// PMKID or MIC
+    pub hash: [u8; HASH_LENGTH],
+    pub ap: [u8; 6],
+    pub station: [u8; 6],
+    pub essid: Vec<u8>,
+    /// AP nonce, empty for PMKID lines
+    pub anonce: Vec<u8>,
+    /// Station's EAPOL frame with the MIC zeroed, empty for PMKID lines
+    pub eapol: Vec<u8>,
+    /// Message pair byte; optional for PMKID lines
+    pub message_pair: Option<u8>,
+}
+
+#[pymethods]
+impl HashLine {
+    /// Decode a 22000 line
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the line is malformed or any field fails validation.
+    #[staticmethod]
+    pub fn parse(line: &str) -> PyResult<Self> {
+        Ok(line.parse()?)
+    }
+
+    /// Hash type: 1 = PMKID, 2 = EAPOL
+    #[getter]
+    #[must_use]
+    pub fn hash_type(&self) -> u8 {
+        self.kind as u8
+    }
+
+    /// PMKID or MIC, hex encoded
+    #[getter(hash)]
+    #[must_use]
+    pub fn hash_hex(&self) -> String {
+        to_hex(&self.hash)
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn bssid(&self) -> String {
+        format_mac(self.ap)
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn client_mac(&self) -> String {
+        format_mac(self.station)
+    }
+
+    /// ESSID decoded as UTF-8, with invalid sequences replaced
+    #[getter(essid)]
+    #[must_use]
+    pub fn essid_text(&self) -> String {
+        String::from_utf8_lossy(&self.essid).into_owned()
+    }
+
+    #[getter(anonce)]
+    #[must_use]
+    pub fn anonce_hex(&self) -> Option<String> {
+        (!self.anonce.is_empty()).then(|| to_hex(&self.anonce))
+    }
+
+    #[getter(eapol)]
+    #[must_use]
+    pub fn eapol_hex(&self) -> Option<String> {
+        (!self.eapol.is_empty()).then(|| to_hex(&self.eapol))
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn message_pair(&self) -> Option<u8> {
+        self.message_pair
+    }
+
+    /// Convert to a `PMKIDCapture` or `HandshakeCapture`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the capture object cannot be created.
+    pub fn to_capture(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let mismatch = || PyValueError::new_err("Hash line does not match its hash type");
+        Ok(match self.kind {
+            HashKind::Pmkid => Py::new(py, PMKIDCapture::from_line(self).ok_or_else(mismatch)?)?.into_any(),
+            HashKind::Eapol => Py::new(py, HandshakeCapture::from_line(self).ok_or_else(mismatch)?)?.into_any(),
+        })
+    }
+
+    /// Check a passphrase, or a raw 32-byte PMK (hashcat 22001 style), against the
+    /// PMKID or EAPOL MIC
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` unless exactly one of `passphrase` and `pmk` is given, or
+    /// if the PMK is not 32 bytes.
+    #[pyo3(signature = (passphrase=None, pmk=None))]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn verify(&self, passphrase: Option<ByteString>, pmk: Option<&[u8]>) -> PyResult<bool> {
+        let pmk = candidate_pmk(passphrase.as_ref(), pmk, &self.essid)?;
+        Ok(self.verify_pmk(&pmk))
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HashLine('{self}')")
+    }
+}
+
+impl HashLine {
+    /// Build a `WPA*01*` line
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PMKID is not 16 bytes or the ESSID is not 1 to 32 bytes.
+    pub fn pmkid(pmkid: &[u8], ap: [u8; 6], station: [u8; 6], essid: &[u8]) -> Result<Self, HashLineError> {
+        let line = Self {
+            kind: HashKind::Pmkid,
+            hash: fixed("PMKID", pmkid)?,
+            ap,
+            station,
+            essid: essid.to_vec(),
+            anonce: Vec::new(),
+            eapol: Vec::new(),
+            message_pair: None,
+        };
+        line.validate()?;
+        Ok(line)
+    }
+
+    /// Build a `WPA*02*` line
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the MIC, AP nonce, ESSID or EAPOL frame has an invalid length.
+    pub fn eapol(
+        mic: &[u8],
+        ap: [u8; 6],
+        station: [u8; 6],
+        essid: &[u8],
+        anonce: &[u8],
+        eapol: &[u8],
+        message_pair: u8,
+    ) -> Result<Self, HashLineError> {
+        let line = Self {
+            kind: HashKind::Eapol,
+            hash: fixed("MIC", mic)?,
+            ap,
+            station,
+            essid: essid.to_vec(),
+            anonce: anonce.to_vec(),
+            eapol: eapol.to_vec(),
+            message_pair: Some(message_pair),
+        };
+        line.validate()?;
+        Ok(line)
+    }
+
+    /// Whether `pmk` reproduces the line's PMKID or MIC
+    ///
+    /// PMKIDs are checked with both the SHA1 and SHA256 AKM constructions; MICs with
+    /// the PTK derivation of the EAPOL frame's key descriptor version.
+    #[must_use]
+    pub fn verify_pmk(&self, pmk: &[u8; 32]) -> bool {
+        match self.kind {
+            HashKind::Pmkid => {
+                crypto::pmkid(pmk, self.ap, self.station) == self.hash
+                    || crypto::pmkid_sha256(pmk, self.ap, self.station) == self.hash
+            }
+            HashKind::Eapol => {
+                let (Ok(anonce), Some(snonce), Some(key_info)) = (
+                    <[u8; NONCE_LENGTH]>::try_from(&self.anonce[..]),
+                    self.station_nonce(),
+                    self.key_info(),
+                ) else {
+                    return false;
+                };
+                let key_version = key_info.version();
+                let ptk = if key_version == 3 {
+                    crypto::ptk_sha256(pmk, self.ap, self.station, &anonce, &snonce, 384)
+                } else {
+                    crypto::ptk(pmk, self.ap, self.station, &anonce, &snonce, 384)
+                };
+                let Ok(kck) = <[u8; 16]>::try_from(&ptk[..16]) else {
+                    return false;
+                };
+                crypto::mic(key_version, &kck, &self.eapol).is_ok_and(|mic| mic == self.hash)
+            }
+        }
+    }
+
+    /// Key information of the EAPOL frame, for `WPA*02*` lines
+    #[must_use]
+    pub fn key_info(&self) -> Option<KeyInformation> {
+        let bytes = self.eapol.get(EAPOL_KEY_INFO_OFFSET..EAPOL_KEY_INFO_OFFSET + 2)?;
+        Some(KeyInformation(u16::from_be_bytes([bytes[0], bytes[1]])))
+    }
+
+    /// Replay counter of the EAPOL frame, for `WPA*02*` lines
+    #[must_use]
+    pub fn replay_counter(&self) -> Option<u64> {
+        let bytes = self.eapol.get(EAPOL_REPLAY_COUNTER_OFFSET..EAPOL_NONCE_OFFSET)?;
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+
+    /// Station nonce carried in the EAPOL frame, for `WPA*02*` lines
+    #[must_use]
+    pub fn station_nonce(&self) -> Option<[u8; NONCE_LENGTH]> {
+        self.eapol.get(EAPOL_NONCE_OFFSET..EAPOL_NONCE_OFFSET + NONCE_LENGTH)?.try_into().ok()
+    }
+
+    fn validate(&self) -> Result<(), HashLineError> {
+        if self.essid.is_empty() || self.essid.len() > MAX_ESSID_LENGTH {
+            return Err(HashLineError::EssidLength(self.essid.len()));
+        }
+        match self.kind {
+            HashKind::Pmkid if !self.anonce.is_empty() || !self.eapol.is_empty() => Err(HashLineError::UnexpectedEapol),
+            HashKind::Pmkid => Ok(()),
+            HashKind::Eapol => {
+                fixed::<NONCE_LENGTH>("ANonce", &self.anonce)?;
+                if !(MIN_EAPOL_LENGTH..=MAX_EAPOL_LENGTH).contains(&self.eapol.len()) {
+                    return Err(HashLineError::EapolLength(self.eapol.len()));
+                }
+                if self.message_pair.is_none() {
+                    return Err(HashLineError::MessagePair(String::new()));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for HashLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "WPA*{:02x}*{}*{}*{}*{}*{}*{}*",
+            self.kind as u8,
+            to_hex(&self.hash),
+            to_hex(&self.ap),
+            to_hex(&self.station),
+            to_hex(&self.essid),
+            to_hex(&self.anonce),
+            to_hex(&self.eapol)
+        )?;
+        if let Some(message_pair) = self.message_pair {
+            write!(f, "{message_pair:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HashLine {
+    type Err = HashLineError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.trim().split('*').collect();
+        if fields[0] != "WPA" {
+            return Err(HashLineError::Signature);
+        }
+        if fields.len() != FIELD_COUNT {
+            return Err(HashLineError::FieldCount(fields.len()));
+        }
+
+        let kind = match fields[1] {
+            "01" => HashKind::Pmkid,
+            "02" => HashKind::Eapol,
+            other => return Err(HashLineError::HashType(other.to_string())),
+        };
+        let field_name = match kind {
+            HashKind::Pmkid => "PMKID",
+            HashKind::Eapol => "MIC",
+        };
+        let message_pair = match (kind, fields[8]) {
+            (HashKind::Pmkid, "") => None,
+            (_, byte) => Some(
+                from_hex(byte)
+                    .and_then(|byte| <[u8; 1]>::try_from(byte).ok())
+                    .ok_or_else(|| HashLineError::MessagePair(byte.to_string()))?[0],
+            ),
+        };
+
+        let line = Self {
+            kind,
+            hash: fixed(field_name, &hex_field(field_name, fields[2])?)?,
+            ap: parse_mac(fields[3])?,
+            station: parse_mac(fields[4])?,
+            essid: hex_field("ESSID", fields[5])?,
+            anonce: hex_field("ANonce", fields[6])?,
+            eapol: hex_field("EAPOL", fields[7])?,
+            message_pair,
+        };
+        line.validate()?;
+        Ok(line)
+    }
+}
+
+/// Parse a MAC address written as `aa:bb:cc:dd:ee:ff`, `aa-bb-cc-dd-ee-ff` or `aabbccddeeff`
+///
+/// # Errors
+///
+/// Returns an error if the address is not six hex octets.
+pub fn parse_mac(mac: &str) -> Result<[u8; 6], HashLineError> {
+    let invalid = || HashLineError::Mac(mac.to_string());
+    let hex = if mac.len() == 17 {
+        let separator = mac.as_bytes()[2];
+        if !matches!(separator, b':' | b'-') || mac.bytes().skip(2).step_by(3).any(|byte| byte != separator) {
+            return Err(invalid());
+        }
+        mac.split(char::from(separator)).collect::<String>()
+    } else {
+        mac.to_string()
+    };
+    from_hex(&hex)
+        .and_then(|bytes| <[u8; 6]>::try_from(bytes).ok())
+        .ok_or_else(invalid)
+}
+
+/// Decode a hex string into a fixed-length array
+///
+/// # Errors
+///
+/// Returns an error naming `field` if the string is not valid hex of the expected length.
+pub fn parse_hex_array<const N: usize>(field: &'static str, hex: &str) -> Result<[u8; N], HashLineError> {
+    fixed(field, &hex_field(field, hex)?)
+}
+
+fn hex_field(field: &'static str, hex: &str) -> Result<Vec<u8>, HashLineError> {
+    from_hex(hex).ok_or(HashLineError::Hex(field))
+}
+
+fn fixed<const N: usize>(field: &'static str, bytes: &[u8]) -> Result<[u8; N], HashLineError> {
+    bytes.try_into().map_err(|_| HashLineError::Length {
+        field,
+        expected: N,
+        actual: bytes.len(),
+    })
+}
@@ -0,0 +1,358 @@
+use pyo3::prelude::*;
+use std::fs;
+use thiserror::Error;
+use crate::eapol::KeyInformation;
+use crate::hashfile::{HashFile, MESSAGE_PAIR_MASK};
+use crate::hashline::{HashKind, HashLine, HashLineError, MAX_ESSID_LENGTH, NONCE_LENGTH};
+
+/// Size of an hccapx record (hashcat mode 2500)
+pub const HCCAPX_RECORD_LEN: usize = 393;
+/// Size of a legacy hccap record
+pub const HCCAP_RECORD_LEN: usize = 392;
+
+const HCCAPX_SIGNATURE: &[u8; 4] = b"HCPX";
+const HCCAPX_VERSION: u32 = 4;
+/// Both formats reserve room for EAPOL frames up to 256 bytes
+const EAPOL_CAPACITY: usize = 256;
+/// hccap stores the ESSID NUL-terminated in a 36-byte field
+const HCCAP_ESSID_LEN: usize = 36;
+
+/// Offset of the MIC within an EAPOL-Key frame
+const MIC_OFFSET: usize = 81;
+
+/// Message pair written for hccap entries whose EAPOL frame is message 4
+const MESSAGE_PAIR_M3M4: u8 = 5;
+/// Message pair written for hccap entries whose EAPOL frame is message 3
+const MESSAGE_PAIR_M3M4_FROM_AP: u8 = 4;
+/// Message pairs whose EAPOL frame is the AP's message 3
+const EAPOL_FROM_AP: [u8; 2] = [3, 4];
+
+/// Errors converting a single hccapx or hccap entry
+#[derive(Debug, Error)]
+pub enum LegacyFormatError {
+    #[error("Bad signature {0:02x?}; expected \"HCPX\"")]
+    Signature([u8; 4]),
+    #[error("Unsupported hccapx version {0}; expected {HCCAPX_VERSION}")]
+    Version(u32),
+    #[error("Truncated record: {actual} of {expected} bytes")]
+    Truncated { expected: usize, actual: usize },
+    #[error("ESSID length {0} exceeds {MAX_ESSID_LENGTH} bytes")]
+    EssidLength(usize),
+    #[error("EAPOL length {0} exceeds {EAPOL_CAPACITY} bytes")]
+    EapolLength(usize),
+    #[error("{0} nonce field does not match the nonce in the EAPOL frame")]
+    Nonce(&'static str),
+    #[error("EAPOL frame carries a zeroed nonce, so the station nonce cannot be used")]
+    ZeroedNonce,
+    #[error("PMKID hashes cannot be stored in {0}")]
+    Pmkid(&'static str),
+    #[error(transparent)]
+    Line(#[from] HashLineError),
+}
+
+/// Entry that could not be converted
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct EntryError {
+    /// Position of the entry in the file or hash list, starting at 0
+    #[pyo3(get)]
+    pub index: usize,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+#[pymethods]
+impl EntryError {
+    /// Get a summary of the error
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!("Entry {}: {}", self.index, self.message)
+    }
+}
+
+/// Hashes read from a legacy file, with the entries that failed to convert
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ConversionReport {
+    #[pyo3(get)]
+    pub hashes: HashFile,
+    #[pyo3(get)]
+    pub errors: Vec<EntryError>,
+}
+
+/// Legacy binary hash formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyFormat {
+    Hccapx,
+    Hccap,
+}
+
+impl LegacyFormat {
+    #[must_use]
+    pub fn record_len(self) -> usize {
+        match self {
+            Self::Hccapx => HCCAPX_RECORD_LEN,
+            Self::Hccap => HCCAP_RECORD_LEN,
+        }
+    }
+
+    /// Decode one record into a `WPA*02*` line
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record is truncated, malformed or fails hash line validation.
+    pub fn decode(self, record: &[u8]) -> Result<HashLine, LegacyFormatError> {
+        if record.len() != self.record_len() {
+            return Err(LegacyFormatError::Truncated {
+                expected: self.record_len(),
+                actual: record.len(),
+            });
+        }
+        match self {
+            Self::Hccapx => decode_hccapx(record),
+            Self::Hccap => decode_hccap(record),
+        }
+    }
+
+    /// Encode a `WPA*02*` line as one record
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for PMKID lines, which neither format can hold.
+    pub fn encode(self, line: &HashLine) -> Result<Vec<u8>, LegacyFormatError> {
+        if line.kind != HashKind::Eapol {
+            return Err(LegacyFormatError::Pmkid(self.as_str()));
+        }
+        Ok(match self {
+            Self::Hccapx => encode_hccapx(line),
+            Self::Hccap => encode_hccap(line),
+        })
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hccapx => "hccapx",
+            Self::Hccap => "hccap",
+        }
+    }
+
+    /// Read every record of a file, collecting per-entry errors
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read(self, path: &str) -> PyResult<ConversionReport> {
+        let data = fs::read(path)?;
+        let mut lines = Vec::new();
+        let mut errors = Vec::new();
+
+        for (index, record) in data.chunks(self.record_len()).enumerate() {
+            match self.decode(record) {
+                Ok(line) => lines.push(line),
+                Err(error) => errors.push(EntryError {
+                    index,
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        Ok(ConversionReport {
+            hashes: HashFile { lines },
+            errors,
+        })
+    }
+
+    /// Write every convertible line, returning the entries that were skipped
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(self, lines: &[HashLine], path: &str) -> PyResult<Vec<EntryError>> {
+        let mut data = Vec::with_capacity(lines.len() * self.record_len());
+        let mut errors = Vec::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            match self.encode(line) {
+                Ok(record) => data.extend_from_slice(&record),
+                Err(error) => errors.push(EntryError {
+                    index,
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        fs::write(path, data)?;
+        Ok(errors)
+    }
+}
+
+/// Record layout: signature (4), version (4), message pair (1), ESSID length (1),
+/// ESSID (32), key version (1), MIC (16), AP MAC (6), AP nonce (32), station MAC (6),
+/// station nonce (32), EAPOL length (2, little endian), EAPOL (256)
+fn decode_hccapx(record: &[u8]) -> Result<HashLine, LegacyFormatError> {
+    let signature = array::<4>(record, 0);
+    if &signature != HCCAPX_SIGNATURE {
+        return Err(LegacyFormatError::Signature(signature));
+    }
+    let version = u32::from_le_bytes(array(record, 4));
+    if version != HCCAPX_VERSION {
+        return Err(LegacyFormatError::Version(version));
+    }
+
+    let essid_len = usize::from(record[9]);
+    if essid_len > MAX_ESSID_LENGTH {
+        return Err(LegacyFormatError::EssidLength(essid_len));
+    }
+    let eapol_len = usize::from(u16::from_le_bytes(array(record, 135)));
+    if eapol_len > EAPOL_CAPACITY {
+        return Err(LegacyFormatError::EapolLength(eapol_len));
+    }
+
+    let mut line = HashLine::eapol(
+        &record[43..59],
+        array(record, 59),
+        array(record, 97),
+        &record[10..10 + essid_len],
+        &record[65..97],
+        &without_mic(&record[137..137 + eapol_len]),
+        record[8],
+    )?;
+    line.anonce = line_nonce(&line, &record[65..97], &record[103..135])?.to_vec();
+    Ok(line)
+}
+
+fn encode_hccapx(line: &HashLine) -> Vec<u8> {
+    let mut record = Vec::with_capacity(HCCAPX_RECORD_LEN);
+    record.extend_from_slice(HCCAPX_SIGNATURE);
+    record.extend_from_slice(&HCCAPX_VERSION.to_le_bytes());
+    record.push(line.message_pair.unwrap_or_default());
+    record.push(u8::try_from(line.essid.len()).unwrap_or_default());
+    record.extend_from_slice(&padded(&line.essid, MAX_ESSID_LENGTH));
+    record.push(key_version(line));
+    record.extend_from_slice(&line.hash);
+    let (anonce, snonce) = record_nonces(line);
+    record.extend_from_slice(&line.ap);
+    record.extend_from_slice(&anonce);
+    record.extend_from_slice(&line.station);
+    record.extend_from_slice(&snonce);
+    record.extend_from_slice(&u16::try_from(line.eapol.len()).unwrap_or_default().to_le_bytes());
+    record.extend_from_slice(&padded(&line.eapol, EAPOL_CAPACITY));
+    record
+}
+
+/// Record layout: ESSID (36, NUL padded), AP MAC (6), station MAC (6), station
+/// nonce (32), AP nonce (32), EAPOL (256), EAPOL length (4), key version (4), MIC (16);
+/// integers little endian
+fn decode_hccap(record: &[u8]) -> Result<HashLine, LegacyFormatError> {
+    let essid_field = &record[..HCCAP_ESSID_LEN];
+    let essid_len = essid_field.iter().position(|byte| *byte == 0).unwrap_or(HCCAP_ESSID_LEN);
+    if essid_len > MAX_ESSID_LENGTH {
+        return Err(LegacyFormatError::EssidLength(essid_len));
+    }
+    let eapol_len = u32::from_le_bytes(array(record, 368));
+    let eapol_len = usize::try_from(eapol_len)
+        .ok()
+        .filter(|len| *len <= EAPOL_CAPACITY)
+        .ok_or(LegacyFormatError::EapolLength(eapol_len as usize))?;
+
+    let mut line = HashLine::eapol(
+        &record[376..392],
+        array(record, 36),
+        array(record, 42),
+        &essid_field[..essid_len],
+        &record[80..112],
+        &without_mic(&record[112..112 + eapol_len]),
+        0,
+    )?;
+
+    // hccap predates message pairs; an EAPOL frame with the Ack bit is the AP's
+    // message 3, and one with only the Secure bit is message 4
+    if line.key_info().is_some_and(KeyInformation::ack) {
+        line.message_pair = Some(MESSAGE_PAIR_M3M4_FROM_AP);
+    } else if line.key_info().is_some_and(KeyInformation::secure) {
+        line.message_pair = Some(MESSAGE_PAIR_M3M4);
+    }
+    line.anonce = line_nonce(&line, &record[80..112], &record[48..80])?.to_vec();
+    Ok(line)
+}
+
+fn encode_hccap(line: &HashLine) -> Vec<u8> {
+    let mut record = Vec::with_capacity(HCCAP_RECORD_LEN);
+    record.extend_from_slice(&padded(&line.essid, HCCAP_ESSID_LEN));
+    record.extend_from_slice(&line.ap);
+    record.extend_from_slice(&line.station);
+    let (anonce, snonce) = record_nonces(line);
+    record.extend_from_slice(&snonce);
+    record.extend_from_slice(&anonce);
+    record.extend_from_slice(&padded(&line.eapol, EAPOL_CAPACITY));
+    record.extend_from_slice(&u32::try_from(line.eapol.len()).unwrap_or_default().to_le_bytes());
+    record.extend_from_slice(&u32::from(key_version(line)).to_le_bytes());
+    record.extend_from_slice(&line.hash);
+    record
+}
+
+/// Whether the line's EAPOL frame is the AP's message 3, whose nonce is the AP
+/// nonce; the line's nonce field then holds the station nonce
+fn eapol_from_ap(line: &HashLine) -> bool {
+    line.message_pair
+        .is_some_and(|pair| EAPOL_FROM_AP.contains(&(pair & MESSAGE_PAIR_MASK)))
+}
+
+/// Nonce a `WPA*02*` line carries beside its EAPOL frame, from a record's AP and
+/// station nonce fields
+///
+/// The field naming the sender of the EAPOL frame must match the nonce in the
+/// frame, which is the one cracking uses.
+fn line_nonce<'a>(line: &HashLine, anonce: &'a [u8], snonce: &'a [u8]) -> Result<&'a [u8], LegacyFormatError> {
+    // Validation guarantees the EAPOL frame holds a nonce
+    let nonce = line.station_nonce().unwrap_or_default();
+    if eapol_from_ap(line) {
+        return if nonce[..] == *anonce { Ok(snonce) } else { Err(LegacyFormatError::Nonce("AP")) };
+    }
+    if nonce[..] == *snonce {
+        Ok(anonce)
+    } else if nonce == [0; NONCE_LENGTH] {
+        // Message 4 often leaves its nonce zeroed, and the MIC covers it
+        Err(LegacyFormatError::ZeroedNonce)
+    } else {
+        Err(LegacyFormatError::Nonce("Station"))
+    }
+}
+
+/// AP and station nonce fields of a record for the line
+fn record_nonces(line: &HashLine) -> ([u8; NONCE_LENGTH], [u8; NONCE_LENGTH]) {
+    let nonce = line.station_nonce().unwrap_or_default();
+    let other = <[u8; NONCE_LENGTH]>::try_from(&line.anonce[..]).unwrap_or_default();
+    if eapol_from_ap(line) {
+        (nonce, other)
+    } else {
+        (other, nonce)
+    }
+}
+
+/// Key descriptor version of the line's EAPOL frame
+fn key_version(line: &HashLine) -> u8 {
+    line.key_info().map_or(0, KeyInformation::version)
+}
+
+/// Copy of an EAPOL frame with the MIC zeroed, as hash lines carry it
+fn without_mic(eapol: &[u8]) -> Vec<u8> {
+    let mut eapol = eapol.to_vec();
+    if let Some(mic) = eapol.get_mut(MIC_OFFSET..MIC_OFFSET + 16) {
+        mic.fill(0);
+    }
+    eapol
+}
+
+fn padded(bytes: &[u8], len: usize) -> Vec<u8> {
+    let mut field = bytes.to_vec();
+    field.resize(len, 0);
+    field
+}
+
+fn array<const N: usize>(record: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&record[offset..offset + N]);
+    bytes
+}
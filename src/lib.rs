@@ -2,16 +2,49 @@ use pyo3::prelude::*;
 
 // Module declarations
 mod network;
+mod dot11;
+mod eapol;
+mod handshake;
+mod hashcat;
+mod hashfile;
+mod hashline;
+mod hccapx;
 mod scanner;
 mod parser;
 mod builder;
+mod checkpoint;
+mod cracker;
+mod crypto;
+mod mask;
+mod frame;
 mod utils;
 mod pmkid;
+mod pmkdb;
+mod potfile;
+mod rules;
+mod radiotap;
+mod security;
+mod station;
 
 // Re-exports for Python bindings
-pub use network::WiFiNetwork;
+pub use network::{NetworkChange, WiFiNetwork};
 pub use scanner::NetworkScanner;
 pub use pmkid::PMKIDCapture;
+pub use pmkdb::PmkDatabase;
+pub use potfile::{Potfile, PotfileEntry};
+pub use rules::{Rule, RuleSet};
+pub use mask::Mask;
+pub use eapol::EapolKey;
+pub use handshake::HandshakeCapture;
+pub use hashcat::HashcatRunner;
+pub use hashfile::HashFile;
+pub use hashline::HashLine;
+pub use hccapx::{ConversionReport, EntryError};
+pub use security::SecurityProfile;
+pub use station::Station;
+pub use frame::UnsupportedLinkTypeError;
+pub use cracker::{CancellationToken, CrackProgress, CrackResult, CrackedHash, DictionaryAttack};
+pub use crypto::{py_compute_mic, py_compute_pmkid, py_derive_pmk, py_derive_ptk, py_prf};
 
 /// A Python module implemented in Rust.
 #[pymodule]
@@ -19,9 +52,90 @@ mod _wifite3 {
     #[pymodule_export]
     use super::WiFiNetwork;
 
+    #[pymodule_export]
+    use super::NetworkChange;
+
     #[pymodule_export]
     use super::NetworkScanner;
 
     #[pymodule_export]
     use super::PMKIDCapture;
+
+    #[pymodule_export]
+    use super::HandshakeCapture;
+
+    #[pymodule_export]
+    use super::HashLine;
+
+    #[pymodule_export]
+    use super::HashFile;
+
+    #[pymodule_export]
+    use super::ConversionReport;
+
+    #[pymodule_export]
+    use super::EntryError;
+
+    #[pymodule_export]
+    use super::EapolKey;
+
+    #[pymodule_export]
+    use super::SecurityProfile;
+
+    #[pymodule_export]
+    use super::Station;
+
+    #[pymodule_export]
+    use super::UnsupportedLinkTypeError;
+
+    #[pymodule_export]
+    use super::DictionaryAttack;
+
+    #[pymodule_export]
+    use super::CancellationToken;
+
+    #[pymodule_export]
+    use super::CrackProgress;
+
+    #[pymodule_export]
+    use super::CrackedHash;
+
+    #[pymodule_export]
+    use super::CrackResult;
+
+    #[pymodule_export]
+    use super::PmkDatabase;
+
+    #[pymodule_export]
+    use super::Rule;
+
+    #[pymodule_export]
+    use super::RuleSet;
+
+    #[pymodule_export]
+    use super::Mask;
+
+    #[pymodule_export]
+    use super::Potfile;
+
+    #[pymodule_export]
+    use super::PotfileEntry;
+
+    #[pymodule_export]
+    use super::HashcatRunner;
+
+    #[pymodule_export]
+    use super::py_derive_pmk;
+
+    #[pymodule_export]
+    use super::py_prf;
+
+    #[pymodule_export]
+    use super::py_derive_ptk;
+
+    #[pymodule_export]
+    use super::py_compute_pmkid;
+
+    #[pymodule_export]
+    use super::py_compute_mic;
 }
@@ -0,0 +1,384 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::io;
+use thiserror::Error;
+use crate::cracker::{CandidateSource, SourcePosition};
+
+/// Custom charsets a mask can reference as `?1` to `?4`
+pub const MAX_CUSTOM_CHARSETS: usize = 4;
+
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const HEX_LOWER: &[u8] = b"0123456789abcdef";
+const HEX_UPPER: &[u8] = b"0123456789ABCDEF";
+const SPECIAL: &[u8] = b" !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Errors parsing a mask or addressing its keyspace
+#[derive(Debug, Error)]
+pub enum MaskError {
+    #[error("Mask is empty")]
+    Empty,
+    #[error("Unknown charset '?{0}'; expected ?l, ?u, ?d, ?h, ?H, ?s, ?a, ?b or ?1-?4")]
+    Charset(char),
+    #[error("Custom charset ?{0} is not defined")]
+    UndefinedCharset(usize),
+    #[error("At most {MAX_CUSTOM_CHARSETS} custom charsets are supported, got {0}")]
+    CustomCharsetCount(usize),
+    #[error("Custom charset ?{0} is empty")]
+    EmptyCharset(usize),
+    #[error("Mask ends with an unfinished '?'")]
+    Trailing,
+    #[error("Length range {min}-{max} does not fit a mask of {len} positions")]
+    Length { min: usize, max: usize, len: usize },
+    #[error("Keyspace exceeds 2^64 candidates")]
+    Keyspace,
+    #[error("Range {start}-{end} is outside the keyspace of {keyspace} candidates")]
+    Range { start: u64, end: u64, keyspace: u64 },
+}
+
+impl From<MaskError> for PyErr {
+    fn from(error: MaskError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// hashcat-style mask such as `?u?u?d?d?d?d?d?d`
+///
+/// Candidates are numbered from 0 in lexicographic charset order, rightmost
+/// position changing fastest, shortest length first when incrementing. Any
+/// index range can be generated independently, which is what splitting and
+/// resuming rely on.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Mask {
+    text: String,
+    /// Definitions of `?1` to `?4` as given
+    custom: Vec<String>,
+    positions: Vec<Vec<u8>>,
+    /// Candidate lengths generated, shortest first
+    lengths: Vec<usize>,
+    /// Candidates of each length in `lengths`
+    counts: Vec<u64>,
+    keyspace: u64,
+}
+
+#[pymethods]
+impl Mask {
+    /// Parse a mask
+    ///
+    /// `custom_charsets` define `?1` to `?4` and may themselves use the built-in
+    /// charsets. With `increment`, every prefix of the mask between
+    /// `increment_min` and `increment_max` positions is generated, shortest first.
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` for malformed masks, invalid length ranges or a
+    /// keyspace over 2^64.
+    #[new]
+    #[pyo3(signature = (mask, custom_charsets=Vec::new(), increment=false, increment_min=None, increment_max=None))]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        mask: &str,
+        custom_charsets: Vec<String>,
+        increment: bool,
+        increment_min: Option<usize>,
+        increment_max: Option<usize>,
+    ) -> PyResult<Self> {
+        let custom: Vec<&str> = custom_charsets.iter().map(String::as_str).collect();
+        let positions = parse_mask(mask, &custom)?;
+        let len = positions.len();
+        let (min, max) = if increment {
+            (increment_min.unwrap_or(1), increment_max.unwrap_or(len))
+        } else {
+            (len, len)
+        };
+        let mut mask = Self::with_lengths(mask, positions, min, max)?;
+        mask.custom = custom_charsets;
+        Ok(mask)
+    }
+
+    /// Candidate at `index`, or `None` past the end of the keyspace
+    #[must_use]
+    pub fn candidate(&self, index: u64) -> Option<Vec<u8>> {
+        let (len, mut offset) = self.locate(index)?;
+        let mut candidate = vec![0; len];
+        for (slot, charset) in candidate.iter_mut().zip(&self.positions).rev() {
+            let size = charset.len() as u64;
+            *slot = charset[usize::try_from(offset % size).ok()?];
+            offset /= size;
+        }
+        Some(candidate)
+    }
+
+    /// Split the keyspace into `parts` contiguous `(start, end)` ranges of nearly
+    /// equal size
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if `parts` is zero.
+    pub fn split(&self, parts: u64) -> PyResult<Vec<(u64, u64)>> {
+        if parts == 0 {
+            return Err(PyValueError::new_err("parts must be at least 1"));
+        }
+        let (size, remainder) = (self.keyspace / parts, self.keyspace % parts);
+        let mut start = 0;
+        Ok((0..parts)
+            .map(|part| {
+                let end = start + size + u64::from(part < remainder);
+                let range = (start, end);
+                start = end;
+                range
+            })
+            .filter(|(start, end)| start < end)
+            .collect())
+    }
+
+    /// Number of candidates the mask generates
+    #[getter]
+    #[must_use]
+    pub fn keyspace(&self) -> u64 {
+        self.keyspace
+    }
+
+    /// Number of mask positions
+    #[getter]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn __str__(&self) -> String {
+        self.text.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Mask({:?}, keyspace={})", self.text, self.keyspace)
+    }
+}
+
+impl Mask {
+    /// Mask as written
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Custom charset definitions for `?1` to `?4`, as written
+    #[must_use]
+    pub fn custom_charsets(&self) -> &[String] {
+        &self.custom
+    }
+
+    /// Charset of every mask position
+    #[must_use]
+    pub fn charsets(&self) -> &[Vec<u8>] {
+        &self.positions
+    }
+
+    /// Candidate lengths generated, shortest first
+    #[must_use]
+    pub fn lengths(&self) -> &[usize] {
+        &self.lengths
+    }
+
+    fn with_lengths(mask: &str, positions: Vec<Vec<u8>>, min: usize, max: usize) -> Result<Self, MaskError> {
+        let len = positions.len();
+        if min == 0 || min > max || max > len {
+            return Err(MaskError::Length { min, max, len });
+        }
+
+        let lengths: Vec<usize> = (min..=max).collect();
+        let counts = lengths
+            .iter()
+            .map(|len| {
+                positions[..*len]
+                    .iter()
+                    .try_fold(1u64, |count, charset| count.checked_mul(charset.len() as u64))
+            })
+            .collect::<Option<Vec<u64>>>()
+            .ok_or(MaskError::Keyspace)?;
+        let keyspace = counts
+            .iter()
+            .try_fold(0u64, |total, count| total.checked_add(*count))
+            .ok_or(MaskError::Keyspace)?;
+
+        Ok(Self {
+            text: mask.to_string(),
+            custom: Vec::new(),
+            positions,
+            lengths,
+            counts,
+            keyspace,
+        })
+    }
+
+    /// Candidate length at `index` and the index within that length
+    fn locate(&self, mut index: u64) -> Option<(usize, u64)> {
+        for (len, count) in self.lengths.iter().zip(&self.counts) {
+            if index < *count {
+                return Some((*len, index));
+            }
+            index -= count;
+        }
+        None
+    }
+
+    /// Check that `start..end` lies within the keyspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range is reversed or extends past the keyspace.
+    pub fn check_range(&self, start: u64, end: u64) -> Result<(), MaskError> {
+        if start > end || end > self.keyspace {
+            return Err(MaskError::Range {
+                start,
+                end,
+                keyspace: self.keyspace,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Bytes of a built-in charset
+fn builtin(symbol: u8) -> Option<Vec<u8>> {
+    Some(match symbol {
+        b'l' => LOWER.to_vec(),
+        b'u' => UPPER.to_vec(),
+        b'd' => DIGITS.to_vec(),
+        b'h' => HEX_LOWER.to_vec(),
+        b'H' => HEX_UPPER.to_vec(),
+        b's' => SPECIAL.to_vec(),
+        b'a' => [LOWER, UPPER, DIGITS, SPECIAL].concat(),
+        b'b' => (0..=u8::MAX).collect(),
+        b'?' => vec![b'?'],
+        _ => return None,
+    })
+}
+
+/// Expand a custom charset definition, keeping the first occurrence of each byte
+fn parse_charset(definition: &str, number: usize) -> Result<Vec<u8>, MaskError> {
+    let bytes = definition.as_bytes();
+    let mut charset: Vec<u8> = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let expanded = if bytes[index] == b'?' {
+            let symbol = *bytes.get(index + 1).ok_or(MaskError::Trailing)?;
+            index += 2;
+            builtin(symbol).ok_or(MaskError::Charset(char::from(symbol)))?
+        } else {
+            index += 1;
+            vec![bytes[index - 1]]
+        };
+        for byte in expanded {
+            if !charset.contains(&byte) {
+                charset.push(byte);
+            }
+        }
+    }
+    if charset.is_empty() {
+        return Err(MaskError::EmptyCharset(number));
+    }
+    Ok(charset)
+}
+
+/// Charset of every mask position; literal bytes become one-byte charsets
+fn parse_mask(mask: &str, custom: &[&str]) -> Result<Vec<Vec<u8>>, MaskError> {
+    if custom.len() > MAX_CUSTOM_CHARSETS {
+        return Err(MaskError::CustomCharsetCount(custom.len()));
+    }
+    let custom = custom
+        .iter()
+        .enumerate()
+        .map(|(index, definition)| parse_charset(definition, index + 1))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let bytes = mask.as_bytes();
+    let mut positions = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'?' {
+            positions.push(vec![bytes[index]]);
+            index += 1;
+            continue;
+        }
+        let symbol = *bytes.get(index + 1).ok_or(MaskError::Trailing)?;
+        let charset = match symbol {
+            b'1'..=b'4' => {
+                let number = usize::from(symbol - b'0');
+                custom.get(number - 1).cloned().ok_or(MaskError::UndefinedCharset(number))?
+            }
+            _ => builtin(symbol).ok_or(MaskError::Charset(char::from(symbol)))?,
+        };
+        positions.push(charset);
+        index += 2;
+    }
+
+    if positions.is_empty() {
+        return Err(MaskError::Empty);
+    }
+    Ok(positions)
+}
+
+/// Candidates of a mask keyspace range, generated in parallel per batch
+pub struct MaskSource {
+    mask: Mask,
+    start: u64,
+    next: u64,
+    end: u64,
+}
+
+impl MaskSource {
+    /// Generate candidates `start..end` of `mask`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range lies outside the keyspace.
+    pub fn new(mask: Mask, start: u64, end: u64) -> Result<Self, MaskError> {
+        mask.check_range(start, end)?;
+        Ok(Self {
+            mask,
+            start,
+            next: start,
+            end,
+        })
+    }
+}
+
+impl CandidateSource for MaskSource {
+    fn fill(&mut self, batch: &mut Vec<Vec<u8>>, limit: usize) -> io::Result<bool> {
+        let stop = self.end.min(self.next.saturating_add(limit as u64));
+        batch.par_extend(
+            (self.next..stop)
+                .into_par_iter()
+                .filter_map(|index| self.mask.candidate(index)),
+        );
+        self.next = stop;
+        Ok(self.next < self.end)
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        (self.end > self.start).then(|| (self.next - self.start) as f64 / (self.end - self.start) as f64)
+    }
+
+    fn position(&self) -> SourcePosition {
+        SourcePosition {
+            offset: self.next,
+            rule: 0,
+        }
+    }
+
+    fn seek(&mut self, position: SourcePosition) -> io::Result<()> {
+        if !(self.start..=self.end).contains(&position.offset) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Index {} is outside the range {}-{}", position.offset, self.start, self.end),
+            ));
+        }
+        self.next = position.offset;
+        Ok(())
+    }
+}
@@ -1,42 +1,249 @@
 use pyo3::prelude::*;
+use pyo3::types::PyList;
+use crate::crypto::ByteString;
+use crate::security::SecurityProfile;
+use crate::station::Station;
+
+/// Placeholder name reported for networks that hide their SSID
+pub const HIDDEN_SSID: &str = "Hidden Network";
+
+/// Management frame a network's details were learned from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSource {
+    Beacon,
+    ProbeResponse,
+    AssociationRequest,
+    ReassociationRequest,
+    ProbeRequest,
+}
+
+impl FrameSource {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Beacon => "beacon",
+            Self::ProbeResponse => "probe_response",
+            Self::AssociationRequest => "association_request",
+            Self::ReassociationRequest => "reassociation_request",
+            Self::ProbeRequest => "probe_request",
+        }
+    }
+}
 
 /// `WiFi` network information
 #[pyclass]
 #[derive(Debug, Clone)]
 pub struct WiFiNetwork {
-    #[pyo3(get)]
-    pub ssid: String,
+    /// SSID bytes as broadcast; empty while a hidden SSID is unknown
+    pub essid: Vec<u8>,
     #[pyo3(get)]
     pub bssid: String,
     #[pyo3(get)]
     pub channel: u8,
+    /// Antenna signal in dBm, if the capture carried radio metadata
+    #[pyo3(get)]
+    pub rssi: Option<i8>,
+    /// Channel frequency in MHz the network was received on
     #[pyo3(get)]
-    pub rssi: i8,
+    pub frequency: Option<u16>,
     #[pyo3(get)]
     pub encryption: String,
+    /// Decoded RSN configuration, if the network advertises one
+    #[pyo3(get)]
+    pub security: Option<SecurityProfile>,
+    /// Whether the network's beacons hide the SSID
+    #[pyo3(get)]
+    pub was_hidden: bool,
+    /// Frame type that revealed a hidden SSID (e.g. `probe_response`)
+    #[pyo3(get)]
+    pub revealed_by: Option<String>,
+    /// Stations associated to this network
+    #[pyo3(get)]
+    pub clients: Vec<Station>,
+    /// Number of beacons received from the network
+    #[pyo3(get)]
+    pub beacon_count: u64,
+    /// Capture timestamp of the first frame from the network (seconds since the epoch)
+    #[pyo3(get)]
+    pub first_seen: Option<f64>,
+    /// Capture timestamp of the latest frame from the network
+    #[pyo3(get)]
+    pub last_seen: Option<f64>,
+    /// Weakest antenna signal in dBm seen from the network
+    #[pyo3(get)]
+    pub rssi_min: Option<i8>,
+    /// Strongest antenna signal in dBm seen from the network
+    #[pyo3(get)]
+    pub rssi_max: Option<i8>,
+    /// Channels the network has advertised, in the order first seen
+    pub channels: Vec<u8>,
+    /// Attribute changes observed during the session
+    #[pyo3(get)]
+    pub changes: Vec<NetworkChange>,
+    rssi_sum: f64,
+    rssi_samples: u32,
+}
+
+/// Change of a network attribute between two observations
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct NetworkChange {
+    /// Capture timestamp of the frame that carried the new value
+    #[pyo3(get)]
+    pub timestamp: f64,
+    /// Changed attribute: `ssid`, `channel`, `encryption` or `security`
+    #[pyo3(get)]
+    pub attribute: String,
+    #[pyo3(get)]
+    pub old_value: String,
+    #[pyo3(get)]
+    pub new_value: String,
+}
+
+#[pymethods]
+impl NetworkChange {
+    /// Get a summary of the change
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!("{}: {} -> {}", self.attribute, self.old_value, self.new_value)
+    }
 }
 
-/// Network data extracted from beacon frames
+/// Network data extracted from beacon and probe response frames
 #[derive(Debug, Clone)]
 pub struct NetworkData {
-    pub ssid: String,
+    /// Raw SSID bytes; empty for hidden networks
+    pub ssid: Vec<u8>,
     pub bssid: String,
     pub channel: u8,
-    pub rssi: i8,
+    pub rssi: Option<i8>,
+    pub frequency: Option<u16>,
     pub encryption: String,
+    pub security: Option<SecurityProfile>,
+    /// EAPOL-Key MIC length implied by the advertised AKMs
+    pub key_mic_length: Option<usize>,
+    /// The SSID element was empty or zero-filled
+    pub hidden: bool,
+    pub source: FrameSource,
+}
+
+/// SSID of a BSS named by a client's association or directed probe request
+#[derive(Debug, Clone)]
+pub struct SsidReveal {
+    pub bssid: String,
+    pub ssid: Vec<u8>,
+    pub source: FrameSource,
 }
 
 #[pymethods]
 impl WiFiNetwork {
     #[new]
+    #[pyo3(signature = (ssid, bssid, channel, rssi, encryption, frequency=None))]
     #[must_use]
-    pub fn new(ssid: String, bssid: String, channel: u8, rssi: i8, encryption: String) -> Self {
-        Self {
-            ssid,
+    pub fn new(
+        ssid: ByteString,
+        bssid: String,
+        channel: u8,
+        rssi: Option<i8>,
+        encryption: String,
+        frequency: Option<u16>,
+    ) -> Self {
+        let mut network = Self {
+            essid: ssid.into_bytes(),
             bssid,
             channel,
-            rssi,
+            rssi: None,
+            frequency,
             encryption,
-        }
+            security: None,
+            was_hidden: false,
+            revealed_by: None,
+            clients: Vec::new(),
+            beacon_count: 0,
+            first_seen: None,
+            last_seen: None,
+            rssi_min: None,
+            rssi_max: None,
+            channels: if channel == 0 { Vec::new() } else { vec![channel] },
+            changes: Vec::new(),
+            rssi_sum: 0.0,
+            rssi_samples: 0,
+        };
+        network.record_rssi(rssi);
+        network
+    }
+
+    /// SSID for display, with invalid UTF-8 replaced; hidden networks whose name
+    /// is unknown report a placeholder
+    #[getter]
+    #[must_use]
+    pub fn ssid(&self) -> String {
+        ssid_text(&self.essid)
+    }
+
+    /// SSID bytes as broadcast, which is what salts the PMK
+    #[getter(essid)]
+    #[must_use]
+    pub fn get_essid(&self) -> Vec<u8> {
+        self.essid.clone()
+    }
+
+    /// Channels the network has advertised, in the order first seen
+    ///
+    /// Returned as a list of ints, since `Vec<u8>` would convert to `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the list cannot be created.
+    #[getter]
+    pub fn channels<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        PyList::new(py, &self.channels)
+    }
+
+    /// Average antenna signal in dBm over all observations
+    #[getter]
+    #[must_use]
+    pub fn rssi_avg(&self) -> Option<f64> {
+        (self.rssi_samples > 0).then(|| self.rssi_sum / f64::from(self.rssi_samples))
+    }
+}
+
+/// Display form of raw SSID bytes, or the hidden placeholder when empty
+#[must_use]
+pub fn ssid_text(essid: &[u8]) -> String {
+    if essid.is_empty() {
+        HIDDEN_SSID.to_string()
+    } else {
+        String::from_utf8_lossy(essid).into_owned()
+    }
+}
+
+impl WiFiNetwork {
+    /// Record a signal measurement, keeping the latest value in `rssi`
+    pub fn record_rssi(&mut self, rssi: Option<i8>) {
+        let Some(rssi) = rssi else {
+            return;
+        };
+        self.rssi = Some(rssi);
+        self.rssi_min = Some(self.rssi_min.map_or(rssi, |min| min.min(rssi)));
+        self.rssi_max = Some(self.rssi_max.map_or(rssi, |max| max.max(rssi)));
+        self.rssi_sum += f64::from(rssi);
+        self.rssi_samples += 1;
+    }
+
+    /// Record the capture time of an observation
+    pub fn record_seen(&mut self, timestamp: f64) {
+        self.first_seen = Some(self.first_seen.map_or(timestamp, |first| first.min(timestamp)));
+        self.last_seen = Some(self.last_seen.map_or(timestamp, |last| last.max(timestamp)));
+    }
+
+    /// Record a change of `attribute` from `old_value` to `new_value`
+    pub fn record_change(&mut self, timestamp: f64, attribute: &str, old_value: String, new_value: String) {
+        self.changes.push(NetworkChange {
+            timestamp,
+            attribute: attribute.to_string(),
+            old_value,
+            new_value,
+        });
     }
 }
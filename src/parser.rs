@@ -1,94 +1,148 @@
-use crate::network::NetworkData;
+use crate::dot11::{
+    decode_ssid, format_mac, is_group_address, InformationElements, ManagementHeader, SUBTYPE_ASSOCIATION_REQUEST,
+    SUBTYPE_BEACON, SUBTYPE_PROBE_REQUEST, SUBTYPE_PROBE_RESPONSE, SUBTYPE_REASSOCIATION_REQUEST,
+};
+use crate::frame::Frame;
+use crate::network::{FrameSource, NetworkData, SsidReveal};
+use crate::radiotap::RadiotapHeader;
+use crate::security::{RsnElement, SecurityProfile};
+
+/// Capability information: data confidentiality required (WEP or better)
+const CAPABILITY_PRIVACY: u16 = 0x0010;
 
 /// Beacon frame parser for extracting network information
 pub struct BeaconParser;
 
 impl BeaconParser {
-    pub fn parse_beacon_frame(data: &[u8]) -> Option<NetworkData> {
-        if data.len() < 24 {
-            return None;
+    /// Parse a decoded frame as a beacon or probe response
+    pub fn parse_beacon_frame(frame: &Frame) -> Option<NetworkData> {
+        match frame {
+            Frame::Ieee80211 { data, radio } => Self::parse_ieee80211_beacon(data, radio.as_ref()),
+            Frame::Ethernet { .. } => None,
         }
+    }
 
-        // Check if this looks like a WiFi frame
-        let frame_control = u16::from_le_bytes([data[0], data[1]]);
-        let frame_type = (frame_control >> 2) & 0x03;
-        let frame_subtype = (frame_control >> 4) & 0x0F;
-        
-        // Management frame (type 0) with beacon subtype (8)
-        if frame_type != 0 || frame_subtype != 8 {
-            return None;
-        }
+    /// Parse a bare 802.11 beacon or probe response frame with optional radiotap metadata
+    pub fn parse_ieee80211_beacon(data: &[u8], radiotap: Option<&RadiotapHeader>) -> Option<NetworkData> {
+        let header = ManagementHeader::parse(data)?;
 
-        // Extract BSSID (source address)
-        if data.len() < 16 {
-            return None;
-        }
+        // Beacons and probe responses share the same body layout
+        let source = match header.subtype() {
+            SUBTYPE_BEACON => FrameSource::Beacon,
+            SUBTYPE_PROBE_RESPONSE => FrameSource::ProbeResponse,
+            _ => return None,
+        };
 
-        let bssid = format!("{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
-            data[10], data[11], data[12], data[13], data[14], data[15]);
+        // Extract BSSID (source address)
+        let bssid = format_mac(header.addr2);
 
         // Parse beacon frame for network data
-        let network_data = Self::extract_network_data(data);
-        
+        let elements = Self::extract_network_data(&data[header.body_offset..]);
+
+        // The DS Parameter Set names the AP's channel; fall back to the channel we received on
+        let channel = elements
+            .channel
+            .or_else(|| radiotap.and_then(RadiotapHeader::channel))
+            .unwrap_or(0);
+
+        let key_mic_length = elements.rsn.as_ref().or(elements.wpa.as_ref()).and_then(RsnElement::mic_length);
+        let security = SecurityProfile::from_elements(elements.rsn.as_ref(), elements.wpa.as_ref(), elements.privacy);
+        let encryption = security
+            .as_ref()
+            .map_or_else(|| "Open".to_string(), |profile| profile.encryption.clone());
+
         Some(NetworkData {
-            ssid: network_data.0,
+            hidden: elements.ssid.is_none(),
+            ssid: elements.ssid.unwrap_or_default(),
             bssid,
-            channel: network_data.1,
-            rssi: network_data.2,
-            encryption: network_data.3,
+            channel,
+            rssi: radiotap.and_then(|radiotap| radiotap.antenna_signal),
+            frequency: radiotap.and_then(|radiotap| radiotap.channel_frequency),
+            encryption,
+            security,
+            key_mic_length,
+            source,
+        })
+    }
+
+    /// Parse a client frame that names the SSID of a specific BSS
+    ///
+    /// Association and reassociation requests always carry the SSID of the target AP;
+    /// probe requests only count when directed at a BSSID rather than broadcast.
+    pub fn parse_ssid_reveal(frame: &Frame) -> Option<SsidReveal> {
+        let Frame::Ieee80211 { data, .. } = frame else {
+            return None;
+        };
+        let header = ManagementHeader::parse(data)?;
+
+        // Fixed parameters before the elements: capability (2), listen interval (2)
+        // and, for reassociation, the current AP address (6)
+        let (source, fixed_len) = match header.subtype() {
+            SUBTYPE_ASSOCIATION_REQUEST => (FrameSource::AssociationRequest, 4),
+            SUBTYPE_REASSOCIATION_REQUEST => (FrameSource::ReassociationRequest, 10),
+            SUBTYPE_PROBE_REQUEST if !is_group_address(header.addr3) => (FrameSource::ProbeRequest, 0),
+            _ => return None,
+        };
+
+        let body = data.get(header.body_offset + fixed_len..)?;
+        let ssid = InformationElements::new(body)
+            .find(|(element_id, _)| *element_id == 0)
+            .and_then(|(_, payload)| decode_ssid(payload))?;
+
+        Some(SsidReveal {
+            bssid: format_mac(header.addr3),
+            ssid,
+            source,
         })
     }
 
-    fn extract_network_data(data: &[u8]) -> (String, u8, i8, String) {
-        let mut ssid = "Hidden Network".to_string();
-        let mut encryption = "Open".to_string();
-        let mut channel = 6u8;
-        
-        if data.len() < 36 {
-            return (ssid, channel, -50, encryption);
+    fn extract_network_data(body: &[u8]) -> BeaconElements {
+        let mut elements = BeaconElements {
+            ssid: None,
+            channel: None,
+            rsn: None,
+            wpa: None,
+            privacy: false,
+        };
+
+        // Fixed parameters: timestamp (8), beacon interval (2), capability information (2)
+        if body.len() < 12 {
+            return elements;
         }
 
-        let mut offset = 36; // Start after fixed header
-        
+        let capabilities = u16::from_le_bytes([body[10], body[11]]);
+        elements.privacy = capabilities & CAPABILITY_PRIVACY != 0;
+
         // Parse information elements
-        while offset + 2 < data.len() {
-            let element_id = data[offset];
-            let element_len = data[offset + 1] as usize;
-            
-            if offset + 2 + element_len > data.len() {
-                break;
-            }
-            
+        for (element_id, payload) in InformationElements::new(&body[12..]) {
             match element_id {
                 0 => { // SSID element
-                    if element_len > 0 {
-                        let ssid_bytes = &data[offset + 2..offset + 2 + element_len];
-                        if let Ok(ssid_str) = String::from_utf8(ssid_bytes.to_vec()) {
-                            if !ssid_str.is_empty() {
-                                ssid = ssid_str;
-                            }
-                        }
-                    }
+                    elements.ssid = decode_ssid(payload);
                 },
-                3 => { // DS Parameter Set (Channel)
-                    if element_len >= 1 {
-                        channel = data[offset + 2];
-                    }
+                3 if !payload.is_empty() => { // DS Parameter Set (Channel)
+                    elements.channel = Some(payload[0]);
+                },
+                48 => { // RSN (WPA2/WPA3)
+                    elements.rsn = RsnElement::parse(payload);
                 },
-                48 => { // RSN (WPA/WPA2)
-                    if element_len >= 2 {
-                        encryption = "WPA2".to_string();
+                221 => { // Vendor specific (legacy WPA)
+                    if let Some(wpa) = RsnElement::parse_wpa_vendor(payload) {
+                        elements.wpa = Some(wpa);
                     }
                 },
                 _ => {} // Ignore other elements
             }
-            
-            offset += 2 + element_len;
         }
-        
-        // Calculate RSSI (simplified simulation)
-        let rssi = -50 - i8::try_from(data.len() % 30).unwrap_or(0);
-        
-        (ssid, channel, rssi, encryption)
+
+        elements
     }
 }
+
+/// Values collected from a beacon's fixed parameters and information elements
+struct BeaconElements {
+    ssid: Option<Vec<u8>>,
+    channel: Option<u8>,
+    rsn: Option<RsnElement>,
+    wpa: Option<RsnElement>,
+    privacy: bool,
+}
@@ -0,0 +1,461 @@
+use memmap2::Mmap;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::iter;
+use std::ops::Range;
+use std::path::Path;
+use thiserror::Error;
+use crate::cracker::{CandidateSource, CrackedHash, WordlistSource, MAX_PASSPHRASE_LEN, MIN_PASSPHRASE_LEN};
+use crate::crypto::{self, ByteString};
+use crate::hashfile::HashSource;
+use crate::hashline::MAX_ESSID_LENGTH;
+use crate::utils::to_hex;
+
+const MAGIC: &[u8; 8] = b"W3PMKDB\0";
+const VERSION: u32 = 1;
+/// Magic (8), version (4, little endian), reserved (4)
+const HEADER_LEN: usize = 16;
+/// ESSID length (1), ESSID (32), passphrase length (1), passphrase (63), PMK (32)
+const RECORD_LEN: usize = 1 + MAX_ESSID_LENGTH + 1 + MAX_PASSPHRASE_LEN + 32;
+const PASSPHRASE_OFFSET: usize = 1 + MAX_ESSID_LENGTH;
+const PMK_OFFSET: usize = PASSPHRASE_OFFSET + 1 + MAX_PASSPHRASE_LEN;
+/// Passphrases read from a wordlist per PBKDF2 batch
+const IMPORT_BATCH: usize = 4096;
+/// New records held in memory before they are sorted and spilled to a run file
+const RUN_RECORDS: usize = 1 << 18;
+
+type Record = [u8; RECORD_LEN];
+
+/// Errors opening or filling a PMK database
+#[derive(Debug, Error)]
+pub enum PmkDatabaseError {
+    #[error("Not a PMK database: bad signature {0:02x?}")]
+    Signature(Vec<u8>),
+    #[error("Unsupported PMK database version {0}; expected {VERSION}")]
+    Version(u32),
+    #[error("PMK database is truncated: {0} bytes is not a whole number of records")]
+    Size(usize),
+    #[error("ESSID must be 1 to {MAX_ESSID_LENGTH} bytes, got {0}")]
+    EssidLength(usize),
+}
+
+impl From<PmkDatabaseError> for PyErr {
+    fn from(error: PmkDatabaseError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// On-disk store of PMKs keyed by (ESSID, passphrase), like airolib-ng or genpmk
+///
+/// Records have a fixed size and are kept sorted, so lookups binary search the
+/// memory-mapped file. Imports spill sorted runs of new records next to the
+/// database and merge them into a new file that replaces the old one.
+#[pyclass]
+pub struct PmkDatabase {
+    #[pyo3(get)]
+    path: String,
+    map: Mmap,
+}
+
+#[pymethods]
+impl PmkDatabase {
+    /// Open a database, creating an empty one if `path` does not exist
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be read or created, or `ValueError` if it
+    /// is not a PMK database.
+    #[new]
+    pub fn open(path: &str) -> PyResult<Self> {
+        if !Path::new(path).exists() {
+            write_database(path, iter::empty(), iter::empty())?;
+        }
+        Ok(Self {
+            path: path.to_string(),
+            map: map_database(path)?,
+        })
+    }
+
+    /// Open or create a database and fill it with the PMKs of every wordlist
+    /// passphrase for every ESSID
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if a file cannot be read or written, or `ValueError` for an
+    /// invalid ESSID or database.
+    #[staticmethod]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn build(py: Python<'_>, path: &str, essids: Vec<ByteString>, wordlist: &str) -> PyResult<Self> {
+        let mut database = Self::open(path)?;
+        let essids: Vec<&[u8]> = essids.iter().map(ByteString::as_bytes).collect();
+        let mut source = WordlistSource::open(wordlist)?;
+        py.detach(|| database.import(&essids, &mut source))?;
+        Ok(database)
+    }
+
+    /// Add the PMKs of every wordlist passphrase for `essid`, returning the number
+    /// of new records
+    ///
+    /// Passphrases shorter than 8 or longer than 63 bytes are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if a file cannot be read or written, or `ValueError` for an
+    /// invalid ESSID.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn import_wordlist(&mut self, py: Python<'_>, essid: ByteString, wordlist: &str) -> PyResult<usize> {
+        let mut source = WordlistSource::open(wordlist)?;
+        py.detach(|| self.import(&[essid.as_bytes()], &mut source))
+    }
+
+    /// PMK stored for `essid` and `passphrase`, if any
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn lookup(&self, essid: ByteString, passphrase: ByteString) -> Option<Vec<u8>> {
+        self.find(essid.as_bytes(), passphrase.as_bytes())
+            .map(|index| pmk_of(self.record(index)).to_vec())
+    }
+
+    /// Check every PMK stored for the target's ESSID against a hash line or capture
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the target is not a valid hash line.
+    pub fn verify(&self, py: Python<'_>, target: HashSource) -> PyResult<Option<CrackedHash>> {
+        let target = target.into_line()?;
+        let range = self.essid_range(&target.essid);
+
+        Ok(py.detach(|| {
+            range.into_par_iter().find_map_any(|index| {
+                let record = self.record(index);
+                let pmk = pmk_of(record);
+                target.verify_pmk(&pmk).then(|| CrackedHash {
+                    target: target.clone(),
+                    passphrase: record_key(record).1.to_vec(),
+                    pmk: to_hex(&pmk),
+                })
+            })
+        }))
+    }
+
+    /// Distinct ESSIDs in the database, as bytes
+    #[must_use]
+    pub fn essids(&self) -> Vec<Vec<u8>> {
+        let mut essids: Vec<Vec<u8>> = Vec::new();
+        let mut index = 0;
+        while index < self.len() {
+            let essid = record_key(self.record(index)).0;
+            essids.push(essid.to_vec());
+            index = self.essid_range(essid).end;
+        }
+        essids
+    }
+
+    /// Number of records for `essid`, or in total
+    #[pyo3(signature = (essid=None))]
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn count(&self, essid: Option<ByteString>) -> usize {
+        essid.map_or_else(|| self.len(), |essid| self.essid_range(essid.as_bytes()).len())
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+impl PmkDatabase {
+    fn len(&self) -> usize {
+        (self.map.len() - HEADER_LEN) / RECORD_LEN
+    }
+
+    fn record(&self, index: usize) -> &[u8] {
+        let start = HEADER_LEN + index * RECORD_LEN;
+        &self.map[start..start + RECORD_LEN]
+    }
+
+    /// First record index for which `before` is false; records must be partitioned by it
+    fn partition_point(&self, before: impl Fn(&[u8]) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if before(self.record(middle)) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    /// Record indices for `essid`
+    fn essid_range(&self, essid: &[u8]) -> Range<usize> {
+        let start = self.partition_point(|record| record_key(record).0 < essid);
+        let end = self.partition_point(|record| record_key(record).0 <= essid);
+        start..end
+    }
+
+    /// Derive PMKs for every candidate of `source` and each of `essids`, and merge
+    /// the ones not stored yet into the database, returning their number
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an ESSID is invalid, the source cannot be read or the
+    /// database cannot be rewritten.
+    pub fn import(&mut self, essids: &[&[u8]], source: &mut dyn CandidateSource) -> PyResult<usize> {
+        if let Some(essid) = essids.iter().find(|essid| essid.is_empty() || essid.len() > MAX_ESSID_LENGTH) {
+            return Err(PmkDatabaseError::EssidLength(essid.len()).into());
+        }
+
+        let mut runs = Runs::new(&self.path);
+        let mut added: Vec<Record> = Vec::new();
+        let mut batch = Vec::with_capacity(IMPORT_BATCH);
+        loop {
+            batch.clear();
+            let more = source.fill(&mut batch, IMPORT_BATCH)?;
+            batch.retain(|candidate| (MIN_PASSPHRASE_LEN..=MAX_PASSPHRASE_LEN).contains(&candidate.len()));
+
+            let pairs: Vec<(&[u8], &Vec<u8>)> = essids
+                .iter()
+                .flat_map(|essid| batch.iter().map(move |candidate| (*essid, candidate)))
+                .filter(|(essid, candidate)| self.find(essid, candidate).is_none())
+                .collect();
+            added.par_extend(
+                pairs
+                    .into_par_iter()
+                    .map(|(essid, candidate)| encode_record(essid, candidate, &crypto::pmk(candidate, essid))),
+            );
+
+            if added.len() >= RUN_RECORDS || !more {
+                runs.spill(&mut added)?;
+            }
+            if !more {
+                break;
+            }
+        }
+        if runs.count == 0 {
+            return Ok(0);
+        }
+
+        let existing = (0..self.len()).map(|index| self.record(index));
+        let temporary = format!("{}.tmp", self.path);
+        let added = write_database(&temporary, existing, runs.merge()?)?;
+        fs::rename(&temporary, &self.path)?;
+        self.map = map_database(&self.path)?;
+        Ok(added)
+    }
+
+    /// Index of the record for `essid` and `passphrase`
+    fn find(&self, essid: &[u8], passphrase: &[u8]) -> Option<usize> {
+        let key = (essid, passphrase);
+        let index = self.partition_point(|record| record_key(record) < key);
+        (index < self.len() && record_key(self.record(index)) == key).then_some(index)
+    }
+}
+
+/// (ESSID, passphrase) of a record; lengths are clamped so a corrupt record cannot
+/// index past its end
+fn record_key(record: &[u8]) -> (&[u8], &[u8]) {
+    let essid_len = usize::from(record[0]).min(MAX_ESSID_LENGTH);
+    let passphrase_len = usize::from(record[PASSPHRASE_OFFSET]).min(MAX_PASSPHRASE_LEN);
+    (
+        &record[1..=essid_len],
+        &record[PASSPHRASE_OFFSET + 1..=PASSPHRASE_OFFSET + passphrase_len],
+    )
+}
+
+fn pmk_of(record: &[u8]) -> [u8; 32] {
+    let mut pmk = [0; 32];
+    pmk.copy_from_slice(&record[PMK_OFFSET..PMK_OFFSET + 32]);
+    pmk
+}
+
+fn encode_record(essid: &[u8], passphrase: &[u8], pmk: &[u8; 32]) -> Record {
+    let mut record = [0; RECORD_LEN];
+    record[0] = u8::try_from(essid.len()).unwrap_or_default();
+    record[1..=essid.len()].copy_from_slice(essid);
+    record[PASSPHRASE_OFFSET] = u8::try_from(passphrase.len()).unwrap_or_default();
+    record[PASSPHRASE_OFFSET + 1..=PASSPHRASE_OFFSET + passphrase.len()].copy_from_slice(passphrase);
+    record[PMK_OFFSET..].copy_from_slice(pmk);
+    record
+}
+
+/// Write a header and the union of two sorted, disjoint record streams in order,
+/// returning the number of added records
+fn write_database<'a>(
+    path: &str,
+    existing: impl Iterator<Item = &'a [u8]>,
+    mut added: impl Iterator<Item = io::Result<Record>>,
+) -> io::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&[0; HEADER_LEN - 12])?;
+
+    let mut count = 0;
+    let mut next = added.next().transpose()?;
+    for old in existing {
+        while let Some(new) = next.filter(|new| record_key(new) < record_key(old)) {
+            writer.write_all(&new)?;
+            count += 1;
+            next = added.next().transpose()?;
+        }
+        writer.write_all(old)?;
+    }
+    while let Some(new) = next {
+        writer.write_all(&new)?;
+        count += 1;
+        next = added.next().transpose()?;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Sorted run files of new records written during an import, removed when dropped
+struct Runs {
+    prefix: String,
+    count: usize,
+}
+
+impl Runs {
+    fn new(database: &str) -> Self {
+        Self {
+            prefix: database.to_string(),
+            count: 0,
+        }
+    }
+
+    fn path(&self, index: usize) -> String {
+        format!("{}.run{index}", self.prefix)
+    }
+
+    /// Sort and deduplicate `records` into a new run file, leaving it empty
+    fn spill(&mut self, records: &mut Vec<Record>) -> io::Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        records.par_sort_unstable_by(|a, b| record_key(a).cmp(&record_key(b)));
+        records.dedup_by(|a, b| record_key(a) == record_key(b));
+
+        let path = self.path(self.count);
+        self.count += 1;
+        let mut writer = BufWriter::new(File::create(path)?);
+        for record in records.drain(..) {
+            writer.write_all(&record)?;
+        }
+        writer.flush()
+    }
+
+    /// Records of every run in order, without duplicates
+    fn merge(&self) -> io::Result<RunMerge> {
+        let mut merge = RunMerge {
+            readers: Vec::with_capacity(self.count),
+            heap: BinaryHeap::with_capacity(self.count),
+            last: None,
+        };
+        for index in 0..self.count {
+            merge.readers.push(BufReader::new(File::open(self.path(index))?));
+            merge.advance(index)?;
+        }
+        Ok(merge)
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for index in 0..self.count {
+            let _ = fs::remove_file(self.path(index));
+        }
+    }
+}
+
+/// Next record of a run, ordered by its key
+struct Head {
+    record: Record,
+    run: usize,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        record_key(&self.record).cmp(&record_key(&other.record))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+/// K-way merge of sorted run files
+struct RunMerge {
+    readers: Vec<BufReader<File>>,
+    heap: BinaryHeap<Reverse<Head>>,
+    last: Option<Record>,
+}
+
+impl RunMerge {
+    /// Queue the next record of `run`, if it has one
+    fn advance(&mut self, run: usize) -> io::Result<()> {
+        let mut record = [0; RECORD_LEN];
+        match self.readers[run].read_exact(&mut record) {
+            Ok(()) => {
+                self.heap.push(Reverse(Head { record, run }));
+                Ok(())
+            }
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl Iterator for RunMerge {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse(Head { record, run })) = self.heap.pop() {
+            if let Err(error) = self.advance(run) {
+                return Some(Err(error));
+            }
+            // A passphrase repeated in the wordlist can land in several runs
+            if self.last.is_some_and(|last| record_key(&last) == record_key(&record)) {
+                continue;
+            }
+            self.last = Some(record);
+            return Some(Ok(record));
+        }
+        None
+    }
+}
+
+fn map_database(path: &str) -> PyResult<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: databases are only ever replaced by renaming a new file over them, so a
+    // mapped file is never modified in place
+    let map = unsafe { Mmap::map(&file)? };
+
+    if map.len() < HEADER_LEN || &map[..8] != MAGIC {
+        return Err(PmkDatabaseError::Signature(map[..map.len().min(8)].to_vec()).into());
+    }
+    let version = u32::from_le_bytes([map[8], map[9], map[10], map[11]]);
+    if version != VERSION {
+        return Err(PmkDatabaseError::Version(version).into());
+    }
+    if !(map.len() - HEADER_LEN).is_multiple_of(RECORD_LEN) {
+        return Err(PmkDatabaseError::Size(map.len()).into());
+    }
+    Ok(map)
+}
@@ -1,5 +1,11 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
+use crate::eapol::{EapolFrame, EapolKey, KeyMessage};
+use crate::frame::Frame;
+use crate::crypto::ByteString;
+use crate::hashline::{parse_hex_array, parse_mac, HashKind, HashLine, HashLineError};
+use crate::dot11::format_mac;
+use crate::utils::to_hex;
 
 /// PMKID capture data structure
 #[pyclass]
@@ -17,37 +23,66 @@ pub struct PMKIDCapture {
     pub pmkid: String,
     #[pyo3(get)]
     pub hashcat_format: String,
+    /// Handshake message the PMKID was taken from: "M1" (PMKID KDE) or "M2" (station RSN element)
+    #[pyo3(get)]
+    pub source_message: Option<String>,
+    /// Replay counter of the source frame
+    #[pyo3(get)]
+    pub replay_counter: Option<u64>,
+    /// Capture timestamp of the source frame (seconds since the epoch)
+    #[pyo3(get)]
+    pub timestamp: Option<f64>,
 }
 
-/// PMKID capture result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// PMKID found in an EAPOL-Key frame
+#[derive(Debug, Clone)]
 pub struct PMKIDData {
-    pub ssid: String,
     pub bssid: String,
     pub client_mac: String,
     pub pmkid: String,
-    pub hashcat_format: String,
+    pub source_message: KeyMessage,
+    pub replay_counter: u64,
 }
 
 #[pymethods]
 impl PMKIDCapture {
+    /// Create a capture, validating the fields that go into the hash line
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if a MAC address or the PMKID is malformed, or the SSID
+    /// is not 1 to 32 bytes.
     #[new]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(ssid: ByteString, bssid: &str, client_mac: &str, pmkid: &str) -> PyResult<Self> {
+        Ok(Self::try_new(ssid.as_bytes(), bssid, client_mac, pmkid)?)
+    }
+
+    /// Get the hashcat 22000 line for this capture
     #[must_use]
-    pub fn new(ssid: String, bssid: String, client_mac: String, pmkid: String) -> Self {
-        let hashcat_format = format!(
-            "WPA*01*{}*{}*{}*{}",
-            pmkid, bssid.replace(':', ""), client_mac.replace(':', ""), ssid
-        );
-        
-        Self {
-            ssid,
-            bssid,
-            client_mac,
-            pmkid,
-            hashcat_format,
-        }
+    pub fn get_hashcat_format(&self) -> String {
+        self.hashcat_format.clone()
+    }
+
+    /// Decode this capture's hash line
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the hash line is invalid.
+    pub fn to_hash_line(&self) -> PyResult<HashLine> {
+        Ok(self.hashcat_format.parse()?)
     }
 
+    /// Check a passphrase, or a raw 32-byte PMK, by recomputing the PMKID
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` unless exactly one of `passphrase` and `pmk` is given, or
+    /// if the PMK is not 32 bytes.
+    #[pyo3(signature = (passphrase=None, pmk=None))]
+    pub fn verify(&self, passphrase: Option<ByteString>, pmk: Option<&[u8]>) -> PyResult<bool> {
+        self.to_hash_line()?.verify(passphrase, pmk)
+    }
 
     /// Get a summary of the PMKID capture
     #[must_use]
@@ -59,185 +94,86 @@ impl PMKIDCapture {
     }
 }
 
-/// PMKID parser for extracting PMKID from EAPOL frames
-pub struct PMKIDParser;
-
-impl PMKIDParser {
-    /// Parse EAPOL frame for PMKID
-    pub fn parse_eapol_frame(data: &[u8]) -> Option<PMKIDData> {
-        if data.len() < 24 {
-            return None;
-        }
-
-        // Check if this is an EAPOL frame
-        if !Self::is_eapol_frame(data) {
-            return None;
-        }
-
-        // Extract MAC addresses
-        let bssid = Self::extract_mac_address(data, 4)?; // Source address
-        let client_mac = Self::extract_mac_address(data, 10)?; // Destination address
-
-        // Parse EAPOL payload for PMKID
-        let pmkid_data = Self::extract_pmkid_from_eapol(data)?;
-        
-        // Extract SSID from beacon frames (we'll need to correlate this)
-        let ssid = "Unknown".to_string(); // TODO: Correlate with beacon frames
-
-        Some(PMKIDData {
-            ssid: ssid.clone(),
-            bssid: bssid.clone(),
-            client_mac: client_mac.clone(),
-            pmkid: pmkid_data.clone(),
-            hashcat_format: format!(
-                "WPA*01*{}*{}*{}*{}",
-                pmkid_data, 
-                bssid.replace(':', ""), 
-                client_mac.replace(':', ""), 
-                ssid
-            ),
+impl PMKIDCapture {
+    /// Create a capture with MAC addresses and PMKID in canonical lowercase form
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fields cannot form a valid `WPA*01*` line.
+    pub fn try_new(essid: &[u8], bssid: &str, client_mac: &str, pmkid: &str) -> Result<Self, HashLineError> {
+        let line = HashLine::pmkid(
+            &parse_hex_array::<16>("PMKID", pmkid)?,
+            parse_mac(bssid)?,
+            parse_mac(client_mac)?,
+            essid,
+        )?;
+
+        Ok(Self {
+            ssid: line.essid_text(),
+            bssid: format_mac(line.ap),
+            client_mac: format_mac(line.station),
+            pmkid: to_hex(&line.hash),
+            hashcat_format: line.to_string(),
+            source_message: None,
+            replay_counter: None,
+            timestamp: None,
         })
     }
 
-    /// Check if packet is an EAPOL frame
-    fn is_eapol_frame(data: &[u8]) -> bool {
-        if data.len() < 24 {
-            return false;
-        }
-
-        // Check for EAPOL frame type (0x888E)
-        let ether_type = u16::from_be_bytes([data[12], data[13]]);
-        ether_type == 0x888E
-    }
-
-    /// Extract MAC address from packet
-    fn extract_mac_address(data: &[u8], offset: usize) -> Option<String> {
-        if data.len() < offset + 6 {
-            return None;
-        }
-
-        let mac_bytes = &data[offset..offset + 6];
-        Some(format!(
-            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
-            mac_bytes[0], mac_bytes[1], mac_bytes[2],
-            mac_bytes[3], mac_bytes[4], mac_bytes[5]
-        ))
-    }
-
-    /// Extract PMKID from EAPOL frame
-    fn extract_pmkid_from_eapol(data: &[u8]) -> Option<String> {
-        // EAPOL frame structure:
-        // - Ethernet header (14 bytes)
-        // - EAPOL header (4 bytes)
-        // - EAPOL payload
-        
-        if data.len() < 18 {
-            return None;
-        }
-
-        let eapol_header_start = 14;
-        let _ = data[eapol_header_start];
-        let packet_type = data[eapol_header_start + 1];
-        let body_length = u16::from_be_bytes([
-            data[eapol_header_start + 2],
-            data[eapol_header_start + 3]
-        ]);
-
-        // Check if this is an EAPOL-Key frame
-        if packet_type != 3 {
-            return None;
-        }
-
-        // Parse EAPOL-Key frame for PMKID
-        let key_data_start = eapol_header_start + 4;
-        if data.len() < key_data_start + body_length as usize {
-            return None;
-        }
-
-        // Look for RSN IE in the key data
-        Self::extract_pmkid_from_rsn_ie(&data[key_data_start..])
+    /// Create a capture from a decoded `WPA*01*` line, or `None` for other hash types
+    #[must_use]
+    pub fn from_line(line: &HashLine) -> Option<Self> {
+        (line.kind == HashKind::Pmkid).then(|| Self {
+            ssid: line.essid_text(),
+            bssid: line.bssid(),
+            client_mac: line.client_mac(),
+            pmkid: line.hash_hex(),
+            hashcat_format: line.to_string(),
+            source_message: None,
+            replay_counter: None,
+            timestamp: None,
+        })
     }
 
-    /// Extract PMKID from RSN Information Element
-    fn extract_pmkid_from_rsn_ie(key_data: &[u8]) -> Option<String> {
-        let mut offset = 0;
-        
-        // Skip EAPOL-Key header (16 bytes)
-        if key_data.len() < 16 {
-            return None;
-        }
-        offset += 16;
-
-        // Look for RSN IE (ID 48)
-        while offset + 2 < key_data.len() {
-            let element_id = key_data[offset];
-            let element_len = key_data[offset + 1] as usize;
-            
-            if offset + 2 + element_len > key_data.len() {
-                break;
-            }
-
-            if element_id == 48 { // RSN IE
-                return Self::parse_rsn_ie_for_pmkid(&key_data[offset + 2..offset + 2 + element_len]);
-            }
-            
-            offset += 2 + element_len;
-        }
-
-        None
+    /// Create a capture from a PMKID found at `timestamp`, recording its source frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SSID is too long for a hash line.
+    pub fn from_data(essid: &[u8], data: &PMKIDData, timestamp: f64) -> Result<Self, HashLineError> {
+        let mut capture = Self::try_new(essid, &data.bssid, &data.client_mac, &data.pmkid)?;
+        capture.source_message = Some(data.source_message.as_str().to_string());
+        capture.replay_counter = Some(data.replay_counter);
+        capture.timestamp = Some(timestamp);
+        Ok(capture)
     }
+}
 
-    /// Parse RSN IE for PMKID
-    fn parse_rsn_ie_for_pmkid(rsn_data: &[u8]) -> Option<String> {
-        if rsn_data.len() < 8 {
-            return None;
-        }
-
-        // RSN IE structure:
-        // - Version (2 bytes)
-        // - Group Cipher Suite (4 bytes)
-        // - Pairwise Cipher Suite Count (2 bytes)
-        // - Pairwise Cipher Suites (variable)
-        // - AKM Suite Count (2 bytes)
-        // - AKM Suites (variable)
-        // - RSN Capabilities (2 bytes)
-        // - PMKID Count (2 bytes) - if present
-        // - PMKID List (variable)
-
-        let mut offset = 8; // Skip version, group cipher, pairwise count, pairwise suites
-        
-        // Skip AKM suite count and AKM suites
-        if offset + 2 > rsn_data.len() {
-            return None;
-        }
-        let akm_count = u16::from_le_bytes([rsn_data[offset], rsn_data[offset + 1]]);
-        offset += 2 + (akm_count * 4) as usize;
-
-        // Skip RSN capabilities
-        if offset + 2 > rsn_data.len() {
-            return None;
-        }
-        offset += 2;
-
-        // Check for PMKID count
-        if offset + 2 > rsn_data.len() {
-            return None;
-        }
-        let pmkid_count = u16::from_le_bytes([rsn_data[offset], rsn_data[offset + 1]]);
-        offset += 2;
+/// PMKID parser for extracting PMKID from EAPOL frames
+pub struct PMKIDParser;
 
-        if pmkid_count > 0 && offset + 16 <= rsn_data.len() {
-            // Extract first PMKID (16 bytes)
-            let pmkid_bytes = &rsn_data[offset..offset + 16];
-            return Some(format!(
-                "{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-                pmkid_bytes[0], pmkid_bytes[1], pmkid_bytes[2], pmkid_bytes[3],
-                pmkid_bytes[4], pmkid_bytes[5], pmkid_bytes[6], pmkid_bytes[7],
-                pmkid_bytes[8], pmkid_bytes[9], pmkid_bytes[10], pmkid_bytes[11],
-                pmkid_bytes[12], pmkid_bytes[13], pmkid_bytes[14], pmkid_bytes[15]
-            ));
-        }
+impl PMKIDParser {
+    /// Parse EAPOL frame for PMKID
+    ///
+    /// Access points send the PMKID as a PMKID KDE in message 1; stations list
+    /// cached PMKIDs in the RSN element of message 2.
+    pub fn parse_eapol_frame(frame: &Frame) -> Option<PMKIDData> {
+        let eapol = EapolFrame::parse(frame)?;
+        let key = EapolKey::parse(&eapol, None)?;
+
+        let source_message = key.classify();
+        let pmkid = match source_message {
+            KeyMessage::M1 => key.pmkid_kde(),
+            KeyMessage::M2 => key.rsn_pmkid(),
+            _ => None,
+        }?;
 
-        None
+        Some(PMKIDData {
+            bssid: key.ap,
+            client_mac: key.station,
+            pmkid: to_hex(&pmkid),
+            source_message,
+            replay_counter: key.replay_counter,
+        })
     }
 }
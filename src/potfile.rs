@@ -0,0 +1,383 @@
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+use crate::cracker::CrackedHash;
+use crate::crypto;
+use crate::dot11::format_mac;
+use crate::hashfile::HashSource;
+use crate::hashline::{parse_hex_array, parse_mac, HashLine, HashLineError, MAX_ESSID_LENGTH};
+use crate::utils::{from_hex, to_hex};
+
+/// Length of a mode 22001 plain: the PMK as hex
+const PMK_HEX_LENGTH: usize = 64;
+
+/// Errors parsing a potfile line as a WPA entry
+#[derive(Debug, Error)]
+pub enum PotfileError {
+    #[error("Not a WPA potfile entry")]
+    NotWpa,
+    #[error("Malformed $HEX[] field: {0}")]
+    Hex(String),
+    #[error("ESSID must be 1 to {MAX_ESSID_LENGTH} bytes, got {0}")]
+    EssidLength(usize),
+    #[error(transparent)]
+    Line(#[from] HashLineError),
+}
+
+/// Cracked WPA hash from a hashcat potfile
+///
+/// Modes 22000, 22001 and 2500 store `hash:ap:sta:essid:plain`, where the hash is
+/// the PMKID or MIC; 16800 potfiles may instead keep the `pmkid*ap*sta*essid` hash
+/// line. For 22001 the plain is the PMK in hex.
+#[pyclass]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PotfileEntry {
+    pub hash: [u8; 16],
+    pub ap: [u8; 6],
+    pub station: [u8; 6],
+    pub essid: Vec<u8>,
+    pub plain: Vec<u8>,
+}
+
+#[pymethods]
+impl PotfileEntry {
+    /// PMKID or MIC as lowercase hex
+    #[getter(hash)]
+    #[must_use]
+    pub fn hash_hex(&self) -> String {
+        to_hex(&self.hash)
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn bssid(&self) -> String {
+        format_mac(self.ap)
+    }
+
+    #[getter]
+    #[must_use]
+    pub fn client_mac(&self) -> String {
+        format_mac(self.station)
+    }
+
+    /// ESSID decoded as UTF-8, with invalid sequences replaced
+    #[getter(essid)]
+    #[must_use]
+    pub fn essid_text(&self) -> String {
+        String::from_utf8_lossy(&self.essid).into_owned()
+    }
+
+    /// Passphrase decoded as UTF-8, or `None` for a mode 22001 PMK entry
+    #[getter]
+    #[must_use]
+    pub fn passphrase(&self) -> Option<String> {
+        self.stored_pmk()
+            .is_none()
+            .then(|| String::from_utf8_lossy(&self.plain).into_owned())
+    }
+
+    /// PMK stored by a mode 22001 entry, as hex
+    #[getter(pmk)]
+    #[must_use]
+    pub fn pmk_hex(&self) -> Option<String> {
+        self.stored_pmk().map(|pmk| to_hex(&pmk))
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PotfileEntry({:?})", self.to_string())
+    }
+}
+
+impl PotfileEntry {
+    /// Entry recording a hit of the built-in engine
+    #[must_use]
+    pub fn from_hit(hit: &CrackedHash) -> Self {
+        Self {
+            hash: hit.target.hash,
+            ap: hit.target.ap,
+            station: hit.target.station,
+            essid: hit.target.essid.clone(),
+            plain: hit.passphrase.clone(),
+        }
+    }
+
+    fn new(hash: [u8; 16], ap: &str, station: &str, essid: Vec<u8>, plain: Vec<u8>) -> Result<Self, PotfileError> {
+        if essid.is_empty() || essid.len() > MAX_ESSID_LENGTH {
+            return Err(PotfileError::EssidLength(essid.len()));
+        }
+        Ok(Self {
+            hash,
+            ap: parse_mac(ap)?,
+            station: parse_mac(station)?,
+            essid,
+            plain,
+        })
+    }
+
+    fn stored_pmk(&self) -> Option<[u8; 32]> {
+        if self.plain.len() != PMK_HEX_LENGTH {
+            return None;
+        }
+        std::str::from_utf8(&self.plain)
+            .ok()
+            .and_then(from_hex)
+            .and_then(|pmk| pmk.try_into().ok())
+    }
+
+    /// PMK of the entry: stored directly for 22001, else derived from the passphrase
+    #[must_use]
+    pub fn pmk(&self) -> [u8; 32] {
+        self.stored_pmk()
+            .unwrap_or_else(|| crypto::pmk(&self.plain, &self.essid))
+    }
+
+    /// Whether the entry was written for exactly this hash
+    #[must_use]
+    pub fn identifies(&self, line: &HashLine) -> bool {
+        self.hash == line.hash && self.ap == line.ap && self.station == line.station && self.essid == line.essid
+    }
+
+    /// Hit on `target`; 22001 entries carry no passphrase, only the PMK
+    fn hit(&self, target: &HashLine, pmk: &[u8; 32]) -> CrackedHash {
+        let passphrase = if self.stored_pmk().is_some() { Vec::new() } else { self.plain.clone() };
+        CrackedHash {
+            target: target.clone(),
+            passphrase,
+            pmk: to_hex(pmk),
+        }
+    }
+}
+
+impl fmt::Display for PotfileEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}",
+            to_hex(&self.hash),
+            to_hex(&self.ap),
+            to_hex(&self.station),
+            encode_field(&self.essid, false),
+            encode_field(&self.plain, true)
+        )
+    }
+}
+
+impl FromStr for PotfileEntry {
+    type Err = PotfileError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        // Hash lines never contain ':', so the plain is everything after the first one
+        if let Some((hash, plain)) = line.split_once(':').filter(|(hash, _)| hash.contains('*')) {
+            let plain = decode_field(plain)?;
+            if hash.starts_with("WPA*") {
+                let line: HashLine = hash.parse()?;
+                return Ok(Self {
+                    hash: line.hash,
+                    ap: line.ap,
+                    station: line.station,
+                    essid: line.essid,
+                    plain,
+                });
+            }
+            let fields: Vec<&str> = hash.split('*').collect();
+            let [pmkid, ap, station, essid] = fields[..] else {
+                return Err(PotfileError::NotWpa);
+            };
+            let essid = from_hex(essid).ok_or(HashLineError::Hex("ESSID"))?;
+            return Self::new(parse_hex_array("PMKID", pmkid)?, ap, station, essid, plain);
+        }
+
+        // ESSIDs containing ':' are written as $HEX[], so only the plain may hold more
+        let fields: Vec<&str> = line.splitn(5, ':').collect();
+        let [hash, ap, station, essid, plain] = fields[..] else {
+            return Err(PotfileError::NotWpa);
+        };
+        if hash.len() != 32 || ap.len() != 12 || station.len() != 12 {
+            return Err(PotfileError::NotWpa);
+        }
+        Self::new(
+            parse_hex_array("hash", hash)?,
+            ap,
+            station,
+            decode_field(essid)?,
+            decode_field(plain)?,
+        )
+    }
+}
+
+/// Field as hashcat writes it: verbatim if printable, else `$HEX[...]`; only the
+/// last field, the plain, may contain the ':' separator
+fn encode_field(bytes: &[u8], last: bool) -> String {
+    let printable = bytes.iter().all(|byte| (0x20..0x7f).contains(byte) && (last || *byte != b':'));
+    if printable && !bytes.starts_with(b"$HEX[") {
+        String::from_utf8_lossy(bytes).into_owned()
+    } else {
+        format!("$HEX[{}]", to_hex(bytes))
+    }
+}
+
+fn decode_field(field: &str) -> Result<Vec<u8>, PotfileError> {
+    match field.strip_prefix("$HEX[").and_then(|hex| hex.strip_suffix(']')) {
+        Some(hex) => from_hex(hex).ok_or_else(|| PotfileError::Hex(field.to_string())),
+        None => Ok(field.as_bytes().to_vec()),
+    }
+}
+
+/// hashcat potfile: WPA entries from it and new hits appended to it
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Potfile {
+    #[pyo3(get)]
+    pub path: String,
+    pub entries: Vec<PotfileEntry>,
+    /// Lines that are not WPA entries, such as hits of other hash modes
+    #[pyo3(get)]
+    pub ignored: usize,
+}
+
+#[pymethods]
+impl Potfile {
+    /// Read the WPA entries of a potfile; a missing file is an empty potfile
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file exists but cannot be read.
+    #[new]
+    pub fn open(path: &str) -> PyResult<Self> {
+        let mut potfile = Self {
+            path: path.to_string(),
+            entries: Vec::new(),
+            ignored: 0,
+        };
+        if !Path::new(path).exists() {
+            return Ok(potfile);
+        }
+
+        for line in BufReader::new(File::open(path)?).split(b'\n') {
+            let line = line?;
+            let Ok(line) = std::str::from_utf8(&line) else {
+                potfile.ignored += 1;
+                continue;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse() {
+                Ok(entry) => potfile.entries.push(entry),
+                Err(_) => potfile.ignored += 1,
+            }
+        }
+        Ok(potfile)
+    }
+
+    #[getter(entries)]
+    #[must_use]
+    pub fn get_entries(&self) -> Vec<PotfileEntry> {
+        self.entries.clone()
+    }
+
+    /// Hit for a hash line or capture, if an entry's passphrase or PMK cracks it
+    ///
+    /// Entries written for exactly this hash are tried first, then every other
+    /// entry for its ESSID, which finds new captures of already cracked networks.
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if the target is not a valid hash line.
+    pub fn lookup(&self, py: Python<'_>, target: HashSource) -> PyResult<Option<CrackedHash>> {
+        let target = target.into_line()?;
+        Ok(py.detach(|| self.find(&target)))
+    }
+
+    /// Split targets into hits found in the potfile and the hash lines still open
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` if a target is not a valid hash line.
+    pub fn partition(&self, py: Python<'_>, targets: Vec<HashSource>) -> PyResult<(Vec<CrackedHash>, Vec<HashLine>)> {
+        let targets = targets
+            .into_iter()
+            .map(HashSource::into_line)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let hits: Vec<Option<CrackedHash>> = py.detach(|| targets.par_iter().map(|target| self.find(target)).collect());
+        let mut found = Vec::new();
+        let mut open = Vec::new();
+        for (target, hit) in targets.into_iter().zip(hits) {
+            match hit {
+                Some(hit) => found.push(hit),
+                None => open.push(target),
+            }
+        }
+        Ok((found, open))
+    }
+
+    /// Append hits of the built-in engine in potfile format, skipping those
+    /// already recorded or without a passphrase, and return the number written
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the potfile cannot be written.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn append(&mut self, hits: Vec<CrackedHash>) -> PyResult<usize> {
+        let mut added = Vec::new();
+        for entry in hits.iter().filter(|hit| !hit.passphrase.is_empty()).map(PotfileEntry::from_hit) {
+            if !self.entries.contains(&entry) && !added.contains(&entry) {
+                added.push(entry);
+            }
+        }
+        if added.is_empty() {
+            return Ok(0);
+        }
+
+        let mut writer = BufWriter::new(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        for entry in &added {
+            writeln!(writer, "{entry}")?;
+        }
+        writer.flush()?;
+
+        let count = added.len();
+        self.entries.extend(added);
+        Ok(count)
+    }
+
+    fn __len__(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Potfile {
+    /// Hit for `target` from the first entry whose passphrase or PMK cracks it
+    #[must_use]
+    pub fn find(&self, target: &HashLine) -> Option<CrackedHash> {
+        let (exact, others): (Vec<&PotfileEntry>, Vec<&PotfileEntry>) = self
+            .entries
+            .iter()
+            .filter(|entry| entry.essid == target.essid)
+            .partition(|entry| entry.identifies(target));
+
+        let mut tried: Vec<&[u8]> = Vec::new();
+        for entry in exact.into_iter().chain(others) {
+            if tried.contains(&entry.plain.as_slice()) {
+                continue;
+            }
+            tried.push(&entry.plain);
+            let pmk = entry.pmk();
+            if target.verify_pmk(&pmk) {
+                return Some(entry.hit(target, &pmk));
+            }
+        }
+        None
+    }
+}
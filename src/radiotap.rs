@@ -0,0 +1,212 @@
+/// Radiotap flags: frame includes a trailing FCS
+pub const FLAG_FCS: u8 = 0x10;
+/// Radiotap flags: frame failed the FCS check
+pub const FLAG_BAD_FCS: u8 = 0x40;
+
+// Presence bits with special meaning in every bitmap word
+const PRESENT_RADIOTAP_NAMESPACE: u32 = 29;
+const PRESENT_VENDOR_NAMESPACE: u32 = 30;
+const PRESENT_EXT: u32 = 31;
+
+/// MCS information from the radiotap MCS field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McsInfo {
+    pub known: u8,
+    pub flags: u8,
+    pub index: u8,
+}
+
+/// Decoded radiotap header
+///
+/// Only the first radiotap namespace is used for per-packet values; additional
+/// namespaces (per-chain antenna data) and vendor namespaces are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RadiotapHeader {
+    /// Total header length; the 802.11 frame starts at this offset
+    pub length: usize,
+    pub tsft: Option<u64>,
+    pub flags: Option<u8>,
+    /// Legacy data rate in 500 kbps units
+    pub rate: Option<u8>,
+    pub channel_frequency: Option<u16>,
+    pub channel_flags: Option<u16>,
+    /// Antenna signal in dBm
+    pub antenna_signal: Option<i8>,
+    /// Antenna noise in dBm
+    pub antenna_noise: Option<i8>,
+    pub antenna: Option<u8>,
+    pub mcs: Option<McsInfo>,
+}
+
+impl RadiotapHeader {
+    /// Parse a radiotap header from the start of a packet
+    ///
+    /// Returns `None` if the data does not start with a valid version 0 radiotap header.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[0] != 0 {
+            return None;
+        }
+
+        let length = usize::from(u16::from_le_bytes([data[2], data[3]]));
+        if length < 8 || length > data.len() {
+            return None;
+        }
+        let header = &data[..length];
+
+        // Collect the presence bitmap words (bit 31 chains another word)
+        let mut present_words = Vec::new();
+        let mut offset = 4;
+        loop {
+            let word = u32::from_le_bytes(header.get(offset..offset + 4)?.try_into().ok()?);
+            present_words.push(word);
+            offset += 4;
+            if word & (1 << PRESENT_EXT) == 0 {
+                break;
+            }
+        }
+
+        let mut radiotap = Self {
+            length,
+            ..Self::default()
+        };
+
+        let mut namespace = Namespace::Radiotap;
+        let mut radiotap_namespaces = 0;
+        let mut word_index = 0;
+
+        for word in present_words {
+            if namespace == Namespace::Radiotap {
+                for bit in 0..PRESENT_RADIOTAP_NAMESPACE {
+                    if word & (1 << bit) == 0 {
+                        continue;
+                    }
+                    let field = word_index * 32 + bit;
+                    let Some(next) = radiotap.parse_field(header, offset, field, radiotap_namespaces == 0) else {
+                        // Unknown field: its size is unknown, so nothing after it can be located
+                        return Some(radiotap);
+                    };
+                    offset = next;
+                }
+            }
+
+            if word & (1 << PRESENT_RADIOTAP_NAMESPACE) != 0 {
+                namespace = Namespace::Radiotap;
+                radiotap_namespaces += 1;
+                word_index = 0;
+            } else if word & (1 << PRESENT_VENDOR_NAMESPACE) != 0 {
+                // Vendor namespace: OUI (3), sub namespace (1), skip length (2), then data
+                offset = align(offset, 2);
+                let skip = header.get(offset + 4..offset + 6)?;
+                offset += 6 + usize::from(u16::from_le_bytes([skip[0], skip[1]]));
+                namespace = Namespace::Vendor;
+                word_index = 0;
+            } else {
+                word_index += 1;
+            }
+        }
+
+        Some(radiotap)
+    }
+
+    /// Parse a single field, returning the offset just past it
+    ///
+    /// Values are only recorded for the first radiotap namespace.
+    fn parse_field(&mut self, header: &[u8], offset: usize, field: u32, record: bool) -> Option<usize> {
+        let (alignment, size) = field_layout(field)?;
+        let start = align(offset, alignment);
+        let bytes = header.get(start..start + size)?;
+
+        if record {
+            match field {
+                0 => self.tsft = Some(u64::from_le_bytes(bytes.try_into().ok()?)),
+                1 => self.flags = Some(bytes[0]),
+                2 => self.rate = Some(bytes[0]),
+                3 => {
+                    self.channel_frequency = Some(u16::from_le_bytes([bytes[0], bytes[1]]));
+                    self.channel_flags = Some(u16::from_le_bytes([bytes[2], bytes[3]]));
+                }
+                5 => self.antenna_signal = Some(i8::from_le_bytes([bytes[0]])),
+                6 => self.antenna_noise = Some(i8::from_le_bytes([bytes[0]])),
+                11 => self.antenna = Some(bytes[0]),
+                18 if self.channel_frequency.is_none() => {
+                    // XChannel: flags (4), frequency (2), channel (1), max power (1)
+                    self.channel_frequency = Some(u16::from_le_bytes([bytes[4], bytes[5]]));
+                    self.channel_flags = Some(u16::from_le_bytes([bytes[0], bytes[1]]));
+                }
+                19 => {
+                    self.mcs = Some(McsInfo {
+                        known: bytes[0],
+                        flags: bytes[1],
+                        index: bytes[2],
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Some(start + size)
+    }
+
+    /// Whether the captured frame carries a trailing 4-byte FCS
+    #[must_use]
+    pub fn has_fcs(&self) -> bool {
+        self.flags.is_some_and(|flags| flags & FLAG_FCS != 0)
+    }
+
+    /// Whether the driver reported a failed FCS check for the frame
+    #[must_use]
+    pub fn bad_fcs(&self) -> bool {
+        self.flags.is_some_and(|flags| flags & FLAG_BAD_FCS != 0)
+    }
+
+    /// Channel number derived from the channel frequency
+    #[must_use]
+    pub fn channel(&self) -> Option<u8> {
+        self.channel_frequency.and_then(frequency_to_channel)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Radiotap,
+    Vendor,
+}
+
+/// Alignment and size of the defined radiotap fields
+fn field_layout(field: u32) -> Option<(usize, usize)> {
+    let layout = match field {
+        // Flags, Rate, dBm antenna signal / noise, dBm TX power, antenna,
+        // dB antenna signal / noise, RTS retries, data retries, 0-length PSDU
+        1 | 2 | 5 | 6 | 10..=13 | 16 | 17 | 26 => (1, 1),
+        19 => (1, 3),                       // MCS
+        // FHSS, lock quality, TX attenuation, dB TX attenuation, RX flags, TX flags
+        4 | 7..=9 | 14 | 15 => (2, 2),
+        3 | 27 => (2, 4),                   // Channel, L-SIG
+        25 => (2, 6),                       // HE-MU-other-user
+        21 | 23 | 24 => (2, 12),            // VHT, HE, HE-MU
+        18 | 20 => (4, 8),                  // XChannel, A-MPDU status
+        0 => (8, 8),                        // TSFT
+        22 => (8, 12),                      // Timestamp
+        _ => return None,
+    };
+    Some(layout)
+}
+
+fn align(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
+}
+
+/// Convert a channel centre frequency in MHz to an IEEE channel number
+#[must_use]
+pub fn frequency_to_channel(frequency: u16) -> Option<u8> {
+    let channel = match frequency {
+        2484 => 14,
+        2412..=2472 => (frequency - 2407) / 5,
+        4915..=4980 => (frequency - 4000) / 5,
+        5000..=5925 => (frequency - 5000) / 5,
+        5955..=7115 => (frequency - 5950) / 5,
+        _ => return None,
+    };
+    u8::try_from(channel).ok()
+}
@@ -0,0 +1,495 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+use thiserror::Error;
+use crate::cracker::{CandidateSource, SourcePosition};
+use crate::crypto::ByteString;
+
+/// hashcat's rule buffer; functions whose output would not fit leave the word unchanged
+const RULE_BUFFER_LEN: usize = 256;
+
+/// Errors parsing a hashcat rule
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RuleError {
+    #[error("Unsupported rule opcode '{opcode}' at column {column}")]
+    Unsupported { opcode: char, column: usize },
+    #[error("Rule opcode '{opcode}' at column {column} is missing an argument")]
+    MissingArgument { opcode: char, column: usize },
+    #[error("Invalid position '{position}' for rule opcode '{opcode}' at column {column}; expected 0-9 or A-Z")]
+    Position { opcode: char, position: char, column: usize },
+}
+
+impl From<RuleError> for PyErr {
+    fn from(error: RuleError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
+
+/// Supported subset of hashcat rule functions
+///
+/// Positions `N` and `M` are written `0`-`9` then `A`-`Z` for 10-35, `X` and `Y`
+/// are literal bytes. Functions addressing a position past the end of the word,
+/// or growing it to 256 bytes or more, leave it unchanged, as hashcat does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOp {
+    /// `:` leave the word unchanged
+    Noop,
+    /// `l` lowercase
+    Lower,
+    /// `u` uppercase
+    Upper,
+    /// `c` capitalize the first letter, lowercase the rest
+    Capitalize,
+    /// `C` lowercase the first letter, uppercase the rest
+    InvertCapitalize,
+    /// `t` toggle the case of every letter
+    ToggleAll,
+    /// `TN` toggle the case of the letter at N
+    Toggle(usize),
+    /// `r` reverse
+    Reverse,
+    /// `d` duplicate the word
+    Duplicate,
+    /// `pN` append N copies of the word
+    DuplicateN(usize),
+    /// `f` append the reversed word
+    Reflect,
+    /// `{` rotate left by one
+    RotateLeft,
+    /// `}` rotate right by one
+    RotateRight,
+    /// `$X` append X
+    Append(u8),
+    /// `^X` prepend X
+    Prepend(u8),
+    /// `[` delete the first character
+    DeleteFirst,
+    /// `]` delete the last character
+    DeleteLast,
+    /// `DN` delete the character at N
+    Delete(usize),
+    /// `'N` truncate to N characters
+    Truncate(usize),
+    /// `xNM` keep M characters starting at N
+    Extract(usize, usize),
+    /// `iNX` insert X at N
+    Insert(usize, u8),
+    /// `oNX` overwrite the character at N with X
+    Overwrite(usize, u8),
+    /// `sXY` replace every X with Y
+    Replace(u8, u8),
+    /// `@X` remove every X
+    Purge(u8),
+}
+
+impl RuleOp {
+    fn apply(self, word: &mut Vec<u8>) {
+        if !self.fits(word.len()) {
+            return;
+        }
+        match self {
+            Self::Noop => {}
+            Self::Lower => word.make_ascii_lowercase(),
+            Self::Upper => word.make_ascii_uppercase(),
+            Self::Capitalize => {
+                word.make_ascii_lowercase();
+                if let Some(first) = word.first_mut() {
+                    first.make_ascii_uppercase();
+                }
+            }
+            Self::InvertCapitalize => {
+                word.make_ascii_uppercase();
+                if let Some(first) = word.first_mut() {
+                    first.make_ascii_lowercase();
+                }
+            }
+            Self::ToggleAll => word.iter_mut().for_each(toggle_case),
+            Self::Toggle(position) => {
+                if let Some(byte) = word.get_mut(position) {
+                    toggle_case(byte);
+                }
+            }
+            Self::Reverse => word.reverse(),
+            Self::Duplicate => word.extend_from_within(..),
+            Self::DuplicateN(copies) => *word = word.repeat(copies + 1),
+            Self::Reflect => {
+                let reversed: Vec<u8> = word.iter().rev().copied().collect();
+                word.extend_from_slice(&reversed);
+            }
+            Self::RotateLeft => {
+                if !word.is_empty() {
+                    word.rotate_left(1);
+                }
+            }
+            Self::RotateRight => {
+                if !word.is_empty() {
+                    word.rotate_right(1);
+                }
+            }
+            Self::Append(byte) => word.push(byte),
+            Self::Prepend(byte) => word.insert(0, byte),
+            Self::DeleteFirst => {
+                if !word.is_empty() {
+                    word.remove(0);
+                }
+            }
+            Self::DeleteLast => {
+                word.pop();
+            }
+            Self::Delete(position) => {
+                if position < word.len() {
+                    word.remove(position);
+                }
+            }
+            Self::Truncate(len) => word.truncate(len),
+            Self::Extract(start, len) => {
+                if start < word.len() && start.saturating_add(len) <= word.len() {
+                    word.truncate(start + len);
+                    word.drain(..start);
+                }
+            }
+            Self::Insert(position, byte) => {
+                if position <= word.len() {
+                    word.insert(position, byte);
+                }
+            }
+            Self::Overwrite(position, byte) => {
+                if let Some(slot) = word.get_mut(position) {
+                    *slot = byte;
+                }
+            }
+            Self::Replace(from, to) => {
+                for byte in word.iter_mut().filter(|byte| **byte == from) {
+                    *byte = to;
+                }
+            }
+            Self::Purge(byte) => word.retain(|other| *other != byte),
+        }
+    }
+
+    /// Whether the function's output for a word of `len` bytes fits hashcat's rule buffer
+    fn fits(self, len: usize) -> bool {
+        let output = match self {
+            Self::Duplicate | Self::Reflect => len.saturating_mul(2),
+            Self::DuplicateN(copies) => len.saturating_mul(copies.saturating_add(1)),
+            Self::Append(_) | Self::Prepend(_) | Self::Insert(..) => len + 1,
+            _ => len,
+        };
+        output < RULE_BUFFER_LEN
+    }
+}
+
+fn toggle_case(byte: &mut u8) {
+    if byte.is_ascii_lowercase() {
+        byte.make_ascii_uppercase();
+    } else if byte.is_ascii_uppercase() {
+        byte.make_ascii_lowercase();
+    }
+}
+
+/// One hashcat rule: a sequence of functions applied left to right
+#[pyclass]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    text: String,
+    ops: Vec<RuleOp>,
+}
+
+#[pymethods]
+impl Rule {
+    /// Parse a rule such as `c $1 $2`
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` for unsupported opcodes or malformed arguments.
+    #[new]
+    pub fn parse(text: &str) -> PyResult<Self> {
+        Ok(text.parse::<Self>()?)
+    }
+
+    /// Apply the rule to a word
+    #[pyo3(name = "apply")]
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn py_apply(&self, word: ByteString) -> Vec<u8> {
+        self.apply(word.as_bytes())
+    }
+
+    fn __str__(&self) -> String {
+        self.text.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Rule({:?})", self.text)
+    }
+}
+
+impl Rule {
+    /// Mutated copy of `word`
+    #[must_use]
+    pub fn apply(&self, word: &[u8]) -> Vec<u8> {
+        let mut word = word.to_vec();
+        for op in &self.ops {
+            op.apply(&mut word);
+        }
+        word
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    /// Functions may be separated by spaces; an empty rule leaves words unchanged
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bytes = text.as_bytes();
+        let mut ops = Vec::new();
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let opcode = char::from(bytes[index]);
+            let column = index + 1;
+            let argument = move |offset: usize| {
+                bytes.get(index + offset).copied().ok_or(RuleError::MissingArgument { opcode, column })
+            };
+            let position = move |byte: u8| match byte {
+                b'0'..=b'9' => Ok(usize::from(byte - b'0')),
+                b'A'..=b'Z' => Ok(usize::from(byte - b'A') + 10),
+                _ => Err(RuleError::Position {
+                    opcode,
+                    position: char::from(byte),
+                    column,
+                }),
+            };
+
+            let (op, width) = match opcode {
+                ' ' => {
+                    index += 1;
+                    continue;
+                }
+                ':' => (RuleOp::Noop, 1),
+                'l' => (RuleOp::Lower, 1),
+                'u' => (RuleOp::Upper, 1),
+                'c' => (RuleOp::Capitalize, 1),
+                'C' => (RuleOp::InvertCapitalize, 1),
+                't' => (RuleOp::ToggleAll, 1),
+                'T' => (RuleOp::Toggle(position(argument(1)?)?), 2),
+                'r' => (RuleOp::Reverse, 1),
+                'd' => (RuleOp::Duplicate, 1),
+                'p' => (RuleOp::DuplicateN(position(argument(1)?)?), 2),
+                'f' => (RuleOp::Reflect, 1),
+                '{' => (RuleOp::RotateLeft, 1),
+                '}' => (RuleOp::RotateRight, 1),
+                '$' => (RuleOp::Append(argument(1)?), 2),
+                '^' => (RuleOp::Prepend(argument(1)?), 2),
+                '[' => (RuleOp::DeleteFirst, 1),
+                ']' => (RuleOp::DeleteLast, 1),
+                'D' => (RuleOp::Delete(position(argument(1)?)?), 2),
+                '\'' => (RuleOp::Truncate(position(argument(1)?)?), 2),
+                'x' => (RuleOp::Extract(position(argument(1)?)?, position(argument(2)?)?), 3),
+                'i' => (RuleOp::Insert(position(argument(1)?)?, argument(2)?), 3),
+                'o' => (RuleOp::Overwrite(position(argument(1)?)?, argument(2)?), 3),
+                's' => (RuleOp::Replace(argument(1)?, argument(2)?), 3),
+                '@' => (RuleOp::Purge(argument(1)?), 2),
+                _ => return Err(RuleError::Unsupported { opcode, column }),
+            };
+            ops.push(op);
+            index += width;
+        }
+
+        Ok(Self {
+            text: text.to_string(),
+            ops,
+        })
+    }
+}
+
+/// Ordered list of rules, as loaded from a hashcat `.rule` file
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+    /// Lines left out by a lenient load, as `line N: error`
+    #[pyo3(get)]
+    pub skipped: Vec<String>,
+}
+
+#[pymethods]
+impl RuleSet {
+    /// Create a rule set from rule strings
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` naming the first rule that cannot be parsed.
+    #[new]
+    #[pyo3(signature = (rules=Vec::new()))]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(rules: Vec<String>) -> PyResult<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| rule.parse())
+            .collect::<Result<Vec<Rule>, _>>()?;
+        Ok(Self {
+            rules,
+            skipped: Vec::new(),
+        })
+    }
+
+    /// Read a `.rule` file, one rule per line, skipping blank lines and `#` comments
+    ///
+    /// With `skip_invalid`, unsupported rules are left out and listed in `skipped`
+    /// instead of failing the load.
+    ///
+    /// # Errors
+    ///
+    /// Raises `OSError` if the file cannot be read, or `ValueError` listing every
+    /// unsupported rule by line number unless `skip_invalid` is set.
+    #[staticmethod]
+    #[pyo3(signature = (path, skip_invalid=false))]
+    pub fn load(path: &str, skip_invalid: bool) -> PyResult<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut set = Self::default();
+
+        for (index, text) in reader.lines().enumerate() {
+            let text = text?;
+            let text = text.trim_end_matches('\r');
+            if text.trim().is_empty() || text.starts_with('#') {
+                continue;
+            }
+            match text.parse() {
+                Ok(rule) => set.rules.push(rule),
+                Err(error) => set.skipped.push(format!("line {}: {error}", index + 1)),
+            }
+        }
+
+        if !skip_invalid && !set.skipped.is_empty() {
+            return Err(PyValueError::new_err(format!("{path}: {}", set.skipped.join("; "))));
+        }
+        Ok(set)
+    }
+
+    /// Every rule applied to `word`, in order
+    #[pyo3(name = "apply")]
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn py_apply(&self, word: ByteString) -> Vec<Vec<u8>> {
+        self.rules.iter().map(|rule| rule.apply(word.as_bytes())).collect()
+    }
+
+    #[getter(rules)]
+    #[must_use]
+    pub fn get_rules(&self) -> Vec<Rule> {
+        self.rules.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+/// Candidates produced by applying every rule to every word of another source
+///
+/// Words are pulled from the inner source only as mutations are needed, so the
+/// full word × rule product is never held in memory.
+pub struct RuleSource<'a> {
+    inner: &'a mut dyn CandidateSource,
+    rules: Vec<Rule>,
+    /// Buffered words with the inner position each was read from
+    words: Vec<(SourcePosition, Vec<u8>)>,
+    word: usize,
+    rule: usize,
+    inner_exhausted: bool,
+}
+
+impl<'a> RuleSource<'a> {
+    #[must_use]
+    pub fn new(inner: &'a mut dyn CandidateSource, rules: Vec<Rule>) -> Self {
+        Self {
+            inner,
+            rules,
+            words: Vec::new(),
+            word: 0,
+            rule: 0,
+            inner_exhausted: false,
+        }
+    }
+
+    /// Buffer up to `limit` words, one at a time so each keeps its position
+    fn refill(&mut self, limit: usize) -> io::Result<()> {
+        self.words.clear();
+        self.word = 0;
+        let mut word = Vec::with_capacity(1);
+        while self.words.len() < limit && !self.inner_exhausted {
+            let position = self.inner.position();
+            self.inner_exhausted = !self.inner.fill(&mut word, 1)?;
+            if let Some(word) = word.pop() {
+                self.words.push((position, word));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CandidateSource for RuleSource<'_> {
+    fn fill(&mut self, batch: &mut Vec<Vec<u8>>, limit: usize) -> io::Result<bool> {
+        if self.rules.is_empty() {
+            return self.inner.fill(batch, limit);
+        }
+
+        let target = batch.len() + limit;
+        while batch.len() < target {
+            if self.word == self.words.len() {
+                if self.inner_exhausted {
+                    return Ok(false);
+                }
+                self.refill((limit / self.rules.len()).max(1))?;
+                continue;
+            }
+
+            batch.push(self.rules[self.rule].apply(&self.words[self.word].1));
+            self.rule += 1;
+            if self.rule == self.rules.len() {
+                self.rule = 0;
+                self.word += 1;
+            }
+        }
+        Ok(!(self.inner_exhausted && self.word == self.words.len()))
+    }
+
+    fn fraction(&self) -> Option<f64> {
+        self.inner.fraction()
+    }
+
+    fn position(&self) -> SourcePosition {
+        match self.words.get(self.word) {
+            Some((position, _)) => SourcePosition {
+                rule: self.rule,
+                ..*position
+            },
+            None => self.inner.position(),
+        }
+    }
+
+    fn seek(&mut self, position: SourcePosition) -> io::Result<()> {
+        if position.rule > 0 && position.rule >= self.rules.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Rule index {} is past the last rule", position.rule),
+            ));
+        }
+        self.inner.seek(SourcePosition { rule: 0, ..position })?;
+        self.words.clear();
+        self.word = 0;
+        self.rule = position.rule;
+        self.inner_exhausted = false;
+        Ok(())
+    }
+}
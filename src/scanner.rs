@@ -1,12 +1,19 @@
 use pyo3::prelude::*;
-use pcap::{Device, Capture};
+use pcap::{Activated, Capture, Device, PacketHeader};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::eapol::{EapolFrame, EapolKey};
+use crate::frame::{Frame, FrameDecoder};
+use crate::handshake::{HandshakeCapture, HandshakeTracker, DEFAULT_HANDSHAKE_WINDOW};
 use crate::network::WiFiNetwork;
 use crate::parser::BeaconParser;
 use crate::builder::NetworkBuilder;
+use crate::dot11::format_mac;
 use crate::utils::create_runtime_error;
 use crate::pmkid::{PMKIDCapture, PMKIDParser};
+use crate::station::{Station, StationParser};
 
 /// Device manager for handling network interface operations
 pub struct DeviceManager {
@@ -61,11 +68,129 @@ impl DeviceManager {
     }
 }
 
+/// Where captured packets come from
+#[derive(Debug, Clone)]
+pub enum CaptureSource {
+    /// Live capture on a network interface
+    Interface(String),
+    /// Offline capture from a pcap or pcapng file
+    File(PathBuf),
+}
+
+impl CaptureSource {
+    /// Open a capture handle for this source
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface cannot be found or the file cannot be opened.
+    pub fn open(&self) -> PyResult<Capture<dyn Activated>> {
+        match self {
+            Self::Interface(interface) => {
+                let device_manager = DeviceManager::new(interface.clone());
+                let device = device_manager.find_device()?;
+
+                let cap = Capture::from_device(device)
+                    .map_err(|e| create_runtime_error(&format!("Failed to create capture: {e}")))?
+                    .promisc(true)
+                    .timeout(1000)
+                    .open()
+                    .map_err(|e| create_runtime_error(&format!("Failed to open capture: {e}")))?;
+                Ok(cap.into())
+            }
+            Self::File(path) => {
+                let cap = Capture::from_file(path).map_err(|e| {
+                    create_runtime_error(&format!("Failed to open capture file '{}': {e}", path.display()))
+                })?;
+                Ok(cap.into())
+            }
+        }
+    }
+
+    /// Whether packets arrive in real time (as opposed to being read from a file)
+    #[must_use]
+    pub fn is_live(&self) -> bool {
+        matches!(self, Self::Interface(_))
+    }
+}
+
+/// How long a live capture runs before returning
+///
+/// File sources ignore the budget and are always read to the end, so that
+/// offline analysis sees exactly the frames a live capture would have seen.
+#[derive(Debug, Clone, Copy)]
+pub enum CaptureBudget {
+    /// Stop after this many packets, or at the first read timeout
+    Packets(usize),
+    /// Keep capturing until this much time has passed
+    Duration(Duration),
+}
+
 /// Network scanner for `WiFi` networks
 #[pyclass]
 pub struct NetworkScanner {
-    interface: String,
-    ssid_cache: Mutex<HashMap<String, String>>, // BSSID -> SSID mapping
+    source: CaptureSource,
+    ssid_cache: Mutex<HashMap<String, Vec<u8>>>, // BSSID -> raw SSID mapping
+}
+
+impl NetworkScanner {
+    /// Decode every packet from the capture source and feed the frames to `handle`
+    /// along with their capture timestamps (seconds since the epoch)
+    ///
+    /// # Errors
+    ///
+    /// Returns `UnsupportedLinkTypeError` if the capture's link type cannot be decoded.
+    fn for_each_frame<F>(&self, budget: CaptureBudget, mut handle: F) -> PyResult<()>
+    where
+        F: FnMut(&Frame, f64),
+    {
+        let mut cap = self.source.open()?;
+        let decoder = FrameDecoder::new(cap.get_datalink())?;
+        let mut handle = |header: &PacketHeader, data: &[u8]| {
+            if let Some(frame) = decoder.decode(data) {
+                handle(&frame, packet_timestamp(header));
+            }
+        };
+
+        if !self.source.is_live() {
+            loop {
+                match cap.next_packet() {
+                    Ok(packet) => handle(packet.header, packet.data),
+                    Err(pcap::Error::NoMorePackets) => return Ok(()),
+                    Err(e) => return Err(create_runtime_error(&format!("Failed to read capture file: {e}"))),
+                }
+            }
+        }
+
+        match budget {
+            CaptureBudget::Packets(max_packets) => {
+                let mut packet_count = 0;
+                while let Ok(packet) = cap.next_packet() {
+                    packet_count += 1;
+                    if packet_count > max_packets {
+                        break;
+                    }
+                    handle(packet.header, packet.data);
+                }
+            }
+            CaptureBudget::Duration(duration) => {
+                let start_time = Instant::now();
+                while start_time.elapsed() < duration {
+                    if let Ok(packet) = cap.next_packet() {
+                        handle(packet.header, packet.data);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a pcap packet timestamp to seconds since the epoch
+fn packet_timestamp(header: &PacketHeader) -> f64 {
+    let seconds = u64::try_from(header.ts.tv_sec).unwrap_or(0);
+    let micros = u32::try_from(header.ts.tv_usec).unwrap_or(0);
+    Duration::new(seconds, micros.saturating_mul(1000)).as_secs_f64()
 }
 
 #[pymethods]
@@ -74,11 +199,35 @@ impl NetworkScanner {
     #[must_use]
     pub fn new(interface: String) -> Self {
         Self { 
-            interface,
+            source: CaptureSource::Interface(interface),
             ssid_cache: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Create a scanner that reads frames from a pcap or pcapng file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not exist.
+    #[staticmethod]
+    pub fn from_file(path: PathBuf) -> PyResult<Self> {
+        if !path.is_file() {
+            return Err(create_runtime_error(&format!("Capture file '{}' not found", path.display())));
+        }
+
+        Ok(Self {
+            source: CaptureSource::File(path),
+            ssid_cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Whether this scanner captures from a live interface
+    #[getter]
+    #[must_use]
+    pub fn is_live(&self) -> bool {
+        self.source.is_live()
+    }
+
     /// Test method to verify `PyO3` bindings work
     ///
     /// # Errors
@@ -92,42 +241,48 @@ impl NetworkScanner {
     ///
     /// # Errors
     ///
-    /// Returns an error if the network interface cannot be found or if packet capture fails.
+    /// Returns an error if the capture source cannot be opened or if packet capture fails.
     pub fn scan(&self) -> PyResult<Vec<WiFiNetwork>> {
-        let device_manager = DeviceManager::new(self.interface.clone());
-        let device = device_manager.find_device()?;
-        
-        let mut cap = Capture::from_device(device)
-            .map_err(|e| create_runtime_error(&format!("Failed to create capture: {e}")))?
-            .promisc(true)
-            .timeout(1000)
-            .open()
-            .map_err(|e| create_runtime_error(&format!("Failed to open capture: {e}")))?;
-        
         let mut network_builder = NetworkBuilder::new();
-        let _ = BeaconParser;
         
         // Capture packets for a short duration
-        let mut packet_count = 0;
         let max_packets = 100; // Limit to prevent infinite scanning
         
-        while let Ok(packet) = cap.next_packet() {
-            packet_count += 1;
-            if packet_count > max_packets {
-                break;
-            }
-            
+        self.for_each_frame(CaptureBudget::Packets(max_packets), |frame, timestamp| {
             // Parse WiFi beacon frames using the dedicated parser
-            if let Some(network_data) = BeaconParser::parse_beacon_frame(packet.data) {
-                network_builder.add_network(network_data);
+            if let Some(network_data) = BeaconParser::parse_beacon_frame(frame) {
+                network_builder.add_network(network_data, timestamp);
+            } else if let Some(reveal) = BeaconParser::parse_ssid_reveal(frame) {
+                network_builder.add_reveal(reveal);
             }
-        }
+
+            if let Some(observation) = StationParser::parse_station_frame(frame) {
+                network_builder.add_station(observation, timestamp);
+            }
+        })?;
         
         Ok(network_builder.get_networks())
     }
 
+    /// Scan for client stations, including ones that are not associated
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the capture source cannot be opened or if packet capture fails.
+    pub fn scan_stations(&self) -> PyResult<Vec<Station>> {
+        let mut network_builder = NetworkBuilder::new();
 
-    /// Get SSID cache for correlation
+        self.for_each_frame(CaptureBudget::Packets(100), |frame, timestamp| {
+            if let Some(observation) = StationParser::parse_station_frame(frame) {
+                network_builder.add_station(observation, timestamp);
+            }
+        })?;
+
+        Ok(network_builder.get_stations())
+    }
+
+
+    /// Get SSID cache for correlation, with SSIDs decoded for display
     ///
     /// # Errors
     ///
@@ -135,7 +290,10 @@ impl NetworkScanner {
     pub fn get_ssid_cache(&self) -> PyResult<HashMap<String, String>> {
         let cache = self.ssid_cache.lock()
             .map_err(|e| create_runtime_error(&format!("Failed to lock cache: {e}")))?;
-        Ok(cache.clone())
+        Ok(cache
+            .iter()
+            .map(|(bssid, ssid)| (bssid.clone(), String::from_utf8_lossy(ssid).into_owned()))
+            .collect())
     }
 
     /// Clear SSID cache
@@ -152,101 +310,155 @@ impl NetworkScanner {
 
     /// Capture PMKID from EAPOL frames with SSID correlation
     ///
+    /// SSIDs are looked up in the cache once the capture ends, so a PMKID may
+    /// precede the frame naming its network. PMKIDs of networks with no SSID in
+    /// the cache are skipped, as the SSID salts the PMK and cannot be guessed.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the network interface cannot be found or if packet capture fails.
+    /// Returns an error if the capture source cannot be opened, if packet capture
+    /// fails or if the cache lock cannot be acquired.
     pub fn capture_pmkid_with_correlation(&self, duration_seconds: u32) -> PyResult<Vec<PMKIDCapture>> {
-        let device_manager = DeviceManager::new(self.interface.clone());
-        let device = device_manager.find_device()?;
-        
-        let mut cap = Capture::from_device(device)
-            .map_err(|e| create_runtime_error(&format!("Failed to create capture: {e}")))?
-            .promisc(true)
-            .timeout(1000)
-            .open()
-            .map_err(|e| create_runtime_error(&format!("Failed to open capture: {e}")))?;
-        
-        let _ = PMKIDParser;
-        let _ = BeaconParser;
-        let mut pmkid_captures = Vec::new();
+        let mut found = Vec::new();
         
         // Capture packets for specified duration
-        let start_time = std::time::Instant::now();
-        let duration = std::time::Duration::from_secs(u64::from(duration_seconds));
+        let duration = Duration::from_secs(u64::from(duration_seconds));
         
-        while start_time.elapsed() < duration {
-            if let Ok(packet) = cap.next_packet() {
-                // First, try to parse as beacon frame to update SSID cache
-                if let Some(network_data) = BeaconParser::parse_beacon_frame(packet.data) {
-                    if let Ok(mut cache) = self.ssid_cache.lock() {
-                        cache.insert(network_data.bssid.clone(), network_data.ssid.clone());
-                    }
-                }
-                
-                // Then, try to parse as EAPOL frame for PMKID
-                if let Some(pmkid_data) = PMKIDParser::parse_eapol_frame(packet.data) {
-                    // Use cached SSID if available, otherwise use "Unknown"
-                    let ssid = if let Ok(cache) = self.ssid_cache.lock() {
-                        cache.get(&pmkid_data.bssid)
-                            .cloned()
-                            .unwrap_or_else(|| "Unknown".to_string())
-                    } else {
-                        "Unknown".to_string()
-                    };
-                    
-                    let pmkid_capture = PMKIDCapture::new(
-                        ssid,
-                        pmkid_data.bssid,
-                        pmkid_data.client_mac,
-                        pmkid_data.pmkid,
-                    );
-                    pmkid_captures.push(pmkid_capture);
+        self.for_each_frame(CaptureBudget::Duration(duration), |frame, timestamp| {
+            // First, learn SSIDs from beacons, probe responses and client requests
+            let named = BeaconParser::parse_beacon_frame(frame)
+                .filter(|network_data| !network_data.hidden)
+                .map(|network_data| (network_data.bssid, network_data.ssid))
+                .or_else(|| BeaconParser::parse_ssid_reveal(frame).map(|reveal| (reveal.bssid, reveal.ssid)));
+            if let Some((bssid, ssid)) = named {
+                if let Ok(mut cache) = self.ssid_cache.lock() {
+                    cache.insert(bssid, ssid);
                 }
             }
-        }
-        
-        Ok(pmkid_captures)
+            
+            // Then, try to parse as EAPOL frame for PMKID
+            if let Some(pmkid_data) = PMKIDParser::parse_eapol_frame(frame) {
+                found.push((pmkid_data, timestamp));
+            }
+        })?;
+
+        let cache = self.ssid_cache.lock()
+            .map_err(|e| create_runtime_error(&format!("Failed to lock cache: {e}")))?;
+        Ok(found
+            .iter()
+            .filter_map(|(data, timestamp)| {
+                let ssid = cache.get(&data.bssid)?;
+                PMKIDCapture::from_data(ssid, data, *timestamp).ok()
+            })
+            .collect())
     }
 
     /// Capture PMKID from EAPOL frames (legacy method)
     ///
+    /// Unlike `capture_pmkid_with_correlation`, SSIDs are only learned from frames
+    /// of this capture and the cache is left alone. PMKIDs of networks whose SSID
+    /// never appears in the capture are skipped once it ends.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the network interface cannot be found or if packet capture fails.
+    /// Returns an error if the capture source cannot be opened or if packet capture fails.
     pub fn capture_pmkid(&self, duration_seconds: u32) -> PyResult<Vec<PMKIDCapture>> {
-        let device_manager = DeviceManager::new(self.interface.clone());
-        let device = device_manager.find_device()?;
-        
-        let mut cap = Capture::from_device(device)
-            .map_err(|e| create_runtime_error(&format!("Failed to create capture: {e}")))?
-            .promisc(true)
-            .timeout(1000)
-            .open()
-            .map_err(|e| create_runtime_error(&format!("Failed to open capture: {e}")))?;
-        
-        let _ = PMKIDParser;
-        let mut pmkid_captures = Vec::new();
+        let mut found = Vec::new();
+        let mut ssids: HashMap<String, Vec<u8>> = HashMap::new();
         
         // Capture packets for specified duration
-        let start_time = std::time::Instant::now();
-        let duration = std::time::Duration::from_secs(u64::from(duration_seconds));
+        let duration = Duration::from_secs(u64::from(duration_seconds));
         
-        while start_time.elapsed() < duration {
-            if let Ok(packet) = cap.next_packet() {
-                // Parse EAPOL frames for PMKID
-                if let Some(pmkid_data) = PMKIDParser::parse_eapol_frame(packet.data) {
-                    let pmkid_capture = PMKIDCapture::new(
-                        pmkid_data.ssid,
-                        pmkid_data.bssid,
-                        pmkid_data.client_mac,
-                        pmkid_data.pmkid,
-                    );
-                    pmkid_captures.push(pmkid_capture);
+        self.for_each_frame(CaptureBudget::Duration(duration), |frame, timestamp| {
+            if let Some(network_data) = BeaconParser::parse_beacon_frame(frame) {
+                if !network_data.hidden {
+                    ssids.insert(network_data.bssid, network_data.ssid);
                 }
+            } else if let Some(reveal) = BeaconParser::parse_ssid_reveal(frame) {
+                ssids.entry(reveal.bssid).or_insert(reveal.ssid);
+            } else if let Some(pmkid_data) = PMKIDParser::parse_eapol_frame(frame) {
+                found.push((pmkid_data, timestamp));
             }
-        }
+        })?;
         
-        Ok(pmkid_captures)
+        Ok(found
+            .iter()
+            .filter_map(|(data, timestamp)| {
+                let ssid = ssids.get(&data.bssid)?;
+                PMKIDCapture::from_data(ssid, data, *timestamp).ok()
+            })
+            .collect())
+    }
+
+    /// Capture EAPOL-Key frames
+    ///
+    /// Beacons seen along the way provide each network's AKM, which fixes the MIC length.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the capture source cannot be opened or if packet capture fails.
+    pub fn capture_eapol_keys(&self, duration_seconds: u32) -> PyResult<Vec<EapolKey>> {
+        let mut keys = Vec::new();
+        let mut mic_lengths: HashMap<String, usize> = HashMap::new();
+        let duration = Duration::from_secs(u64::from(duration_seconds));
+
+        self.for_each_frame(CaptureBudget::Duration(duration), |frame, _| {
+            if let Some(network_data) = BeaconParser::parse_beacon_frame(frame) {
+                if let Some(mic_length) = network_data.key_mic_length {
+                    mic_lengths.insert(network_data.bssid, mic_length);
+                }
+            } else if let Some(eapol) = EapolFrame::parse(frame) {
+                let mic_length = mic_lengths.get(&format_mac(eapol.ap)).copied();
+                keys.extend(EapolKey::parse(&eapol, mic_length));
+            }
+        })?;
+
+        Ok(keys)
+    }
+
+    /// Capture 4-way handshakes
+    ///
+    /// Message pairs are matched per AP/station link within `window_seconds`; the
+    /// SSID of each handshake comes from beacons, probe responses or client requests
+    /// seen anywhere in the capture. Handshakes of networks whose SSID never
+    /// appears are dropped, as the SSID salts the PMK and cannot be guessed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the capture source cannot be opened or if packet capture fails.
+    #[pyo3(signature = (duration_seconds, window_seconds=DEFAULT_HANDSHAKE_WINDOW))]
+    pub fn capture_handshakes(&self, duration_seconds: u32, window_seconds: f64) -> PyResult<Vec<HandshakeCapture>> {
+        let mut tracker = HandshakeTracker::new(window_seconds);
+        let mut ssids: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut mic_lengths: HashMap<String, usize> = HashMap::new();
+        let duration = Duration::from_secs(u64::from(duration_seconds));
+
+        self.for_each_frame(CaptureBudget::Duration(duration), |frame, timestamp| {
+            if let Some(network_data) = BeaconParser::parse_beacon_frame(frame) {
+                if let Some(mic_length) = network_data.key_mic_length {
+                    mic_lengths.insert(network_data.bssid.clone(), mic_length);
+                }
+                if !network_data.hidden {
+                    ssids.insert(network_data.bssid, network_data.ssid);
+                }
+            } else if let Some(reveal) = BeaconParser::parse_ssid_reveal(frame) {
+                ssids.entry(reveal.bssid).or_insert(reveal.ssid);
+            } else if let Some(eapol) = EapolFrame::parse(frame) {
+                let mic_length = mic_lengths.get(&format_mac(eapol.ap)).copied();
+                if let Some(key) = EapolKey::parse(&eapol, mic_length) {
+                    tracker.add_key(key, timestamp);
+                }
+            }
+        })?;
+
+        Ok(tracker
+            .get_handshakes()
+            .into_iter()
+            .filter_map(|data| {
+                let ssid = ssids.get(&data.bssid)?;
+                HandshakeCapture::from_data(ssid, data).ok()
+            })
+            .collect())
     }
 
     /// Get available network interfaces
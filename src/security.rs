@@ -0,0 +1,540 @@
+use pyo3::prelude::*;
+use std::fmt;
+use crate::utils::to_hex;
+
+/// IEEE 802.11 OUI used for RSN cipher and AKM suite selectors
+pub const IEEE80211_OUI: [u8; 3] = [0x00, 0x0F, 0xAC];
+/// Microsoft OUI used by the legacy WPA vendor element and its suite selectors
+pub const WPA_OUI: [u8; 3] = [0x00, 0x50, 0xF2];
+/// Vendor-specific type of the WPA information element
+pub const WPA_VENDOR_TYPE: u8 = 1;
+
+/// Cipher suite from an RSN element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CipherSuite {
+    UseGroup,
+    Wep40,
+    Tkip,
+    Ccmp128,
+    Wep104,
+    BipCmac128,
+    GroupAddressedNotAllowed,
+    Gcmp128,
+    Gcmp256,
+    Ccmp256,
+    BipGmac128,
+    BipGmac256,
+    BipCmac256,
+    Other([u8; 3], u8),
+}
+
+impl CipherSuite {
+    #[must_use]
+    pub fn from_selector(oui: [u8; 3], suite_type: u8) -> Self {
+        // WPA selectors reuse the RSN numbering for the ciphers they define
+        let known = oui == IEEE80211_OUI || (oui == WPA_OUI && suite_type <= 5);
+        if !known {
+            return Self::Other(oui, suite_type);
+        }
+
+        match suite_type {
+            0 => Self::UseGroup,
+            1 => Self::Wep40,
+            2 => Self::Tkip,
+            4 => Self::Ccmp128,
+            5 => Self::Wep104,
+            6 => Self::BipCmac128,
+            7 => Self::GroupAddressedNotAllowed,
+            8 => Self::Gcmp128,
+            9 => Self::Gcmp256,
+            10 => Self::Ccmp256,
+            11 => Self::BipGmac128,
+            12 => Self::BipGmac256,
+            13 => Self::BipCmac256,
+            other => Self::Other(oui, other),
+        }
+    }
+}
+
+impl fmt::Display for CipherSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UseGroup => write!(f, "Group"),
+            Self::Wep40 => write!(f, "WEP-40"),
+            Self::Tkip => write!(f, "TKIP"),
+            Self::Ccmp128 => write!(f, "CCMP-128"),
+            Self::Wep104 => write!(f, "WEP-104"),
+            Self::BipCmac128 => write!(f, "BIP-CMAC-128"),
+            Self::GroupAddressedNotAllowed => write!(f, "Group addressed traffic not allowed"),
+            Self::Gcmp128 => write!(f, "GCMP-128"),
+            Self::Gcmp256 => write!(f, "GCMP-256"),
+            Self::Ccmp256 => write!(f, "CCMP-256"),
+            Self::BipGmac128 => write!(f, "BIP-GMAC-128"),
+            Self::BipGmac256 => write!(f, "BIP-GMAC-256"),
+            Self::BipCmac256 => write!(f, "BIP-CMAC-256"),
+            Self::Other(oui, suite_type) => write!(f, "{}:{suite_type}", to_hex(oui)),
+        }
+    }
+}
+
+/// Authentication and key management suite from an RSN element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AkmSuite {
+    Ieee8021x,
+    Psk,
+    FtIeee8021x,
+    FtPsk,
+    Ieee8021xSha256,
+    PskSha256,
+    Tdls,
+    Sae,
+    FtSae,
+    ApPeerKey,
+    SuiteB,
+    SuiteB192,
+    FtIeee8021xSha384,
+    FilsSha256,
+    FilsSha384,
+    FtFilsSha256,
+    FtFilsSha384,
+    Owe,
+    FtPskSha384,
+    PskSha384,
+    SaeExtKey,
+    FtSaeExtKey,
+    Other([u8; 3], u8),
+}
+
+impl AkmSuite {
+    #[must_use]
+    pub fn from_selector(oui: [u8; 3], suite_type: u8) -> Self {
+        // WPA only defines 802.1X (1) and PSK (2), numbered as in RSN
+        let known = oui == IEEE80211_OUI || (oui == WPA_OUI && matches!(suite_type, 1 | 2));
+        if !known {
+            return Self::Other(oui, suite_type);
+        }
+
+        match suite_type {
+            1 => Self::Ieee8021x,
+            2 => Self::Psk,
+            3 => Self::FtIeee8021x,
+            4 => Self::FtPsk,
+            5 => Self::Ieee8021xSha256,
+            6 => Self::PskSha256,
+            7 => Self::Tdls,
+            8 => Self::Sae,
+            9 => Self::FtSae,
+            10 => Self::ApPeerKey,
+            11 => Self::SuiteB,
+            12 => Self::SuiteB192,
+            13 => Self::FtIeee8021xSha384,
+            14 => Self::FilsSha256,
+            15 => Self::FilsSha384,
+            16 => Self::FtFilsSha256,
+            17 => Self::FtFilsSha384,
+            18 => Self::Owe,
+            19 => Self::FtPskSha384,
+            20 => Self::PskSha384,
+            24 => Self::SaeExtKey,
+            25 => Self::FtSaeExtKey,
+            other => Self::Other(oui, other),
+        }
+    }
+
+    /// Length of the EAPOL-Key MIC field under this AKM
+    #[must_use]
+    pub fn mic_length(self) -> usize {
+        match self {
+            Self::SuiteB192 | Self::FtIeee8021xSha384 | Self::PskSha384 | Self::FtPskSha384 | Self::SaeExtKey | Self::FtSaeExtKey => 24,
+            // FILS protects EAPOL-Key frames with AEAD instead of a MIC
+            _ if self.is_fils() => 0,
+            _ => 16,
+        }
+    }
+
+    fn is_psk(self) -> bool {
+        matches!(self, Self::Psk | Self::PskSha256 | Self::PskSha384 | Self::FtPsk | Self::FtPskSha384)
+    }
+
+    fn is_sae(self) -> bool {
+        matches!(self, Self::Sae | Self::FtSae | Self::SaeExtKey | Self::FtSaeExtKey)
+    }
+
+    fn is_fils(self) -> bool {
+        matches!(self, Self::FilsSha256 | Self::FilsSha384 | Self::FtFilsSha256 | Self::FtFilsSha384)
+    }
+
+    fn is_ieee8021x(self) -> bool {
+        matches!(self, Self::Ieee8021x | Self::FtIeee8021x | Self::Ieee8021xSha256)
+    }
+}
+
+impl fmt::Display for AkmSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ieee8021x => write!(f, "802.1X"),
+            Self::Psk => write!(f, "PSK"),
+            Self::FtIeee8021x => write!(f, "FT-802.1X"),
+            Self::FtPsk => write!(f, "FT-PSK"),
+            Self::Ieee8021xSha256 => write!(f, "802.1X-SHA256"),
+            Self::PskSha256 => write!(f, "PSK-SHA256"),
+            Self::Tdls => write!(f, "TDLS"),
+            Self::Sae => write!(f, "SAE"),
+            Self::FtSae => write!(f, "FT-SAE"),
+            Self::ApPeerKey => write!(f, "AP-PeerKey"),
+            Self::SuiteB => write!(f, "802.1X-Suite-B"),
+            Self::SuiteB192 => write!(f, "802.1X-Suite-B-192"),
+            Self::FtIeee8021xSha384 => write!(f, "FT-802.1X-SHA384"),
+            Self::FilsSha256 => write!(f, "FILS-SHA256"),
+            Self::FilsSha384 => write!(f, "FILS-SHA384"),
+            Self::FtFilsSha256 => write!(f, "FT-FILS-SHA256"),
+            Self::FtFilsSha384 => write!(f, "FT-FILS-SHA384"),
+            Self::Owe => write!(f, "OWE"),
+            Self::FtPskSha384 => write!(f, "FT-PSK-SHA384"),
+            Self::PskSha384 => write!(f, "PSK-SHA384"),
+            Self::SaeExtKey => write!(f, "SAE-EXT-KEY"),
+            Self::FtSaeExtKey => write!(f, "FT-SAE-EXT-KEY"),
+            Self::Other(oui, suite_type) => write!(f, "{}:{suite_type}", to_hex(oui)),
+        }
+    }
+}
+
+/// Decoded RSN information element (element ID 48)
+///
+/// Trailing fields are optional in the element; absent suites take the defaults
+/// from IEEE 802.11 (CCMP-128 ciphers and 802.1X AKM). The legacy WPA vendor
+/// element shares the layout up to the AKM list and is decoded into the same type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsnElement {
+    pub version: u16,
+    pub group_cipher: CipherSuite,
+    pub pairwise_ciphers: Vec<CipherSuite>,
+    pub akm_suites: Vec<AkmSuite>,
+    pub capabilities: u16,
+    pub pmkids: Vec<[u8; 16]>,
+    pub group_management_cipher: Option<CipherSuite>,
+}
+
+impl RsnElement {
+    /// Parse the body of an RSN element
+    ///
+    /// Returns `None` for an unknown version or a field list cut off mid-suite.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        Self::parse_body(data, CipherSuite::Ccmp128)
+    }
+
+    /// Parse the body of a vendor-specific element (ID 221) if it is a WPA element
+    ///
+    /// WPA defaults to TKIP where the element omits its cipher suites.
+    #[must_use]
+    pub fn parse_wpa_vendor(data: &[u8]) -> Option<Self> {
+        if data.len() < 4 || data[..3] != WPA_OUI || data[3] != WPA_VENDOR_TYPE {
+            return None;
+        }
+        Self::parse_body(&data[4..], CipherSuite::Tkip)
+    }
+
+    fn parse_body(data: &[u8], default_cipher: CipherSuite) -> Option<Self> {
+        let mut reader = ElementReader { data, offset: 0 };
+
+        let version = reader.u16()?;
+        if version != 1 {
+            return None;
+        }
+
+        let mut rsn = Self {
+            version,
+            group_cipher: default_cipher,
+            pairwise_ciphers: vec![default_cipher],
+            akm_suites: vec![AkmSuite::Ieee8021x],
+            capabilities: 0,
+            pmkids: Vec::new(),
+            group_management_cipher: None,
+        };
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.group_cipher = reader.suite().map(|(oui, t)| CipherSuite::from_selector(oui, t))?;
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.pairwise_ciphers = reader.suite_list(CipherSuite::from_selector)?;
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.akm_suites = reader.suite_list(AkmSuite::from_selector)?;
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.capabilities = reader.u16()?;
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        let pmkid_count = reader.u16()?;
+        for _ in 0..pmkid_count {
+            rsn.pmkids.push(reader.bytes(16)?.try_into().ok()?);
+        }
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.group_management_cipher = reader.suite().map(|(oui, t)| CipherSuite::from_selector(oui, t));
+
+        Some(rsn)
+    }
+
+    /// EAPOL-Key MIC length, if all advertised AKMs agree on it
+    #[must_use]
+    pub fn mic_length(&self) -> Option<usize> {
+        let mut lengths = self.akm_suites.iter().map(|akm| akm.mic_length());
+        let first = lengths.next()?;
+        lengths.all(|length| length == first).then_some(first)
+    }
+
+    /// Management frame protection capable (MFPC)
+    #[must_use]
+    pub fn mfp_capable(&self) -> bool {
+        self.capabilities & 0x0080 != 0
+    }
+
+    /// Management frame protection required (MFPR)
+    #[must_use]
+    pub fn mfp_required(&self) -> bool {
+        self.capabilities & 0x0040 != 0
+    }
+
+    /// Number of PTKSA replay counters
+    #[must_use]
+    pub fn ptksa_replay_counters(&self) -> u8 {
+        replay_counters((self.capabilities >> 2) & 0x03)
+    }
+
+    /// Number of GTKSA replay counters
+    #[must_use]
+    pub fn gtksa_replay_counters(&self) -> u8 {
+        replay_counters((self.capabilities >> 4) & 0x03)
+    }
+}
+
+fn replay_counters(field: u16) -> u8 {
+    match field {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        _ => 16,
+    }
+}
+
+/// Cursor over the fields of an information element body
+struct ElementReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ElementReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn suite(&mut self) -> Option<([u8; 3], u8)> {
+        self.bytes(4).map(|b| ([b[0], b[1], b[2]], b[3]))
+    }
+
+    fn suite_list<T>(&mut self, decode: fn([u8; 3], u8) -> T) -> Option<Vec<T>> {
+        let count = self.u16()?;
+        (0..count)
+            .map(|_| self.suite().map(|(oui, t)| decode(oui, t)))
+            .collect()
+    }
+}
+
+/// Security configuration advertised by a network
+#[pyclass]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityProfile {
+    /// Detailed label, e.g. "WEP", "WPA/WPA2-PSK", "WPA3-Transition" or "OWE"
+    #[pyo3(get)]
+    pub label: String,
+    /// Protocol family: the weakest protocol a client may connect with
+    #[pyo3(get)]
+    pub encryption: String,
+    /// Security mechanisms advertised: any of "WEP", "WPA" and "RSN"
+    #[pyo3(get)]
+    pub protocols: Vec<String>,
+    #[pyo3(get)]
+    pub group_cipher: String,
+    #[pyo3(get)]
+    pub pairwise_ciphers: Vec<String>,
+    #[pyo3(get)]
+    pub akm_suites: Vec<String>,
+    #[pyo3(get)]
+    pub mfp_capable: bool,
+    #[pyo3(get)]
+    pub mfp_required: bool,
+    #[pyo3(get)]
+    pub ptksa_replay_counters: u8,
+    #[pyo3(get)]
+    pub gtksa_replay_counters: u8,
+    #[pyo3(get)]
+    pub pmkids: Vec<String>,
+    #[pyo3(get)]
+    pub group_management_cipher: Option<String>,
+}
+
+impl SecurityProfile {
+    /// Build a profile from a beacon's RSN and WPA elements and its capability privacy bit
+    ///
+    /// Returns `None` for open networks.
+    #[must_use]
+    pub fn from_elements(rsn: Option<&RsnElement>, wpa: Option<&RsnElement>, privacy: bool) -> Option<Self> {
+        match (rsn, wpa) {
+            (Some(rsn), None) => {
+                let (label, encryption) = Self::classify(&rsn.akm_suites);
+                Some(Self::from_suites(label.to_string(), encryption, &["RSN"], rsn, None))
+            }
+            (Some(rsn), Some(wpa)) => {
+                // Mixed mode: WPA1 clients are still accepted
+                let (label, _) = Self::classify(&rsn.akm_suites);
+                let label = format!("WPA/{label}");
+                Some(Self::from_suites(label, "WPA", &["WPA", "RSN"], rsn, Some(wpa)))
+            }
+            (None, Some(wpa)) => {
+                let label = if wpa.akm_suites.iter().any(|akm| akm.is_psk()) {
+                    "WPA-PSK"
+                } else if wpa.akm_suites.iter().any(|akm| akm.is_ieee8021x()) {
+                    "WPA-Enterprise"
+                } else {
+                    "WPA"
+                };
+                Some(Self::from_suites(label.to_string(), "WPA", &["WPA"], wpa, None))
+            }
+            (None, None) if privacy => Some(Self::wep()),
+            (None, None) => None,
+        }
+    }
+
+    /// Build a profile from a primary element, merging suites from a secondary one
+    fn from_suites(
+        label: String,
+        encryption: &str,
+        protocols: &[&str],
+        primary: &RsnElement,
+        secondary: Option<&RsnElement>,
+    ) -> Self {
+        let mut pairwise_ciphers = primary.pairwise_ciphers.clone();
+        let mut akm_suites = primary.akm_suites.clone();
+        if let Some(secondary) = secondary {
+            for cipher in &secondary.pairwise_ciphers {
+                if !pairwise_ciphers.contains(cipher) {
+                    pairwise_ciphers.push(*cipher);
+                }
+            }
+            for akm in &secondary.akm_suites {
+                if !akm_suites.contains(akm) {
+                    akm_suites.push(*akm);
+                }
+            }
+        }
+
+        Self {
+            label,
+            encryption: encryption.to_string(),
+            protocols: protocols.iter().map(ToString::to_string).collect(),
+            group_cipher: primary.group_cipher.to_string(),
+            pairwise_ciphers: pairwise_ciphers.iter().map(ToString::to_string).collect(),
+            akm_suites: akm_suites.iter().map(ToString::to_string).collect(),
+            mfp_capable: primary.mfp_capable(),
+            mfp_required: primary.mfp_required(),
+            ptksa_replay_counters: primary.ptksa_replay_counters(),
+            gtksa_replay_counters: primary.gtksa_replay_counters(),
+            pmkids: primary.pmkids.iter().map(|pmkid| to_hex(pmkid)).collect(),
+            group_management_cipher: primary.group_management_cipher.map(|cipher| cipher.to_string()),
+        }
+    }
+
+    /// Profile for a network with the privacy bit set but no RSN or WPA element
+    fn wep() -> Self {
+        Self {
+            label: "WEP".to_string(),
+            encryption: "WEP".to_string(),
+            protocols: vec!["WEP".to_string()],
+            group_cipher: "WEP".to_string(),
+            pairwise_ciphers: Vec::new(),
+            akm_suites: Vec::new(),
+            mfp_capable: false,
+            mfp_required: false,
+            ptksa_replay_counters: 0,
+            gtksa_replay_counters: 0,
+            pmkids: Vec::new(),
+            group_management_cipher: None,
+        }
+    }
+
+    /// Derive the detailed label and protocol family from the advertised AKM suites
+    fn classify(akms: &[AkmSuite]) -> (&'static str, &'static str) {
+        let has = |akm: AkmSuite| akms.contains(&akm);
+        let any = |predicate: fn(AkmSuite) -> bool| akms.iter().any(|akm| predicate(*akm));
+
+        if has(AkmSuite::Owe) {
+            ("OWE", "OWE")
+        } else if has(AkmSuite::SuiteB192) || has(AkmSuite::FtIeee8021xSha384) {
+            ("WPA3-Enterprise-192", "WPA3")
+        } else if any(AkmSuite::is_fils) {
+            ("FILS", "WPA2")
+        } else if any(AkmSuite::is_sae) && any(AkmSuite::is_psk) {
+            ("WPA3-Transition", "WPA2")
+        } else if has(AkmSuite::FtSae) || has(AkmSuite::FtSaeExtKey) {
+            ("FT-SAE", "WPA3")
+        } else if any(AkmSuite::is_sae) {
+            ("WPA3-SAE", "WPA3")
+        } else if has(AkmSuite::FtPsk) || has(AkmSuite::FtPskSha384) {
+            ("FT-PSK", "WPA2")
+        } else if any(AkmSuite::is_psk) {
+            ("WPA2-PSK", "WPA2")
+        } else if has(AkmSuite::SuiteB) {
+            ("WPA3-Enterprise", "WPA3")
+        } else if any(AkmSuite::is_ieee8021x) {
+            ("WPA2-Enterprise", "WPA2")
+        } else {
+            ("WPA2", "WPA2")
+        }
+    }
+}
+
+#[pymethods]
+impl SecurityProfile {
+    /// Get a summary of the security profile
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!(
+            "{} (group: {}, pairwise: {}, AKM: {}, MFP: {})",
+            self.label,
+            self.group_cipher,
+            self.pairwise_ciphers.join("/"),
+            self.akm_suites.join("/"),
+            if self.mfp_required {
+                "required"
+            } else if self.mfp_capable {
+                "capable"
+            } else {
+                "off"
+            }
+        )
+    }
+}
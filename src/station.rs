@@ -0,0 +1,204 @@
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use crate::dot11::{
+    decode_ssid, format_mac, is_group_address, DataHeader, InformationElements, ManagementHeader,
+    SUBTYPE_ASSOCIATION_REQUEST, SUBTYPE_ASSOCIATION_RESPONSE, SUBTYPE_BEACON, SUBTYPE_PROBE_REQUEST,
+    SUBTYPE_PROBE_RESPONSE, SUBTYPE_REASSOCIATION_REQUEST, SUBTYPE_REASSOCIATION_RESPONSE,
+};
+use crate::frame::Frame;
+
+/// Client station seen during a capture
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Station {
+    #[pyo3(get)]
+    pub mac: String,
+    /// BSSID the station is associated to, if known
+    #[pyo3(get)]
+    pub bssid: Option<String>,
+    /// Capture timestamp of the first frame involving the station (seconds since the epoch)
+    #[pyo3(get)]
+    pub first_seen: f64,
+    /// Capture timestamp of the latest frame involving the station
+    #[pyo3(get)]
+    pub last_seen: f64,
+    /// Frames sent or received by the station
+    #[pyo3(get)]
+    pub frame_count: u64,
+    /// Data frames sent or received by the station
+    #[pyo3(get)]
+    pub data_frame_count: u64,
+    /// Antenna signal in dBm of the latest frame transmitted by the station
+    #[pyo3(get)]
+    pub rssi: Option<i8>,
+    /// SSIDs the station has probed for, in the order first seen
+    #[pyo3(get)]
+    pub probed_ssids: Vec<String>,
+}
+
+#[pymethods]
+impl Station {
+    /// Get a summary of the station
+    #[must_use]
+    pub fn get_summary(&self) -> String {
+        format!(
+            "Station: {} -> {} ({} frames)",
+            self.mac,
+            self.bssid.as_deref().unwrap_or("not associated"),
+            self.frame_count
+        )
+    }
+}
+
+/// What a single frame tells us about a station
+#[derive(Debug, Clone)]
+pub struct StationObservation {
+    pub mac: String,
+    /// BSSID the frame shows the station to be associated to
+    pub bssid: Option<String>,
+    pub is_data: bool,
+    /// Signal of the frame, only set when the station transmitted it
+    pub rssi: Option<i8>,
+    pub probed_ssid: Option<String>,
+}
+
+/// Station parser for attributing frames to client stations
+pub struct StationParser;
+
+impl StationParser {
+    /// Parse a decoded frame for the client station it involves
+    pub fn parse_station_frame(frame: &Frame) -> Option<StationObservation> {
+        let Frame::Ieee80211 { data, radio } = frame else {
+            return None;
+        };
+        let signal = radio.as_ref().and_then(|radio| radio.antenna_signal);
+
+        if let Some(header) = DataHeader::parse(data) {
+            return Self::parse_data_frame(&header, signal);
+        }
+        let header = ManagementHeader::parse(data)?;
+        Self::parse_management_frame(&header, &data[header.body_offset..], signal)
+    }
+
+    fn parse_data_frame(header: &DataHeader, signal: Option<i8>) -> Option<StationObservation> {
+        let bssid = header.bssid()?;
+
+        // Uplink and ad-hoc frames are transmitted by the station; downlink frames are addressed to it
+        let (station, rssi) = if header.frame_control.is_from_ds() {
+            (header.addr1, None)
+        } else {
+            (header.addr2, signal)
+        };
+        if is_group_address(station) || station == bssid {
+            return None;
+        }
+
+        Some(StationObservation {
+            mac: format_mac(station),
+            bssid: Some(format_mac(bssid)),
+            is_data: true,
+            rssi,
+            probed_ssid: None,
+        })
+    }
+
+    fn parse_management_frame(header: &ManagementHeader, body: &[u8], signal: Option<i8>) -> Option<StationObservation> {
+        let (station, bssid, rssi, probed_ssid) = match header.subtype() {
+            // Access point announcements do not involve a particular station
+            SUBTYPE_BEACON | SUBTYPE_PROBE_RESPONSE => return None,
+            SUBTYPE_PROBE_REQUEST => {
+                let ssid = InformationElements::new(body)
+                    .find(|(element_id, _)| *element_id == 0)
+                    .and_then(|(_, payload)| decode_ssid(payload))
+                    .map(|ssid| String::from_utf8_lossy(&ssid).into_owned());
+                (header.addr2, None, signal, ssid)
+            }
+            SUBTYPE_ASSOCIATION_REQUEST | SUBTYPE_REASSOCIATION_REQUEST => {
+                (header.addr2, Some(header.addr3), signal, None)
+            }
+            SUBTYPE_ASSOCIATION_RESPONSE | SUBTYPE_REASSOCIATION_RESPONSE => {
+                // Fixed parameters: capability (2), status code (2); only status 0 associates
+                let accepted = body.get(2..4) == Some(&[0, 0]);
+                (header.addr1, accepted.then_some(header.addr2), None, None)
+            }
+            // Authentication, deauthentication, action frames etc. between a station and its AP
+            _ if header.addr2 == header.addr3 => (header.addr1, None, None, None),
+            _ if header.addr1 == header.addr3 => (header.addr2, None, signal, None),
+            _ => return None,
+        };
+        if is_group_address(station) || Some(station) == bssid {
+            return None;
+        }
+
+        Some(StationObservation {
+            mac: format_mac(station),
+            bssid: bssid.map(format_mac),
+            is_data: false,
+            rssi,
+            probed_ssid,
+        })
+    }
+}
+
+/// Tracker that accumulates station observations over a capture
+pub struct StationTracker {
+    stations: HashMap<String, Station>,
+}
+
+impl StationTracker {
+    pub fn new() -> Self {
+        Self {
+            stations: HashMap::new(),
+        }
+    }
+
+    pub fn add_observation(&mut self, observation: StationObservation, timestamp: f64) {
+        let station = self.stations.entry(observation.mac.clone()).or_insert_with(|| Station {
+            mac: observation.mac,
+            bssid: None,
+            first_seen: timestamp,
+            last_seen: timestamp,
+            frame_count: 0,
+            data_frame_count: 0,
+            rssi: None,
+            probed_ssids: Vec::new(),
+        });
+
+        station.first_seen = station.first_seen.min(timestamp);
+        station.last_seen = station.last_seen.max(timestamp);
+        station.frame_count += 1;
+        if observation.is_data {
+            station.data_frame_count += 1;
+        }
+        if observation.bssid.is_some() {
+            station.bssid = observation.bssid;
+        }
+        if observation.rssi.is_some() {
+            station.rssi = observation.rssi;
+        }
+        if let Some(ssid) = observation.probed_ssid {
+            if !station.probed_ssids.contains(&ssid) {
+                station.probed_ssids.push(ssid);
+            }
+        }
+    }
+
+    /// Stations associated to `bssid`, ordered by MAC address
+    pub fn clients_of(&self, bssid: &str) -> Vec<Station> {
+        let mut clients: Vec<Station> = self
+            .stations
+            .values()
+            .filter(|station| station.bssid.as_deref() == Some(bssid))
+            .cloned()
+            .collect();
+        clients.sort_by(|a, b| a.mac.cmp(&b.mac));
+        clients
+    }
+
+    /// All stations, ordered by MAC address
+    pub fn get_stations(self) -> Vec<Station> {
+        let mut stations: Vec<Station> = self.stations.into_values().collect();
+        stations.sort_by(|a, b| a.mac.cmp(&b.mac));
+        stations
+    }
+}
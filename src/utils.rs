@@ -4,3 +4,26 @@ use pyo3::prelude::*;
 pub fn create_runtime_error(message: &str) -> PyErr {
     PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(message.to_string())
 }
+
+/// Encode bytes as a lowercase hex string
+#[must_use]
+pub fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// Decode a hex string of either case, returning `None` on odd length or non-hex characters
+#[must_use]
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|offset| u8::from_str_radix(&hex[offset..offset + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,297 @@
+"""
+Helpers for building 802.11 frames and pcap files in tests
+"""
+
+import struct
+
+LINKTYPE_IEEE802_11 = 105
+LINKTYPE_ETHERNET = 1
+LINKTYPE_IEEE802_11_PRISM = 119
+LINKTYPE_IEEE802_11_RADIOTAP = 127
+LINKTYPE_IEEE802_11_AVS = 163
+LINKTYPE_PPI = 192
+
+# Radiotap field number -> (alignment, struct format)
+RADIOTAP_FIELDS = {
+    0: (8, "<Q"),  # TSFT
+    1: (1, "<B"),  # Flags
+    2: (1, "<B"),  # Rate
+    3: (2, "<HH"),  # Channel
+    4: (2, "<BB"),  # FHSS
+    5: (1, "<b"),  # dBm antenna signal
+    6: (1, "<b"),  # dBm antenna noise
+    11: (1, "<B"),  # Antenna
+    19: (1, "<BBB"),  # MCS
+}
+
+
+def mac_bytes(mac):
+    """Convert a colon separated MAC address to bytes"""
+    return bytes(int(part, 16) for part in mac.split(":"))
+
+
+def information_element(element_id, payload):
+    """Build a tagged information element"""
+    return bytes([element_id, len(payload)]) + payload
+
+
+def suite(suite_type, oui=b"\x00\x0f\xac"):
+    """Build a 4-byte cipher or AKM suite selector"""
+    return oui + bytes([suite_type])
+
+
+def rsn_element(
+    group=4, pairwise=(4,), akms=(2,), capabilities=0, pmkids=(), group_mgmt=None
+):
+    """Build an RSN information element"""
+    body = struct.pack("<H", 1) + suite(group)
+    body += struct.pack("<H", len(pairwise)) + b"".join(suite(p) for p in pairwise)
+    body += struct.pack("<H", len(akms)) + b"".join(suite(a) for a in akms)
+    body += struct.pack("<H", capabilities)
+    if pmkids or group_mgmt is not None:
+        body += struct.pack("<H", len(pmkids)) + b"".join(pmkids)
+    if group_mgmt is not None:
+        body += suite(group_mgmt)
+    return information_element(48, body)
+
+
+def wpa_element(group=2, pairwise=(2,), akms=(2,)):
+    """Build a legacy WPA vendor information element"""
+    oui = b"\x00\x50\xf2"
+    body = oui + b"\x01" + struct.pack("<H", 1) + suite(group, oui)
+    body += struct.pack("<H", len(pairwise)) + b"".join(suite(p, oui) for p in pairwise)
+    body += struct.pack("<H", len(akms)) + b"".join(suite(a, oui) for a in akms)
+    return information_element(221, body)
+
+
+def management_header(subtype, addr1, addr2, addr3):
+    """Build a 24-byte management frame header"""
+    return (
+        struct.pack("<HH", subtype << 4, 0)
+        + mac_bytes(addr1)
+        + mac_bytes(addr2)
+        + mac_bytes(addr3)
+        + struct.pack("<H", 0)
+    )
+
+
+def data_frame(bssid, station, to_ds=True, subtype=0, payload=b"\xaa\xaa\x03\x00\x00\x00\x08\x00"):
+    """Build a data frame between a station and its AP
+
+    `to_ds` selects uplink (station -> AP) or downlink (AP -> station). Subtype 4 is
+    null-data, 8 is QoS data and 12 is QoS null.
+    """
+    flags = 0x01 if to_ds else 0x02
+    addr1, addr2 = (bssid, station) if to_ds else (station, bssid)
+    header = (
+        struct.pack("<BBH", 0x08 | (subtype << 4), flags, 0)
+        + mac_bytes(addr1)
+        + mac_bytes(addr2)
+        + mac_bytes(bssid)
+        + struct.pack("<H", 0)
+    )
+    if subtype & 0x08:
+        header += struct.pack("<H", 0)
+    return header + (b"" if subtype & 0x04 else payload)
+
+
+# Key Information values of the 4-way and group key handshakes (HMAC-SHA1/AES, version 2)
+KEY_INFO_M1 = 0x008A
+KEY_INFO_M2 = 0x010A
+KEY_INFO_M3 = 0x13CA
+KEY_INFO_M4 = 0x030A
+KEY_INFO_GROUP1 = 0x1382
+KEY_INFO_GROUP2 = 0x0302
+
+
+def kde(data_type, data):
+    """Build a key data encapsulation with the IEEE 802.11 OUI"""
+    return information_element(0xDD, b"\x00\x0f\xac" + bytes([data_type]) + data)
+
+
+def pmkid_kde(pmkid):
+    """Build a PMKID KDE"""
+    return kde(4, pmkid)
+
+
+def eapol_key(
+    key_info,
+    replay_counter=1,
+    nonce=b"\x00" * 32,
+    key_data=b"",
+    mic=None,
+    mic_length=16,
+    descriptor=2,
+    key_length=16,
+):
+    """Build an EAPOL-Key PDU (EAPOL header included)"""
+    if mic is None:
+        mic = b"\x00" * mic_length
+    body = (
+        struct.pack(">BHHQ", descriptor, key_info, key_length, replay_counter)
+        + nonce
+        + b"\x00" * 16  # IV
+        + b"\x00" * 8  # RSC
+        + b"\x00" * 8  # reserved
+        + mic
+        + struct.pack(">H", len(key_data))
+        + key_data
+    )
+    return struct.pack(">BBH", 2, 3, len(body)) + body
+
+
+def eapol_data_frame(bssid, station, eapol, from_ap=True, qos=False, htc=False):
+    """Build a data frame carrying an LLC/SNAP encapsulated EAPOL PDU
+
+    `qos` selects the QoS data subtype; `htc` adds an HT Control field after QoS Control.
+    """
+    flags = 0x02 if from_ap else 0x01
+    if htc:
+        flags |= 0x80
+    addr1, addr2 = (station, bssid) if from_ap else (bssid, station)
+    header = (
+        struct.pack("<BBH", 0x88 if qos else 0x08, flags, 0)
+        + mac_bytes(addr1)
+        + mac_bytes(addr2)
+        + mac_bytes(bssid)
+        + struct.pack("<H", 0)
+    )
+    if qos:
+        header += struct.pack("<H", 0x0007)
+        if htc:
+            header += b"\x00" * 4
+    return header + b"\xaa\xaa\x03\x00\x00\x00\x88\x8e" + eapol
+
+
+def eapol_ethernet_frame(source, destination, eapol):
+    """Build an Ethernet II frame carrying an EAPOL PDU"""
+    return mac_bytes(destination) + mac_bytes(source) + b"\x88\x8e" + eapol
+
+
+def association_response_frame(bssid, client, status=0, reassociation=False):
+    """Build an (re)association response frame"""
+    header = management_header(3 if reassociation else 1, client, bssid, bssid)
+    return header + struct.pack("<HHH", 0x0011, status, 0xC001)
+
+
+def beacon_frame(bssid, ssid, channel=6, elements=b"", privacy=False, subtype=8,
+                 destination="ff:ff:ff:ff:ff:ff"):
+    """Build a raw 802.11 beacon frame (or probe response with subtype 5)
+
+    `ssid` may be a string or raw bytes, e.g. zero bytes for a hidden network.
+    """
+    header = management_header(subtype, destination, bssid, bssid)
+    capabilities = 0x0011 if privacy else 0x0001
+    fixed = struct.pack("<QHH", 0, 100, capabilities)
+    ssid = ssid.encode() if isinstance(ssid, str) else ssid
+    body = (
+        information_element(0, ssid)
+        + information_element(3, bytes([channel]))
+        + elements
+    )
+    return header + fixed + body
+
+
+def probe_response_frame(bssid, ssid, client, **kwargs):
+    """Build a raw 802.11 probe response frame"""
+    return beacon_frame(bssid, ssid, subtype=5, destination=client, **kwargs)
+
+
+def association_request_frame(bssid, ssid, client, current_ap=None):
+    """Build an association request, or a reassociation request if `current_ap` is given"""
+    if current_ap is None:
+        header = management_header(0, bssid, client, bssid)
+        fixed = struct.pack("<HH", 0x0011, 10)
+    else:
+        header = management_header(2, bssid, client, bssid)
+        fixed = struct.pack("<HH", 0x0011, 10) + mac_bytes(current_ap)
+    return header + fixed + information_element(0, ssid.encode())
+
+
+def probe_request_frame(client, ssid, bssid="ff:ff:ff:ff:ff:ff"):
+    """Build a probe request, directed at `bssid` or broadcast"""
+    header = management_header(4, bssid, client, bssid)
+    return header + information_element(0, ssid.encode())
+
+
+def radiotap_header(fields, *namespaces):
+    """Build a radiotap header
+
+    `fields` maps radiotap field numbers to tuples of values. Each additional
+    dict in `namespaces` is encoded as a further radiotap namespace, as used
+    for per-antenna values.
+    """
+    groups = [fields, *namespaces]
+    words = []
+    for index, group in enumerate(groups):
+        word = sum(1 << field for field in group)
+        if index + 1 < len(groups):
+            word |= (1 << 29) | (1 << 31)
+        words.append(word)
+
+    length = 4 + 4 * len(words)
+    body = b""
+    for group in groups:
+        for field in sorted(group):
+            alignment, fmt = RADIOTAP_FIELDS[field]
+            padding = -(length + len(body)) % alignment
+            body += b"\x00" * padding + struct.pack(fmt, *group[field])
+
+    header = struct.pack("<BBH", 0, 0, length + len(body))
+    return header + struct.pack(f"<{len(words)}I", *words) + body
+
+
+def ppi_header(frequency, signal, noise=-95, flags=0):
+    """Build a PPI header with an 802.11-Common field"""
+    common = struct.pack(
+        "<QHHHHBBbb", 0, flags, 2, frequency, 0x00A0, 0, 0, signal, noise
+    )
+    field = struct.pack("<HH", 2, len(common)) + common
+    return struct.pack("<BBHI", 0, 0, 8 + len(field), LINKTYPE_IEEE802_11) + field
+
+
+def prism_header(channel, signal, noise=-95, rate=2):
+    """Build a 144-byte Prism monitor header"""
+    values = [0, 0, channel, 0, 0, signal, noise, rate, 0, 0]
+    items = b"".join(
+        struct.pack("<IHHi", 0x0041 + index * 0x1000, 0, 4, value)
+        for index, value in enumerate(values)
+    )
+    return struct.pack("<II16s", 0x0041, 144, b"wlan0") + items
+
+
+def avs_header(channel, signal, noise=-95):
+    """Build a 64-byte AVS version 1 capture header (dBm signal)"""
+    return struct.pack(
+        ">IIQQIIIIIIiiII",
+        0x80211001,
+        64,
+        0,
+        0,
+        0,
+        channel,
+        10,
+        0,
+        0,
+        2,
+        signal,
+        noise,
+        0,
+        0,
+    )
+
+
+def write_pcap(path, frames, linktype=LINKTYPE_IEEE802_11):
+    """Write frames to a classic pcap file
+
+    Each frame is either raw bytes or a `(timestamp, bytes)` tuple.
+    """
+    with open(path, "wb") as f:
+        f.write(struct.pack("<IHHiIII", 0xA1B2C3D4, 2, 4, 0, 0, 65535, linktype))
+        for index, frame in enumerate(frames):
+            timestamp, data = frame if isinstance(frame, tuple) else (index, frame)
+            seconds = int(timestamp)
+            micros = int(round((timestamp - seconds) * 1_000_000))
+            f.write(struct.pack("<IIII", seconds, micros, len(data), len(data)))
+            f.write(data)
+    return path
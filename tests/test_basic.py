@@ -36,3 +36,14 @@ def test_cli_interface():
     runner = CliRunner()
     result = runner.invoke(main, ["-i", "wlan0"])
     assert result.exit_code == 0
+
+
+def test_cli_interface_warning():
+    """Test that the missing interface warning only accompanies captures"""
+    runner = CliRunner()
+    result = runner.invoke(main, ["--scan"])
+    assert "No interface specified" in result.output
+
+    result = runner.invoke(main, ["--crack", __file__])
+    assert result.exit_code == 0
+    assert "No interface specified" not in result.output
@@ -0,0 +1,169 @@
+"""
+Tests for resumable cracking checkpoints
+"""
+
+import json
+import os
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+OTHER_LINE = PMKID_LINE.replace("4d4fe7aa", "00000000")
+
+
+def write_wordlist(tmp_path, words, name="words.txt"):
+    path = tmp_path / name
+    path.write_bytes(b"\n".join(words) + b"\n")
+    return str(path)
+
+
+def filler(count):
+    return [f"filler{index:06d}".encode() for index in range(count)]
+
+
+def cancel_after_first_report(_wifite3):
+    token = _wifite3.CancellationToken()
+    return token, lambda progress: token.cancel()
+
+
+def test_resume_after_cancel(tmp_path):
+    """Test that a cancelled attack resumes where it stopped"""
+    try:
+        import _wifite3
+
+        # Several batches, so the first report lands mid-wordlist
+        words = filler(600 * (os.cpu_count() or 1)) + [b"hashcat!"]
+        wordlist = write_wordlist(tmp_path, words)
+        checkpoint = str(tmp_path / "job.json")
+        attack = _wifite3.DictionaryAttack([PMKID_LINE, OTHER_LINE])
+
+        token, cancel = cancel_after_first_report(_wifite3)
+        first = attack.run(
+            wordlist,
+            progress=cancel,
+            cancel=token,
+            progress_interval=0,
+            checkpoint=checkpoint,
+        )
+        assert first.cancelled
+        assert not first.resumed
+        assert first.found == []
+        assert 0 < first.tested < len(words)
+
+        saved = json.loads(open(checkpoint).read())
+        assert saved["position"]["offset"] > 0
+        assert saved["tested"] == first.tested
+        assert saved["open"] == [PMKID_LINE, OTHER_LINE]
+
+        second = attack.run(wordlist, checkpoint=checkpoint)
+        assert second.resumed
+        assert second.exhausted
+        assert second.tested == len(words)
+        assert [hit.passphrase for hit in second.found] == ["hashcat!"]
+        assert second.elapsed >= first.elapsed
+
+        saved = json.loads(open(checkpoint).read())
+        assert saved["open"] == [OTHER_LINE]
+        assert saved["cracked"][0]["passphrase"] == b"hashcat!".hex()
+
+        # A finished job resumes to the same result without testing anything new
+        third = attack.run(wordlist, checkpoint=checkpoint)
+        assert third.tested == len(words)
+        assert [hit.passphrase for hit in third.found] == ["hashcat!"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_resume_with_rules(tmp_path):
+    """Test that every word and rule combination is tried exactly once across runs"""
+    try:
+        import _wifite3
+
+        words = filler(250 * (os.cpu_count() or 1)) + [b"HASHCAT"]
+        wordlist = write_wordlist(tmp_path, words)
+        rules = _wifite3.RuleSet([":", "$1", "l $!"])
+        checkpoint = str(tmp_path / "job.json")
+        attack = _wifite3.DictionaryAttack([PMKID_LINE])
+
+        token, cancel = cancel_after_first_report(_wifite3)
+        first = attack.run(
+            wordlist,
+            progress=cancel,
+            cancel=token,
+            progress_interval=0,
+            rules=rules,
+            checkpoint=checkpoint,
+        )
+        assert first.cancelled
+
+        second = attack.run(wordlist, rules=rules, checkpoint=checkpoint)
+        assert [hit.passphrase for hit in second.found] == ["hashcat!"]
+        # ":" leaves "HASHCAT" one byte short
+        assert second.tested + second.skipped == len(words) * 3
+        assert second.skipped == 1
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_refuses_changed_inputs(tmp_path):
+    """Test that a checkpoint only resumes against the inputs it was written for"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(tmp_path, [b"password1"])
+        checkpoint = str(tmp_path / "job.json")
+        attack = _wifite3.DictionaryAttack([PMKID_LINE])
+        attack.run(wordlist, checkpoint=checkpoint)
+
+        with pytest.raises(ValueError, match="targets"):
+            _wifite3.DictionaryAttack([OTHER_LINE]).run(wordlist, checkpoint=checkpoint)
+        with pytest.raises(ValueError, match="rules"):
+            attack.run(wordlist, rules=_wifite3.RuleSet(["c"]), checkpoint=checkpoint)
+        with pytest.raises(ValueError, match="attack mode"):
+            attack.run_mask(_wifite3.Mask("?d" * 8), checkpoint=checkpoint)
+
+        changed = write_wordlist(tmp_path, [b"password2"])
+        with pytest.raises(ValueError, match="wordlist"):
+            attack.run(changed, checkpoint=checkpoint)
+
+        with open(checkpoint, "w") as file:
+            file.write("{not json")
+        with pytest.raises(ValueError, match="malformed"):
+            attack.run(wordlist, checkpoint=checkpoint)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mask_checkpoint(tmp_path):
+    """Test that mask checkpoints record the keyspace index and range"""
+    try:
+        import _wifite3
+
+        mask = _wifite3.Mask("hash?lat!")
+        checkpoint = str(tmp_path / "mask.json")
+        attack = _wifite3.DictionaryAttack([PMKID_LINE, OTHER_LINE])
+
+        first = attack.run_mask(mask, checkpoint=checkpoint)
+        saved = json.loads(open(checkpoint).read())
+        assert saved["position"] == {"offset": 26, "rule": 0}
+        assert saved["inputs"]["mode"] == "mask"
+
+        again = attack.run_mask(mask, checkpoint=checkpoint)
+        assert again.resumed
+        assert again.tested == first.tested == 26
+        assert [hit.passphrase for hit in again.found] == ["hashcat!"]
+
+        with pytest.raises(ValueError, match="mask"):
+            attack.run_mask(mask, range=(0, 13), checkpoint=checkpoint)
+        with pytest.raises(ValueError, match="mask"):
+            attack.run_mask(_wifite3.Mask("hash?uat!"), checkpoint=checkpoint)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,189 @@
+"""
+Tests for the CPU dictionary attack
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+AP = bytes.fromhex("001122334455")
+CLIENT = bytes.fromhex("aabbccddeeff")
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+
+
+def handshake_line(passphrase, essid=b"Office", ap=AP):
+    import _wifite3
+
+    eapol = bytes.fromhex("0103005f02010a0010") + (1).to_bytes(8, "big") + SNONCE + bytes(50)
+    pmk = _wifite3.derive_pmk(passphrase, essid)
+    ptk = _wifite3.derive_ptk(pmk, ap, CLIENT, ANONCE, SNONCE)
+    mic = _wifite3.compute_mic(ptk[:16], eapol, 2)
+    return (
+        f"WPA*02*{mic.hex()}*{ap.hex()}*{CLIENT.hex()}*{essid.hex()}"
+        f"*{ANONCE.hex()}*{eapol.hex()}*00"
+    )
+
+
+def write_wordlist(tmp_path, words, newline=b"\n"):
+    path = tmp_path / "words.txt"
+    path.write_bytes(newline.join(words) + newline)
+    return str(path)
+
+
+def test_cracks_pmkid_and_handshake(tmp_path):
+    """Test that both target types are cracked from one wordlist"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(
+            tmp_path, [b"password1", b"hashcat!", b"letmein123", b"sesame-open"], b"\r\n"
+        )
+        attack = _wifite3.DictionaryAttack([PMKID_LINE, handshake_line("sesame-open")])
+        result = attack.run(wordlist)
+
+        cracked = {hit.target.hash_type: hit for hit in result.found}
+        assert cracked[1].passphrase == "hashcat!"
+        assert cracked[2].passphrase == "sesame-open"
+        assert cracked[2].passphrase_bytes == b"sesame-open"
+        assert cracked[1].pmk == _wifite3.derive_pmk("hashcat!", "hashcat-essid").hex()
+        assert result.tested == 4
+        assert not result.cancelled
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_length_limits(tmp_path):
+    """Test that candidates outside 8-63 bytes are skipped, not tested"""
+    try:
+        import _wifite3
+
+        words = [b"short", b"", b"x" * 64, b"exactly8", b"y" * 63]
+        result = _wifite3.DictionaryAttack([PMKID_LINE]).run(write_wordlist(tmp_path, words))
+
+        assert result.tested == 2
+        assert result.skipped == 3
+        assert result.found == []
+        assert result.exhausted
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_progress_reported(tmp_path):
+    """Test that progress snapshots reach the Python callback"""
+    try:
+        import _wifite3
+
+        snapshots = []
+        wordlist = write_wordlist(tmp_path, [b"candidate%d" % i for i in range(20)])
+        _wifite3.DictionaryAttack([PMKID_LINE]).run(
+            wordlist, progress=snapshots.append, progress_interval=0
+        )
+
+        assert snapshots
+        final = snapshots[-1]
+        assert isinstance(final, _wifite3.CrackProgress)
+        assert final.tested == 20
+        assert final.percent == pytest.approx(100.0)
+        assert final.rate > 0
+        assert "20 tested" in final.get_summary()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+
+def test_infinite_progress_interval(tmp_path):
+    """Test that an interval too large for a duration raises ValueError"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(tmp_path, [b"candidate"])
+        attack = _wifite3.DictionaryAttack([PMKID_LINE])
+        with pytest.raises(ValueError):
+            attack.run(wordlist, progress_interval=float("inf"))
+        with pytest.raises(ValueError):
+            attack.run_mask(_wifite3.Mask("?d"), progress_interval=float("inf"))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+def test_cancellation(tmp_path):
+    """Test that a cancelled token stops the attack"""
+    try:
+        import _wifite3
+
+        token = _wifite3.CancellationToken()
+        assert not token.cancelled
+        token.cancel()
+
+        wordlist = write_wordlist(tmp_path, [b"hashcat!"])
+        result = _wifite3.DictionaryAttack([PMKID_LINE]).run(wordlist, cancel=token)
+
+        assert token.cancelled
+        assert result.cancelled
+        assert result.tested == 0
+        assert result.found == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_shared_essid_reuses_pmk(tmp_path):
+    """Test that targets sharing an ESSID cost one PBKDF2 run per candidate"""
+    try:
+        import _wifite3
+
+        aps = [bytes([0x00, 0x11, 0x22, 0x33, 0x44, last]) for last in range(3)]
+        targets = [handshake_line("sesame-open", ap=ap) for ap in aps]
+        targets.append(handshake_line("letmein123", essid=b"Lab"))
+        wordlist = write_wordlist(tmp_path, [b"password1", b"letmein123", b"sesame-open"])
+
+        result = _wifite3.DictionaryAttack(targets).run(wordlist)
+
+        assert len(result.found) == 4
+        assert {hit.target.bssid for hit in result.found} >= {
+            ":".join(f"{byte:02x}" for byte in ap) for ap in aps
+        }
+        assert result.essid_groups == 2
+        assert result.pmks_computed == result.tested * 2
+        assert result.targets_checked == result.tested * 4
+        assert result.pmk_reuse_factor == pytest.approx(2.0)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_callback_errors_propagate(tmp_path):
+    """Test that an exception in the progress callback aborts the attack"""
+    try:
+        import _wifite3
+
+        def fail(progress):
+            raise KeyError("stop")
+
+        wordlist = write_wordlist(tmp_path, [b"password1"])
+        with pytest.raises(KeyError):
+            _wifite3.DictionaryAttack([PMKID_LINE]).run(wordlist, progress=fail)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_invalid_targets_rejected():
+    """Test that an attack needs at least one valid target"""
+    try:
+        import _wifite3
+
+        with pytest.raises(ValueError):
+            _wifite3.DictionaryAttack([])
+        with pytest.raises(ValueError):
+            _wifite3.DictionaryAttack(["WPA*01*broken"])
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,198 @@
+"""
+Tests for the PMK, PTK, PMKID and MIC primitives
+"""
+
+import hashlib
+import hmac
+
+import pytest
+
+AA = bytes.fromhex("001122334455")
+SPA = bytes.fromhex("aabbccddeeff")
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+
+
+def reference_prf(key, label, data, length):
+    output = b""
+    counter = 0
+    while len(output) < length:
+        output += hmac.new(key, label + b"\x00" + data + bytes([counter]), "sha1").digest()
+        counter += 1
+    return output[:length]
+
+
+def reference_kdf_sha256(key, label, context, bits):
+    output = b""
+    counter = 1
+    while len(output) < bits // 8:
+        message = counter.to_bytes(2, "little") + label + context + bits.to_bytes(2, "little")
+        output += hmac.new(key, message, "sha256").digest()
+        counter += 1
+    return output[: bits // 8]
+
+
+def ptk_context():
+    return min(AA, SPA) + max(AA, SPA) + min(ANONCE, SNONCE) + max(ANONCE, SNONCE)
+
+
+@pytest.mark.parametrize(
+    "passphrase,ssid,pmk",
+    [
+        # IEEE 802.11 Annex J.4
+        ("password", "IEEE", "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e"),
+        (
+            "ThisIsAPassword",
+            "ThisIsASSID",
+            "0dc0d6eb90555ed6419756b9a15ec3e3209b63df707dd508d14581f8982721af",
+        ),
+        ("a" * 32, "Z" * 32, "becb93866bb8c3832cb777c2f559807c8c59afcb6eae734885001300a981cc62"),
+    ],
+)
+def test_pmk_annex_j(passphrase, ssid, pmk):
+    """Test PMK derivation against the Annex J test vectors"""
+    try:
+        import _wifite3
+
+        assert _wifite3.derive_pmk(passphrase, ssid).hex() == pmk
+        assert _wifite3.derive_pmk(passphrase.encode(), ssid.encode()).hex() == pmk
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize(
+    "key,label,data,bits,output",
+    [
+        # IEEE 802.11 Annex J.3
+        (
+            b"\x0b" * 20,
+            b"prefix",
+            b"Hi There",
+            512,
+            "bcd4c650b30b9684951829e0d75f9d54b862175ed9f00606e17d8da35402ffee"
+            "75df78c3d31e0f889f012120c0862beb67753e7439ae242edb8373698356cf5a",
+        ),
+        (
+            b"Jefe",
+            b"prefix-2",
+            b"what do ya want for nothing?",
+            256,
+            "47c4908e30c947521ad20be9053450ecbea23d3aa604b77326d8b3825ff7475c",
+        ),
+        (
+            b"\xaa" * 80,
+            b"prefix-3",
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+            384,
+            "0ab6c33ccf70d0d736f4b04c8a7373255511abc5073713163bd0b8c9eeb7e195"
+            "6fa066820a73ddee3f6d3bd407e0682a",
+        ),
+    ],
+)
+def test_prf_annex_j(key, label, data, bits, output):
+    """Test the SHA1 PRF against the Annex J test vectors"""
+    try:
+        import _wifite3
+
+        assert _wifite3.prf(key, label, data, bits).hex() == output
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize("bits", [384, 512])
+def test_ptk_derivation(bits):
+    """Test PRF-384/512 and KDF-SHA256 PTK derivation"""
+    try:
+        import _wifite3
+
+        pmk = _wifite3.derive_pmk("password", "IEEE")
+        label = b"Pairwise key expansion"
+
+        ptk = _wifite3.derive_ptk(pmk, AA, SPA, ANONCE, SNONCE, bits)
+        assert ptk == reference_prf(pmk, label, ptk_context(), bits // 8)
+        # Address and nonce order does not matter
+        assert _wifite3.derive_ptk(pmk, SPA, AA, SNONCE, ANONCE, bits) == ptk
+
+        ptk_sha256 = _wifite3.derive_ptk(pmk, AA, SPA, ANONCE, SNONCE, bits, sha256=True)
+        assert ptk_sha256 == reference_kdf_sha256(pmk, label, ptk_context(), bits)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_hashcat_example():
+    """Test PMKID computation against hashcat's 22000 example hash ("hashcat!")"""
+    try:
+        import _wifite3
+
+        pmk = _wifite3.derive_pmk("hashcat!", "hashcat-essid")
+        aa = bytes.fromhex("fc690c158264")
+        spa = bytes.fromhex("f4747f87f9f4")
+
+        assert _wifite3.compute_pmkid(pmk, aa, spa).hex() == "4d4fe7aac3a2cecab195321ceb99a7d0"
+
+        expected = hmac.new(pmk, b"PMK Name" + aa + spa, "sha256").digest()[:16]
+        assert _wifite3.compute_pmkid(pmk, aa, spa, sha256=True) == expected
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mic_hmac_versions():
+    """Test HMAC-MD5 (version 1) and HMAC-SHA1-128 (version 2) MICs"""
+    try:
+        import _wifite3
+
+        kck = bytes(range(16))
+        eapol = bytes.fromhex("0103005f02010a0010") + bytes(90)
+
+        assert _wifite3.compute_mic(kck, eapol, 1) == hmac.new(kck, eapol, hashlib.md5).digest()
+        assert _wifite3.compute_mic(kck, eapol, 2) == hmac.new(kck, eapol, "sha1").digest()[:16]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize(
+    "message,mic",
+    [
+        # RFC 4493 AES-CMAC examples
+        (b"", "bb1d6929e95937287fa37d129b756746"),
+        (bytes.fromhex("6bc1bee22e409f96e93d7e117393172a"), "070a16b46b4d4144f79bdd9dd04a287c"),
+    ],
+)
+def test_mic_aes_cmac(message, mic):
+    """Test AES-128-CMAC MICs (version 3)"""
+    try:
+        import _wifite3
+
+        kck = bytes.fromhex("2b7e151628aed2a6abf7158809cf4f3c")
+        assert _wifite3.compute_mic(kck, message, 3).hex() == mic
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_invalid_inputs_rejected():
+    """Test that wrong lengths and unsupported versions raise ValueError"""
+    try:
+        import _wifite3
+
+        pmk = bytes(32)
+        with pytest.raises(ValueError):
+            _wifite3.derive_ptk(pmk[:31], AA, SPA, ANONCE, SNONCE)
+        with pytest.raises(ValueError):
+            _wifite3.derive_ptk(pmk, AA, SPA, ANONCE, SNONCE, 256)
+        with pytest.raises(ValueError):
+            _wifite3.compute_pmkid(pmk, AA[:5], SPA)
+        with pytest.raises(ValueError):
+            _wifite3.compute_mic(bytes(15), b"", 2)
+        with pytest.raises(ValueError):
+            _wifite3.compute_mic(bytes(16), b"", 4)
+        with pytest.raises(ValueError):
+            _wifite3.prf(b"key", b"label", b"data", 12)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,229 @@
+"""
+Tests for extracting and decoding EAPOL frames from 802.11 and Ethernet captures
+"""
+
+import struct
+
+import pytest
+
+from frames import (
+    KEY_INFO_GROUP1,
+    KEY_INFO_GROUP2,
+    KEY_INFO_M1,
+    KEY_INFO_M2,
+    KEY_INFO_M3,
+    KEY_INFO_M4,
+    LINKTYPE_ETHERNET,
+    LINKTYPE_IEEE802_11_RADIOTAP,
+    beacon_frame,
+    eapol_data_frame,
+    eapol_key,
+    pmkid_kde,
+    eapol_ethernet_frame,
+    radiotap_header,
+    rsn_element,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+PMKID = bytes.fromhex("1234567890abcdef1234567890abcdef")
+
+
+def m1_with_pmkid():
+    """Message 1 carrying a PMKID KDE"""
+    return eapol_key(KEY_INFO_M1, key_data=pmkid_kde(PMKID))
+
+
+def capture(tmp_path, frames, linktype=105):
+    """Capture PMKIDs after a beacon naming the network, which salts the hash line"""
+    import _wifite3
+
+    beacon = beacon_frame(AP, "Office")
+    if linktype == LINKTYPE_IEEE802_11_RADIOTAP:
+        beacon = radiotap_header({}) + beacon
+    path = write_pcap(tmp_path / "capture.pcap", [beacon, *frames], linktype)
+    return _wifite3.NetworkScanner.from_file(str(path)).capture_pmkid(1)
+
+
+@pytest.mark.parametrize(
+    "qos,htc", [(False, False), (True, False), (True, True)]
+)
+def test_eapol_in_data_frames(tmp_path, qos, htc):
+    """Test EAPOL detection in data, QoS data and QoS+HT Control frames"""
+    try:
+        frame = eapol_data_frame(AP, CLIENT, m1_with_pmkid(), qos=qos, htc=htc)
+        (result,) = capture(tmp_path, [frame])
+
+        assert result.bssid == AP
+        assert result.client_mac == CLIENT
+        assert result.pmkid == PMKID.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_eapol_address_selection_by_ds_bits(tmp_path):
+    """Test that AP and station addresses follow ToDS/FromDS"""
+    try:
+        frame = eapol_data_frame(AP, CLIENT, m1_with_pmkid(), from_ap=False)
+        (result,) = capture(tmp_path, [frame])
+
+        assert result.bssid == AP
+        assert result.client_mac == CLIENT
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_eapol_with_radiotap_and_padding(tmp_path):
+    """Test monitor-mode EAPOL with radiotap and trailing padding"""
+    try:
+        frame = radiotap_header({5: (-42,)}) + eapol_data_frame(
+            AP, CLIENT, m1_with_pmkid() + b"\x00" * 6, qos=True
+        )
+        (result,) = capture(tmp_path, [frame], LINKTYPE_IEEE802_11_RADIOTAP)
+
+        assert result.pmkid == PMKID.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_non_eapol_snap_ignored(tmp_path):
+    """Test that other LLC/SNAP ethertypes are not treated as EAPOL"""
+    try:
+        frame = eapol_data_frame(AP, CLIENT, m1_with_pmkid())
+        frame = frame.replace(b"\x88\x8e", b"\x08\x00", 1)
+
+        assert capture(tmp_path, [frame]) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_eapol_in_ethernet_frames(tmp_path):
+    """Test EAPOL extraction from wired Ethernet captures"""
+    try:
+        frame = eapol_ethernet_frame(AP, CLIENT, m1_with_pmkid())
+        (key,) = capture_keys(tmp_path, [frame], LINKTYPE_ETHERNET)
+
+        assert key.ap == AP
+        assert key.station == CLIENT
+        assert key.pmkid == PMKID
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def capture_keys(tmp_path, frames, linktype=105):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "keys.pcap", frames, linktype)
+    return _wifite3.NetworkScanner.from_file(str(path)).capture_eapol_keys(1)
+
+
+def test_eapol_key_fields(tmp_path):
+    """Test decoding of the EAPOL-Key descriptor fields"""
+    try:
+        nonce = bytes(range(32))
+        mic = bytes(range(100, 116))
+        pdu = eapol_key(KEY_INFO_M2, replay_counter=7, nonce=nonce, mic=mic, key_data=rsn_element())
+        (key,) = capture_keys(tmp_path, [eapol_data_frame(AP, CLIENT, pdu, from_ap=False)])
+
+        assert key.ap == AP
+        assert key.station == CLIENT
+        assert not key.from_ap
+        assert key.descriptor_type == 2
+        assert key.key_info == KEY_INFO_M2
+        assert key.key_version == 2
+        assert key.key_length == 16
+        assert key.replay_counter == 7
+        assert key.nonce == nonce
+        assert key.iv == b"\x00" * 16
+        assert key.rsc == b"\x00" * 8
+        assert key.mic == mic
+        assert key.key_data == rsn_element()
+        assert key.pairwise and key.has_mic
+        assert not (key.ack or key.install or key.secure or key.encrypted_key_data)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_eapol_key_classification(tmp_path):
+    """Test that frames are labelled M1 to M4 or group"""
+    try:
+        frames = [
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M1)),
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M2, key_data=rsn_element()), from_ap=False),
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M3, key_data=b"\xdd" * 56)),
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M4), from_ap=False),
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_GROUP1, key_data=b"\xdd" * 40)),
+            eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_GROUP2), from_ap=False),
+        ]
+        keys = capture_keys(tmp_path, frames)
+
+        assert [key.message for key in keys] == ["M1", "M2", "M3", "M4", "group", "group"]
+        assert [key.from_ap for key in keys] == [True, False, True, False, True, False]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_legacy_wpa_m4_without_secure_bit(tmp_path):
+    """Test that a legacy WPA M4 without the Secure bit is still classified as M4"""
+    try:
+        pdu = eapol_key(KEY_INFO_M2 & ~0x0002 | 0x0001, descriptor=254, key_length=32)
+        (key,) = capture_keys(tmp_path, [eapol_data_frame(AP, CLIENT, pdu, from_ap=False)])
+
+        assert key.descriptor_type == 254
+        assert key.key_version == 1
+        assert key.message == "M4"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mic_length_inferred(tmp_path):
+    """Test that a 24-byte MIC is detected without beacon information"""
+    try:
+        pdu = eapol_key(KEY_INFO_M2, mic=b"\x11" * 24, key_data=rsn_element())
+        (key,) = capture_keys(tmp_path, [eapol_data_frame(AP, CLIENT, pdu, from_ap=False)])
+
+        assert key.mic == b"\x11" * 24
+        assert key.key_data == rsn_element()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mic_length_from_beacon_akm(tmp_path):
+    """Test that the beaconed AKM selects the MIC length"""
+    try:
+        # The Suite B 192-bit AKM uses a 24-byte MIC, so a 16-byte MIC frame no longer fits
+        pdu = eapol_key(KEY_INFO_M4, mic=b"\x22" * 24)
+        beacon = beacon_frame(AP, "Enterprise", elements=rsn_element(group=9, pairwise=(9,), akms=(12,)), privacy=True)
+        (key,) = capture_keys(tmp_path, [beacon, eapol_data_frame(AP, CLIENT, pdu, from_ap=False)])
+
+        assert key.mic == b"\x22" * 24
+
+        keys = capture_keys(tmp_path, [beacon, eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M4), from_ap=False)])
+        assert keys == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_malformed_key_frames_ignored(tmp_path):
+    """Test that key frames with inconsistent lengths or descriptors are dropped"""
+    try:
+        truncated = eapol_key(KEY_INFO_M1)[:-3]
+        truncated = truncated[:2] + struct.pack(">H", len(truncated) - 4) + truncated[4:]
+        unknown_descriptor = eapol_key(KEY_INFO_M1, descriptor=1)
+
+        frames = [eapol_data_frame(AP, CLIENT, pdu) for pdu in (truncated, unknown_descriptor)]
+        assert capture_keys(tmp_path, frames) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,182 @@
+"""
+Tests for 4-way handshake tracking and WPA*02* hash generation
+"""
+
+import pytest
+
+from frames import (
+    KEY_INFO_M1,
+    KEY_INFO_M2,
+    KEY_INFO_M3,
+    KEY_INFO_M4,
+    beacon_frame,
+    eapol_data_frame,
+    eapol_key,
+    rsn_element,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+MIC_M2 = bytes(range(100, 116))
+MIC_M3 = bytes(range(116, 132))
+MIC_M4 = bytes(range(132, 148))
+
+
+def m1(replay_counter=1):
+    return eapol_data_frame(AP, CLIENT, eapol_key(KEY_INFO_M1, replay_counter, ANONCE))
+
+
+def m2(replay_counter=1, mic=MIC_M2):
+    pdu = eapol_key(KEY_INFO_M2, replay_counter, SNONCE, rsn_element(), mic=mic)
+    return eapol_data_frame(AP, CLIENT, pdu, from_ap=False)
+
+
+def m3(replay_counter=2):
+    pdu = eapol_key(KEY_INFO_M3, replay_counter, ANONCE, b"\xdd" * 56, mic=MIC_M3)
+    return eapol_data_frame(AP, CLIENT, pdu)
+
+
+def m4(replay_counter=2, nonce=SNONCE):
+    pdu = eapol_key(KEY_INFO_M4, replay_counter, nonce, mic=MIC_M4)
+    return eapol_data_frame(AP, CLIENT, pdu, from_ap=False)
+
+
+def capture(tmp_path, frames, **kwargs):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "handshake.pcap", frames)
+    return _wifite3.NetworkScanner.from_file(str(path)).capture_handshakes(1, **kwargs)
+
+
+def test_handshake_class_exported():
+    """Test that the HandshakeCapture class is exposed to Python"""
+    try:
+        import _wifite3
+
+        assert hasattr(_wifite3, "HandshakeCapture")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_full_handshake_pairs(tmp_path):
+    """Test that a complete handshake yields M1+M2, M2+M3 and M3+M4 pairs"""
+    try:
+        frames = [
+            (0.0, beacon_frame(AP, "Office", elements=rsn_element(), privacy=True)),
+            (1.0, m1()),
+            (1.1, m2()),
+            (1.2, m3()),
+            (1.3, m4()),
+        ]
+        captures = {capture.message_pair: capture for capture in capture(tmp_path, frames)}
+
+        assert set(captures) == {0, 2, 5}
+        assert captures[0].anonce == ANONCE.hex()
+        assert captures[0].mic == MIC_M2.hex()
+        assert captures[2].mic == MIC_M2.hex()
+        assert captures[5].mic == MIC_M4.hex()
+        assert captures[5].anonce == ANONCE.hex()
+        assert all(c.ssid == "Office" for c in captures.values())
+        assert all(c.key_version == 2 for c in captures.values())
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_hashcat_line(tmp_path):
+    """Test the WPA*02* line of an M1+M2 pair"""
+    try:
+        frames = [beacon_frame(AP, "Office"), m1(), m2()]
+        (result,) = capture(tmp_path, frames)
+
+        eapol = eapol_key(KEY_INFO_M2, 1, SNONCE, rsn_element())
+        fields = result.hashcat_format.split("*")
+        assert fields[:2] == ["WPA", "02"]
+        assert fields[2] == MIC_M2.hex()
+        assert fields[3] == "001122334455"
+        assert fields[4] == "aabbccddeeff"
+        assert fields[5] == b"Office".hex()
+        assert fields[6] == ANONCE.hex()
+        assert fields[7] == eapol.hex()  # MIC zeroed
+        assert fields[8] == "00"
+        assert result.eapol == eapol.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_replay_counter_mismatch(tmp_path):
+    """Test that messages with unrelated replay counters are not paired"""
+    try:
+        frames = [
+            beacon_frame(AP, "Office"),
+            m1(replay_counter=1),
+            m2(replay_counter=5),
+            m3(replay_counter=9),
+            m4(replay_counter=3),
+        ]
+
+        assert capture(tmp_path, frames) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_time_window(tmp_path):
+    """Test that messages further apart than the window are not paired"""
+    try:
+        frames = [(0.0, beacon_frame(AP, "Office")), (1.0, m1()), (20.0, m2())]
+
+        assert capture(tmp_path, frames) == []
+        assert len(capture(tmp_path, frames, window_seconds=30.0)) == 1
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_m4_without_snonce_ignored(tmp_path):
+    """Test that an M4 with an empty nonce does not produce an M3+M4 pair"""
+    try:
+        frames = [beacon_frame(AP, "Office"), m3(), m4(nonce=b"\x00" * 32)]
+
+        assert capture(tmp_path, frames) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_retransmissions_deduplicated(tmp_path):
+    """Test that retransmitted messages do not duplicate handshakes"""
+    try:
+        frames = [beacon_frame(AP, "Office"), m1(), m2(), m1(), m2()]
+
+        assert len(capture(tmp_path, frames)) == 1
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_non_utf8_ssid(tmp_path):
+    """Test that SSIDs that are not UTF-8 reach the hash line as raw bytes"""
+    try:
+        essid = b"\xff" * 32
+        (result,) = capture(tmp_path, [beacon_frame(AP, essid), m1(), m2()])
+
+        assert result.hashcat_format.split("*")[5] == essid.hex()
+        assert result.ssid == "\ufffd" * 32
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_unknown_ssid(tmp_path):
+    """Test that handshakes of networks whose SSID never appeared are dropped"""
+    try:
+        assert capture(tmp_path, [m1(), m2()]) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,208 @@
+"""
+Tests for running hashcat as a subprocess, with a stub in its place
+"""
+
+import json
+import sys
+import time
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+OTHER_LINE = (
+    "WPA*01*00112233445566778899aabbccddeeff*001122334455*aabbccddeeff"
+    "*4f6666696365***"
+)
+HIT = "4d4fe7aac3a2cecab195321ceb99a7d0:fc690c158264:f4747f87f9f4:hashcat-essid:hashcat!"
+
+
+def status(done, total, recovered=0, speed=1000):
+    return json.dumps(
+        {
+            "session": "hashcat",
+            "status": 3,
+            "progress": [done, total],
+            "recovered_hashes": [recovered, 2],
+            "recovered_salts": [recovered, 2],
+            "rejected": 4,
+            "devices": [{"device_id": 1, "speed": speed, "temp": 50}],
+        }
+    )
+
+
+def write_stub(tmp_path, body):
+    """A hashcat stand-in recording its arguments next to the outfile"""
+    path = tmp_path / "hashcat-stub"
+    path.write_text(
+        f"#!{sys.executable}\n"
+        "import json, pathlib, sys, time\n"
+        "args = sys.argv[1:]\n"
+        "outfile = next(a.split('=', 1)[1] for a in args if a.startswith('--outfile='))\n"
+        "pathlib.Path(outfile).with_name('args.json').write_text(json.dumps(args))\n"
+        + body
+    )
+    path.chmod(0o755)
+    return str(path)
+
+
+def test_run_reports_progress_and_hits(tmp_path):
+    """Test that status lines reach the progress callback and outfile hits the result"""
+    try:
+        import _wifite3
+
+        binary = write_stub(
+            tmp_path,
+            f"print({status(100, 1000)!r}, flush=True)\n"
+            "print('Session..........: hashcat', flush=True)\n"
+            f"print({status(400, 1000, recovered=1)!r}, flush=True)\n"
+            f"open(outfile, 'a').write({HIT!r} + '\\n')\n"
+            "sys.exit(0)\n",
+        )
+        workdir = tmp_path / "job"
+        runner = _wifite3.HashcatRunner(
+            [PMKID_LINE, OTHER_LINE], binary=binary, workdir=str(workdir)
+        )
+        wordlist = tmp_path / "words.txt"
+        wordlist.write_text("hashcat!\n")
+        reports = []
+
+        result = runner.run(
+            str(wordlist), progress=reports.append, rules=_wifite3.RuleSet(["c", "$1"])
+        )
+
+        assert [report.tested for report in reports] == [50, 200, 200]
+        assert reports[0].percent == pytest.approx(10.0)
+        assert reports[0].rate == pytest.approx(500.0)
+        assert reports[0].skipped == 2
+        assert reports[-1].found == 1
+        assert [hit.passphrase for hit in result.found] == ["hashcat!"]
+        assert result.found[0].target.essid == "hashcat-essid"
+        assert result.tested == 200
+        assert not result.exhausted
+        assert not result.cancelled
+        assert result.essid_groups == 2
+
+        args = json.loads((workdir / "args.json").read_text())
+        assert args[args.index("-m") + 1] == "22000"
+        assert args[args.index("-a") + 1] == "0"
+        assert "--status-json" in args
+        assert args[-1] == str(wordlist)
+        assert (workdir / "hashes.22000").read_text() == f"{PMKID_LINE}\n{OTHER_LINE}\n"
+        rules = args[args.index("-r") + 1]
+        assert open(rules).read() == "c\n$1\n"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mask_arguments(tmp_path):
+    """Test that masks pass their charsets and increment range to hashcat"""
+    try:
+        import _wifite3
+
+        binary = write_stub(tmp_path, "sys.exit(1)\n")
+        workdir = tmp_path / "job"
+        runner = _wifite3.HashcatRunner(
+            [PMKID_LINE], binary=binary, extra_args=["-w", "3"], workdir=str(workdir)
+        )
+        mask = _wifite3.Mask("?1?d?d", custom_charsets=["ab"], increment=True, increment_min=2)
+
+        result = runner.run_mask(mask)
+
+        assert result.exhausted
+        assert result.found == []
+        args = json.loads((workdir / "args.json").read_text())
+        assert args[args.index("-a") + 1] == "3"
+        assert args[args.index("-1") + 1] == "ab"
+        assert "--increment-min=2" in args and "--increment-max=3" in args
+        assert args[-4:] == ["-w", "3", str(workdir / "hashes.22000"), "?1?d?d"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_failures(tmp_path):
+    """Test that hashcat errors, a missing binary and a bad interval are raised"""
+    try:
+        import _wifite3
+
+        binary = write_stub(
+            tmp_path, "print('No devices found/left.', file=sys.stderr)\nsys.exit(255)\n"
+        )
+        wordlist = tmp_path / "words.txt"
+        wordlist.write_text("hashcat!\n")
+
+        with pytest.raises(RuntimeError, match="No devices found"):
+            _wifite3.HashcatRunner([PMKID_LINE], binary=binary).run(str(wordlist))
+        with pytest.raises(FileNotFoundError):
+            _wifite3.HashcatRunner(
+                [PMKID_LINE], binary=str(tmp_path / "missing")
+            ).run(str(wordlist))
+        with pytest.raises(ValueError):
+            _wifite3.HashcatRunner([PMKID_LINE], binary=binary).run(
+                str(wordlist), progress_interval=float("inf")
+            )
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_cancel(tmp_path):
+    """Test that cancelling terminates hashcat"""
+    try:
+        import _wifite3
+
+        binary = write_stub(
+            tmp_path,
+            "while True:\n"
+            f"    print({status(10, 1000)!r}, flush=True)\n"
+            "    time.sleep(0.1)\n",
+        )
+        wordlist = tmp_path / "words.txt"
+        wordlist.write_text("hashcat!\n")
+        token = _wifite3.CancellationToken()
+
+        started = time.monotonic()
+        result = _wifite3.HashcatRunner([PMKID_LINE], binary=binary).run(
+            str(wordlist), progress=lambda progress: token.cancel(), cancel=token
+        )
+
+        assert result.cancelled
+        assert result.tested == 5
+        assert time.monotonic() - started < 10
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_cli_rejects_checkpoint(tmp_path):
+    """Test that --checkpoint is refused together with --hashcat"""
+    from click.testing import CliRunner
+
+    from wifite3.cli import main
+
+    hashes = tmp_path / "hashes.22000"
+    hashes.write_text(PMKID_LINE + "\n")
+    wordlist = tmp_path / "words.txt"
+    wordlist.write_text("hashcat!\n")
+
+    runner = CliRunner()
+    result = runner.invoke(
+        main,
+        [
+            "--crack",
+            str(hashes),
+            "--wordlist",
+            str(wordlist),
+            "--hashcat",
+            "hashcat",
+            "--checkpoint",
+            str(tmp_path / "job.json"),
+        ],
+    )
+    assert result.exit_code == 2
+    assert "--checkpoint cannot be used with --hashcat" in result.output
@@ -0,0 +1,137 @@
+"""
+Tests for reading, merging, deduplicating and filtering 22000 hash files
+"""
+
+import pytest
+
+AP = "00:11:22:33:44:55"
+OTHER_AP = "66:77:88:99:aa:bb"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+PMKID = "1234567890abcdef1234567890abcdef"
+MIC = "00112233445566778899aabbccddeeff"
+ANONCE = bytes(range(32)).hex()
+# EAPOL-Key M2: key information 0x010a (version 2), replay counter 7
+EAPOL = "0103007502010a0010" + "0000000000000007" + "00" * 80 + "0000"
+
+
+def pmkid_line(ap="001122334455", essid=b"Office", pmkid=PMKID):
+    return f"WPA*01*{pmkid}*{ap}*aabbccddeeff*{essid.hex()}***"
+
+
+def eapol_line(message_pair="00", essid=b"Office", ap="001122334455"):
+    return f"WPA*02*{MIC}*{ap}*aabbccddeeff*{essid.hex()}*{ANONCE}*{EAPOL}*{message_pair}"
+
+
+def test_read_write_round_trip(tmp_path):
+    """Test that a file is read, skipping blanks and comments, and written back"""
+    try:
+        import _wifite3
+
+        source = tmp_path / "in.22000"
+        source.write_text(f"# session 1\n{pmkid_line()}\n\n{eapol_line()}\r\n")
+
+        hashes = _wifite3.HashFile.read(str(source))
+        assert len(hashes) == 2
+
+        target = tmp_path / "out.22000"
+        hashes.write(str(target))
+        assert target.read_text() == f"{pmkid_line()}\n{eapol_line()}\n"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_read_reports_line_number(tmp_path):
+    """Test that malformed lines are reported with their line number"""
+    try:
+        import _wifite3
+
+        source = tmp_path / "bad.22000"
+        source.write_text(f"{pmkid_line()}\nWPA*01*broken\n")
+
+        with pytest.raises(ValueError, match=":2:"):
+            _wifite3.HashFile.read(str(source))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_merge_and_dedupe():
+    """Test that merged files drop repeated hashes, keeping the first"""
+    try:
+        import _wifite3
+
+        first = _wifite3.HashFile([pmkid_line(), eapol_line("00")])
+        second = _wifite3.HashFile([pmkid_line(), eapol_line("02"), pmkid_line(essid=b"Lab")])
+        first.merge(second)
+
+        assert len(first) == 5
+        assert first.dedupe() == 2
+        assert [str(line) for line in first.lines] == [
+            pmkid_line(),
+            eapol_line("00"),
+            pmkid_line(essid=b"Lab"),
+        ]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_filters():
+    """Test filtering by ESSID, BSSID, OUI, hash type and message pair"""
+    try:
+        import _wifite3
+
+        hashes = _wifite3.HashFile(
+            [
+                pmkid_line(),
+                pmkid_line(ap="66778899aabb", essid=b"Lab"),
+                eapol_line("00"),
+                eapol_line("82"),
+                eapol_line("05", essid=b"Lab"),
+            ]
+        )
+
+        assert len(hashes.filter(essid="Lab")) == 2
+        assert len(hashes.filter(bssid=OTHER_AP)) == 1
+        assert len(hashes.filter(oui="00:11:22")) == 4
+        assert len(hashes.filter(oui="667788")) == 1
+        assert len(hashes.filter(hash_type=1)) == 2
+        assert len(hashes.filter(message_pair=2)) == 1
+        assert len(hashes.filter(essid="Office", hash_type=2)) == 2
+        assert len(hashes.filter(essid="Nowhere")) == 0
+
+        with pytest.raises(ValueError):
+            hashes.filter(oui="0011")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_captures_interchangeable():
+    """Test that captures and loaded hashes convert into each other"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture("Office", AP, CLIENT, PMKID)
+        hashes = _wifite3.HashFile([capture, _wifite3.HashLine.parse(eapol_line())])
+        hashes.add(capture.to_hash_line())
+        assert hashes.dedupe() == 1
+
+        pmkid, handshake = hashes.to_captures()
+        assert isinstance(pmkid, _wifite3.PMKIDCapture)
+        assert pmkid.ssid == "Office"
+        assert pmkid.pmkid == PMKID
+        assert pmkid.hashcat_format == capture.hashcat_format
+
+        assert isinstance(handshake, _wifite3.HandshakeCapture)
+        assert handshake.bssid == AP
+        assert handshake.message_pair == 0
+        assert handshake.replay_counter == 7
+        assert handshake.key_version == 2
+        assert handshake.timestamp is None
+        assert handshake.hashcat_format == eapol_line()
+        assert _wifite3.HashFile([handshake]).lines[0] == hashes.lines[1]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,157 @@
+"""
+Tests for hashcat 22000 line encoding and decoding
+"""
+
+import pytest
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+PMKID = "1234567890abcdef1234567890abcdef"
+MIC = "00112233445566778899aabbccddeeff"
+ANONCE = bytes(range(32)).hex()
+EAPOL = "0103007502010a00100000000000000001" + "00" * 82 + "0000"
+
+PMKID_LINE = f"WPA*01*{PMKID}*001122334455*aabbccddeeff*{b'Office'.hex()}***"
+EAPOL_LINE = (
+    f"WPA*02*{MIC}*001122334455*aabbccddeeff*{b'Office'.hex()}*{ANONCE}*{EAPOL}*00"
+)
+
+
+def test_pmkid_line_format():
+    """Test that PMKID lines carry a hex ESSID and empty trailing fields"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture("Office", AP, CLIENT, PMKID)
+
+        assert capture.hashcat_format == PMKID_LINE
+        assert capture.get_hashcat_format() == PMKID_LINE
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_capture_normalizes_fields():
+    """Test that MAC addresses and PMKID are stored in lowercase colon form"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture("Office", "00-11-22-33-44-55", CLIENT.upper(), PMKID.upper())
+
+        assert capture.bssid == AP
+        assert capture.client_mac == CLIENT
+        assert capture.pmkid == PMKID
+        assert capture.hashcat_format == PMKID_LINE
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize("line", [PMKID_LINE, EAPOL_LINE, PMKID_LINE + "01"])
+def test_round_trip(line):
+    """Test that decoding and re-encoding a line reproduces it"""
+    try:
+        import _wifite3
+
+        assert str(_wifite3.HashLine.parse(line)) == line
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_decoded_fields():
+    """Test the fields of decoded PMKID and EAPOL lines"""
+    try:
+        import _wifite3
+
+        pmkid = _wifite3.HashLine.parse(PMKID_LINE)
+        assert pmkid.hash_type == 1
+        assert pmkid.hash == PMKID
+        assert pmkid.bssid == AP
+        assert pmkid.client_mac == CLIENT
+        assert pmkid.essid == "Office"
+        assert pmkid.anonce is None
+        assert pmkid.eapol is None
+        assert pmkid.message_pair is None
+
+        eapol = _wifite3.HashLine.parse(EAPOL_LINE)
+        assert eapol.hash_type == 2
+        assert eapol.hash == MIC
+        assert eapol.anonce == ANONCE
+        assert eapol.eapol == EAPOL
+        assert eapol.message_pair == 0
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_capture_lines_decode():
+    """Test that capture hash lines go through the shared decoder"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture("Café ☕", AP, CLIENT, PMKID)
+        line = _wifite3.HashLine.parse(capture.hashcat_format)
+
+        assert line.essid == "Café ☕"
+        assert str(line) == capture.hashcat_format
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize(
+    "line",
+    [
+        "",
+        PMKID_LINE.replace("WPA*", "WPB*", 1),
+        PMKID_LINE.replace("WPA*01*", "WPA*03*"),
+        PMKID_LINE + "*",
+        PMKID_LINE.replace(PMKID, PMKID[:-2]),
+        PMKID_LINE.replace(PMKID, PMKID[:-1] + "g"),
+        PMKID_LINE.replace("001122334455", "0011223344"),
+        PMKID_LINE.replace(b"Office".hex(), ""),
+        PMKID_LINE.replace(b"Office".hex(), "41" * 33),
+        PMKID_LINE[:-2] + f"{ANONCE}**",
+        EAPOL_LINE[:-3],
+        EAPOL_LINE.replace(ANONCE, ANONCE[:-2]),
+        EAPOL_LINE.replace(EAPOL, EAPOL[:40]),
+        EAPOL_LINE.replace(EAPOL, "00" * 257),
+        EAPOL_LINE[:-2] + "100",
+    ],
+)
+def test_invalid_lines_rejected(line):
+    """Test that malformed lines raise ValueError"""
+    try:
+        import _wifite3
+
+        with pytest.raises(ValueError):
+            _wifite3.HashLine.parse(line)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    [
+        {"ssid": "x" * 33},
+        {"ssid": ""},
+        {"bssid": "00:11:22:33:44"},
+        {"client_mac": "aa:bb:cc:dd:ee:fg"},
+        {"pmkid": PMKID[:30]},
+    ],
+)
+def test_invalid_pmkid_capture_rejected(kwargs):
+    """Test that PMKIDCapture validates the fields of its hash line"""
+    try:
+        import _wifite3
+
+        fields = {"ssid": "Office", "bssid": AP, "client_mac": CLIENT, "pmkid": PMKID}
+        fields.update(kwargs)
+
+        with pytest.raises(ValueError):
+            _wifite3.PMKIDCapture(**fields)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
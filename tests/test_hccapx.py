@@ -0,0 +1,239 @@
+"""
+Tests for hccapx and hccap import/export
+"""
+
+import struct
+
+import pytest
+
+PMKID = "1234567890abcdef1234567890abcdef"
+MIC = bytes(range(16))
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+# EAPOL-Key M2 with key information 0x010a (version 2), MIC zeroed
+EAPOL = bytes.fromhex("0103005f02010a0010" + "00" * 7 + "01") + SNONCE + bytes(64)
+# EAPOL-Key M3 (Install, Ack, MIC, Secure, Encrypted) carrying the AP nonce
+EAPOL_M3 = bytes.fromhex("0103005f0213ca0010" + "00" * 7 + "02") + ANONCE + bytes(64)
+# EAPOL-Key M4 (MIC, Secure) carrying the station nonce
+EAPOL_M4 = bytes.fromhex("0103005f02030a0010" + "00" * 7 + "02") + SNONCE + bytes(64)
+
+EAPOL_LINE = (
+    f"WPA*02*{MIC.hex()}*001122334455*aabbccddeeff*{b'Office'.hex()}"
+    f"*{ANONCE.hex()}*{EAPOL.hex()}*00"
+)
+PMKID_LINE = f"WPA*01*{PMKID}*001122334455*aabbccddeeff*{b'Office'.hex()}***"
+
+
+def hccapx_record(
+    signature=b"HCPX",
+    version=4,
+    essid=b"Office",
+    eapol=EAPOL,
+    mic=MIC,
+    message_pair=0,
+    anonce=ANONCE,
+    snonce=SNONCE,
+):
+    return (
+        signature
+        + struct.pack("<IBB", version, message_pair, len(essid))
+        + essid.ljust(32, b"\x00")
+        + bytes([2])
+        + mic
+        + bytes.fromhex("001122334455")
+        + anonce
+        + bytes.fromhex("aabbccddeeff")
+        + snonce
+        + struct.pack("<H", len(eapol))
+        + eapol.ljust(256, b"\x00")
+    )
+
+
+def test_hccapx_round_trip(tmp_path):
+    """Test that handshake lines survive an hccapx write and read"""
+    try:
+        import _wifite3
+
+        path = tmp_path / "out.hccapx"
+        errors = _wifite3.HashFile([EAPOL_LINE]).write_hccapx(str(path))
+
+        assert errors == []
+        assert path.read_bytes() == hccapx_record()
+
+        report = _wifite3.HashFile.read_hccapx(str(path))
+        assert report.errors == []
+        assert [str(line) for line in report.hashes.lines] == [EAPOL_LINE]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_hccap_round_trip(tmp_path):
+    """Test that handshake lines survive an hccap write and read"""
+    try:
+        import _wifite3
+
+        path = tmp_path / "out.hccap"
+        _wifite3.HashFile([EAPOL_LINE]).write_hccap(str(path))
+
+        data = path.read_bytes()
+        assert len(data) == 392
+        assert data[:36] == b"Office".ljust(36, b"\x00")
+        assert data[48:80] == SNONCE
+        assert data[80:112] == ANONCE
+        assert struct.unpack("<II", data[368:376]) == (len(EAPOL), 2)
+
+        report = _wifite3.HashFile.read_hccap(str(path))
+        assert [str(line) for line in report.hashes.lines] == [EAPOL_LINE]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mic_zeroed_on_import(tmp_path):
+    """Test that a MIC left in the stored EAPOL frame is zeroed"""
+    try:
+        import _wifite3
+
+        eapol = bytearray(EAPOL)
+        eapol[81:97] = MIC
+        path = tmp_path / "mic.hccapx"
+        path.write_bytes(hccapx_record(eapol=bytes(eapol)))
+
+        (line,) = _wifite3.HashFile.read_hccapx(str(path)).hashes.lines
+        assert line.eapol == EAPOL.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_import_errors_reported_per_entry(tmp_path):
+    """Test that bad records are reported by index while good ones load"""
+    try:
+        import _wifite3
+
+        path = tmp_path / "mixed.hccapx"
+        path.write_bytes(
+            hccapx_record()
+            + hccapx_record(signature=b"XXXX")
+            + hccapx_record(version=3)
+            + hccapx_record(essid=b"")
+            + hccapx_record()[:100]
+        )
+
+        report = _wifite3.HashFile.read_hccapx(str(path))
+        assert len(report.hashes) == 1
+        assert [error.index for error in report.errors] == [1, 2, 3, 4]
+        assert "signature" in report.errors[0].message
+        assert "version" in report.errors[1].message
+        assert "ESSID" in report.errors[2].message
+        assert "Truncated" in report.errors[3].message
+        assert report.errors[3].get_summary().startswith("Entry 4:")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_message_pairs_with_ap_eapol(tmp_path):
+    """Test that M3 frames pair their AP nonce with the record's station nonce"""
+    try:
+        import _wifite3
+
+        for message_pair in [3, 4, 0x84]:
+            record = hccapx_record(eapol=EAPOL_M3, message_pair=message_pair)
+            path = tmp_path / "m3.hccapx"
+            path.write_bytes(record)
+
+            report = _wifite3.HashFile.read_hccapx(str(path))
+            assert report.errors == []
+            (line,) = report.hashes.lines
+            assert line.anonce == SNONCE.hex()
+            assert line.eapol == EAPOL_M3.hex()
+            assert line.message_pair == message_pair
+
+            report.hashes.write_hccapx(str(path))
+            assert path.read_bytes() == record
+
+            path = tmp_path / "m3.hccap"
+            report.hashes.write_hccap(str(path))
+            data = path.read_bytes()
+            assert data[48:80] == SNONCE
+            assert data[80:112] == ANONCE
+            (line,) = _wifite3.HashFile.read_hccap(str(path)).hashes.lines
+            assert line.anonce == SNONCE.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_message_pair_with_m4_eapol(tmp_path):
+    """Test that M4 frames carrying the station nonce convert and zeroed ones fail"""
+    try:
+        import _wifite3
+
+        zeroed = EAPOL_M4[:17] + bytes(32) + EAPOL_M4[49:]
+        path = tmp_path / "m4.hccapx"
+        path.write_bytes(
+            hccapx_record(eapol=EAPOL_M4, message_pair=5)
+            + hccapx_record(eapol=zeroed, message_pair=5)
+        )
+
+        report = _wifite3.HashFile.read_hccapx(str(path))
+        (line,) = report.hashes.lines
+        assert line.anonce == ANONCE.hex()
+        assert line.message_pair == 5
+        assert [error.index for error in report.errors] == [1]
+        assert "zeroed nonce" in report.errors[0].message
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_nonce_mismatch(tmp_path):
+    """Test that records whose nonce fields contradict the EAPOL frame are rejected"""
+    try:
+        import _wifite3
+
+        other = bytes(reversed(SNONCE))
+        path = tmp_path / "nonce.hccapx"
+        path.write_bytes(
+            hccapx_record()
+            + hccapx_record(snonce=other)
+            + hccapx_record(eapol=EAPOL_M3, message_pair=4, anonce=other)
+        )
+
+        report = _wifite3.HashFile.read_hccapx(str(path))
+        assert len(report.hashes) == 1
+        assert [error.index for error in report.errors] == [1, 2]
+        assert "Station nonce" in report.errors[0].message
+        assert "AP nonce" in report.errors[1].message
+
+        path = tmp_path / "nonce.hccap"
+        _wifite3.HashFile([EAPOL_LINE]).write_hccap(str(path))
+        record = bytearray(path.read_bytes())
+        record[48:80] = other
+        path.write_bytes(bytes(record))
+
+        report = _wifite3.HashFile.read_hccap(str(path))
+        assert len(report.hashes) == 0
+        assert "nonce" in report.errors[0].message
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_export_skipped(tmp_path):
+    """Test that PMKID hashes are reported instead of written"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture("Office", "00:11:22:33:44:55", "aa:bb:cc:dd:ee:ff", PMKID)
+        path = tmp_path / "out.hccapx"
+        errors = _wifite3.HashFile([capture, EAPOL_LINE]).write_hccapx(str(path))
+
+        assert [error.index for error in errors] == [0]
+        assert "PMKID" in errors[0].message
+        assert len(path.read_bytes()) == 393
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
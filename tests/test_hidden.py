@@ -0,0 +1,129 @@
+"""
+Tests for probe response parsing and hidden SSID decloaking
+"""
+
+import pytest
+
+from frames import (
+    association_request_frame,
+    beacon_frame,
+    probe_request_frame,
+    probe_response_frame,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+
+
+def scan_frames(tmp_path, frames):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "capture.pcap", frames)
+    scanner = _wifite3.NetworkScanner.from_file(str(path))
+    return {network.bssid: network for network in scanner.scan()}
+
+
+def test_visible_network_not_hidden(tmp_path):
+    """Test that broadcast SSIDs are not flagged as hidden"""
+    try:
+        networks = scan_frames(tmp_path, [beacon_frame(AP, "Visible")])
+
+        assert networks[AP].ssid == "Visible"
+        assert not networks[AP].was_hidden
+        assert networks[AP].revealed_by is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize("ssid", [b"", b"\x00" * 8])
+def test_hidden_network_unresolved(tmp_path, ssid):
+    """Test that empty and zero-filled SSIDs are reported as hidden"""
+    try:
+        networks = scan_frames(tmp_path, [beacon_frame(AP, ssid)])
+
+        assert networks[AP].ssid == "Hidden Network"
+        assert networks[AP].was_hidden
+        assert networks[AP].revealed_by is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_probe_response_reveals_ssid(tmp_path):
+    """Test that a probe response decloaks a hidden network in either order"""
+    try:
+        for frames in (
+            [beacon_frame(AP, b""), probe_response_frame(AP, "Secret", CLIENT)],
+            [probe_response_frame(AP, "Secret", CLIENT), beacon_frame(AP, b"")],
+        ):
+            networks = scan_frames(tmp_path, frames)
+
+            assert networks[AP].ssid == "Secret"
+            assert networks[AP].was_hidden
+            assert networks[AP].revealed_by == "probe_response"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_probe_response_alone_creates_network(tmp_path):
+    """Test that a probe response is enough to discover a network"""
+    try:
+        networks = scan_frames(tmp_path, [probe_response_frame(AP, "Answer", CLIENT, channel=3)])
+
+        assert networks[AP].ssid == "Answer"
+        assert networks[AP].channel == 3
+        assert not networks[AP].was_hidden
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize(
+    "frame,source",
+    [
+        (association_request_frame(AP, "Secret", CLIENT), "association_request"),
+        (
+            association_request_frame(AP, "Secret", CLIENT, current_ap="02:00:00:00:00:01"),
+            "reassociation_request",
+        ),
+        (probe_request_frame(CLIENT, "Secret", bssid=AP), "probe_request"),
+    ],
+)
+def test_client_frames_reveal_ssid(tmp_path, frame, source):
+    """Test that client requests aimed at a BSSID decloak it"""
+    try:
+        networks = scan_frames(tmp_path, [beacon_frame(AP, b""), frame])
+
+        assert networks[AP].ssid == "Secret"
+        assert networks[AP].revealed_by == source
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_broadcast_probe_request_ignored(tmp_path):
+    """Test that broadcast probe requests do not name any network"""
+    try:
+        networks = scan_frames(
+            tmp_path, [beacon_frame(AP, b""), probe_request_frame(CLIENT, "Elsewhere")]
+        )
+
+        assert networks[AP].ssid == "Hidden Network"
+        assert networks[AP].revealed_by is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_requests_do_not_create_networks(tmp_path):
+    """Test that client requests alone do not produce network records"""
+    try:
+        networks = scan_frames(tmp_path, [association_request_frame(AP, "Secret", CLIENT)])
+
+        assert networks == {}
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
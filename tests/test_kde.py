@@ -0,0 +1,195 @@
+"""
+Tests for key data encapsulation (KDE) parsing and PMKID extraction
+"""
+
+import pytest
+
+from frames import (
+    KEY_INFO_M1,
+    KEY_INFO_M2,
+    KEY_INFO_M3,
+    beacon_frame,
+    eapol_data_frame,
+    eapol_key,
+    kde,
+    mac_bytes,
+    pmkid_kde,
+    rsn_element,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+PMKID = bytes.fromhex("4d4fe7aac3a2cecab195321ceb99a7d0")
+
+
+def capture(tmp_path, frames, method="capture_pmkid"):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "capture.pcap", frames)
+    scanner = _wifite3.NetworkScanner.from_file(str(path))
+    return getattr(scanner, method)(1)
+
+
+def test_pmkid_from_m1_kde(tmp_path):
+    """Test PMKID extraction from the PMKID KDE of message 1"""
+    try:
+        pdu = eapol_key(KEY_INFO_M1, replay_counter=3, key_data=pmkid_kde(PMKID))
+        frames = [(50.0, beacon_frame(AP, "Office")), (50.25, eapol_data_frame(AP, CLIENT, pdu))]
+        (result,) = capture(tmp_path, frames)
+
+        assert result.pmkid == PMKID.hex()
+        assert result.bssid == AP
+        assert result.client_mac == CLIENT
+        assert result.source_message == "M1"
+        assert result.replay_counter == 3
+        assert result.timestamp == pytest.approx(50.25)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_zero_pmkid_filtered(tmp_path):
+    """Test that all-zero PMKIDs are not reported"""
+    try:
+        pdu = eapol_key(KEY_INFO_M1, key_data=pmkid_kde(b"\x00" * 16))
+        frames = [beacon_frame(AP, "Office"), eapol_data_frame(AP, CLIENT, pdu)]
+
+        assert capture(tmp_path, frames) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_from_m2_rsn_element(tmp_path):
+    """Test PMKID extraction from the station's RSN element in message 2"""
+    try:
+        pdu = eapol_key(KEY_INFO_M2, key_data=rsn_element(pmkids=(b"\x00" * 16, PMKID)))
+        frames = [beacon_frame(AP, "Office"), eapol_data_frame(AP, CLIENT, pdu, from_ap=False)]
+        (result,) = capture(tmp_path, frames)
+
+        assert result.pmkid == PMKID.hex()
+        assert result.source_message == "M2"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_only_from_m1_and_m2(tmp_path):
+    """Test that PMKID KDEs in other messages are ignored"""
+    try:
+        pdu = eapol_key(KEY_INFO_M3 & ~0x1000, key_data=pmkid_kde(PMKID))
+        frames = [beacon_frame(AP, "Office"), eapol_data_frame(AP, CLIENT, pdu)]
+
+        assert capture(tmp_path, frames) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_correlated_with_beacon_ssid(tmp_path):
+    """Test that correlated captures name the network the PMKID belongs to"""
+    try:
+        pdu = eapol_key(KEY_INFO_M1, key_data=pmkid_kde(PMKID))
+        (result,) = capture(
+            tmp_path,
+            [beacon_frame(AP, "Office"), eapol_data_frame(AP, CLIENT, pdu)],
+            "capture_pmkid_with_correlation",
+        )
+
+        assert result.ssid == "Office"
+        assert result.pmkid == PMKID.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_before_beacon(tmp_path):
+    """Test that a PMKID seen before the beacon naming its network is kept"""
+    try:
+        pdu = eapol_key(KEY_INFO_M1, key_data=pmkid_kde(PMKID))
+        frames = [eapol_data_frame(AP, CLIENT, pdu), beacon_frame(AP, "Office")]
+
+        for method in ["capture_pmkid", "capture_pmkid_with_correlation"]:
+            (result,) = capture(tmp_path, frames, method)
+            assert result.ssid == "Office"
+            assert result.pmkid == PMKID.hex()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_non_utf8_ssid(tmp_path):
+    """Test that a PMKID line is salted with the SSID bytes as broadcast"""
+    try:
+        essid = b"Caf\xe9"
+        pdu = eapol_key(KEY_INFO_M1, key_data=pmkid_kde(PMKID))
+        (result,) = capture(
+            tmp_path,
+            [beacon_frame(AP, essid), eapol_data_frame(AP, CLIENT, pdu)],
+            "capture_pmkid_with_correlation",
+        )
+
+        assert result.hashcat_format.split("*")[5] == essid.hex()
+        assert result.ssid == "Caf\ufffd"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_without_ssid_skipped(tmp_path):
+    """Test that PMKIDs of networks whose SSID never appeared yield no hash line"""
+    try:
+        pdu = eapol_key(KEY_INFO_M1, key_data=pmkid_kde(PMKID))
+        frames = [eapol_data_frame(AP, CLIENT, pdu)]
+
+        assert capture(tmp_path, frames) == []
+        assert capture(tmp_path, frames, "capture_pmkid_with_correlation") == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_key_data_kdes(tmp_path):
+    """Test decoding of GTK, IGTK, BIGTK and MAC address KDEs"""
+    try:
+        gtk = bytes(range(16))
+        igtk = bytes(range(16, 32))
+        bigtk = bytes(range(32, 48))
+        key_data = (
+            rsn_element()
+            + kde(1, b"\x01\x00" + gtk)
+            + kde(9, b"\x04\x00" + b"\x00" * 6 + igtk)
+            + kde(14, b"\x06\x00" + b"\x00" * 6 + bigtk)
+            + kde(3, mac_bytes(CLIENT))
+            + key_data_padding()
+        )
+        pdu = eapol_key(KEY_INFO_M3 & ~0x1000, key_data=key_data)
+        (key,) = capture(tmp_path, [eapol_data_frame(AP, CLIENT, pdu)], "capture_eapol_keys")
+
+        assert key.gtk == gtk
+        assert key.igtk == igtk
+        assert key.bigtk == bigtk
+        assert key.kde_mac_address == CLIENT
+        assert key.pmkid is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_encrypted_key_data_not_parsed(tmp_path):
+    """Test that encrypted key data yields no KDEs"""
+    try:
+        pdu = eapol_key(KEY_INFO_M3, key_data=kde(1, b"\x01\x00" + b"\x11" * 16))
+        (key,) = capture(tmp_path, [eapol_data_frame(AP, CLIENT, pdu)], "capture_eapol_keys")
+
+        assert key.encrypted_key_data
+        assert key.gtk is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def key_data_padding():
+    """Key data padding: 0xDD followed by zeros"""
+    return b"\xdd\x00\x00\x00"
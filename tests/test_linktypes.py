@@ -0,0 +1,106 @@
+"""
+Tests for link-type aware frame dispatch
+"""
+
+import pytest
+
+from frames import (
+    LINKTYPE_ETHERNET,
+    LINKTYPE_IEEE802_11_AVS,
+    LINKTYPE_IEEE802_11_PRISM,
+    LINKTYPE_PPI,
+    avs_header,
+    beacon_frame,
+    ppi_header,
+    prism_header,
+    write_pcap,
+)
+
+BEACON = beacon_frame("00:11:22:33:44:55", "LinkTest", channel=11)
+
+
+def scan(tmp_path, frames, linktype):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "capture.pcap", frames, linktype=linktype)
+    return _wifite3.NetworkScanner.from_file(str(path)).scan()
+
+
+def test_unsupported_link_type(tmp_path):
+    """Test that an unknown link type raises a typed error"""
+    try:
+        import _wifite3
+
+        assert issubclass(_wifite3.UnsupportedLinkTypeError, RuntimeError)
+        with pytest.raises(_wifite3.UnsupportedLinkTypeError, match="228"):
+            scan(tmp_path, [b"\x45" + b"\x00" * 39], linktype=228)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_ppi_link_type(tmp_path):
+    """Test beacons encapsulated in PPI headers"""
+    try:
+        networks = scan(tmp_path, [ppi_header(2462, -47) + BEACON], LINKTYPE_PPI)
+
+        assert len(networks) == 1
+        assert networks[0].ssid == "LinkTest"
+        assert networks[0].rssi == -47
+        assert networks[0].frequency == 2462
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_ppi_fcs_flag(tmp_path):
+    """Test that the PPI FCS flags strip or drop frames"""
+    try:
+        with_fcs = ppi_header(2462, -47, flags=0x0001) + BEACON + b"\x30\x02\x00\x00"
+        networks = scan(tmp_path, [with_fcs], LINKTYPE_PPI)
+        assert networks[0].encryption == "Open"
+
+        bad_fcs = ppi_header(2462, -47, flags=0x0005) + BEACON + b"\x00" * 4
+        assert scan(tmp_path, [bad_fcs], LINKTYPE_PPI) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_prism_link_type(tmp_path):
+    """Test beacons encapsulated in Prism headers"""
+    try:
+        networks = scan(
+            tmp_path, [prism_header(11, -61) + BEACON], LINKTYPE_IEEE802_11_PRISM
+        )
+
+        assert networks[0].ssid == "LinkTest"
+        assert networks[0].rssi == -61
+        assert networks[0].frequency == 2462
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_avs_link_type(tmp_path):
+    """Test beacons encapsulated in AVS headers, including under the Prism DLT"""
+    try:
+        for linktype in (LINKTYPE_IEEE802_11_AVS, LINKTYPE_IEEE802_11_PRISM):
+            networks = scan(tmp_path, [avs_header(11, -70) + BEACON], linktype)
+
+            assert networks[0].ssid == "LinkTest"
+            assert networks[0].rssi == -70
+            assert networks[0].frequency == 2462
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_ethernet_link_type_has_no_beacons(tmp_path):
+    """Test that Ethernet captures are accepted but yield no networks"""
+    try:
+        ethernet = b"\xff" * 6 + b"\x00\x11\x22\x33\x44\x55" + b"\x08\x00" + BEACON
+        assert scan(tmp_path, [ethernet], LINKTYPE_ETHERNET) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
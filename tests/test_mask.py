@@ -0,0 +1,134 @@
+"""
+Tests for the mask candidate generator
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+
+
+def test_builtin_charsets():
+    """Test the keyspace and ordering of the built-in charsets"""
+    try:
+        import _wifite3
+
+        assert _wifite3.Mask("?d" * 10).keyspace == 10**10
+        assert _wifite3.Mask("?H" * 8).keyspace == 16**8
+        assert _wifite3.Mask("?l?u?h?s?a").keyspace == 26 * 26 * 16 * 33 * 95
+        assert _wifite3.Mask("?b").keyspace == 256
+
+        mask = _wifite3.Mask("?u?d")
+        assert mask.length == 2
+        assert mask.candidate(0) == b"A0"
+        assert mask.candidate(1) == b"A1"
+        assert mask.candidate(10) == b"B0"
+        assert mask.candidate(259) == b"Z9"
+        assert mask.candidate(260) is None
+
+        assert _wifite3.Mask("?h").candidate(15) == b"f"
+        assert _wifite3.Mask("?s").candidate(0) == b" "
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_literals_and_custom_charsets():
+    """Test literal characters, ?? and custom charsets"""
+    try:
+        import _wifite3
+
+        mask = _wifite3.Mask("net-?1??", custom_charsets=["ab?d"])
+        assert mask.keyspace == 12
+        assert mask.candidate(0) == b"net-a?"
+        assert mask.candidate(11) == b"net-9?"
+
+        # Duplicate bytes in a custom charset are generated once
+        assert _wifite3.Mask("?1", custom_charsets=["aab"]).keyspace == 2
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_increment():
+    """Test that incremental masks generate shorter lengths first"""
+    try:
+        import _wifite3
+
+        mask = _wifite3.Mask("?d?d?d", increment=True)
+        assert mask.keyspace == 10 + 100 + 1000
+        assert mask.candidate(9) == b"9"
+        assert mask.candidate(10) == b"00"
+        assert mask.candidate(110) == b"000"
+
+        mask = _wifite3.Mask("?d?d?d?d", increment=True, increment_min=3)
+        assert mask.keyspace == 1000 + 10000
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_split():
+    """Test that split ranges cover the keyspace without overlap"""
+    try:
+        import _wifite3
+
+        mask = _wifite3.Mask("?d?d", increment=True)
+        ranges = mask.split(4)
+        assert ranges == [(0, 28), (28, 56), (56, 83), (83, 110)]
+        assert _wifite3.Mask("?d").split(20) == [(i, i + 1) for i in range(10)]
+        with pytest.raises(ValueError):
+            mask.split(0)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_invalid_masks():
+    """Test that malformed masks are rejected"""
+    try:
+        import _wifite3
+
+        for mask, kwargs in [
+            ("", {}),
+            ("?x", {}),
+            ("?d?", {}),
+            ("?2", {"custom_charsets": ["ab"]}),
+            ("?1", {"custom_charsets": [""]}),
+            ("?1", {"custom_charsets": ["a"] * 5}),
+            ("?d?d", {"increment": True, "increment_min": 3}),
+            ("?b" * 9, {}),
+        ]:
+            with pytest.raises(ValueError):
+                _wifite3.Mask(mask, **kwargs)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_mask_attack():
+    """Test that a mask attack cracks a hash and honours keyspace ranges"""
+    try:
+        import _wifite3
+
+        attack = _wifite3.DictionaryAttack([PMKID_LINE])
+        mask = _wifite3.Mask("hash?lat!")
+
+        result = attack.run_mask(mask)
+        assert [hit.passphrase for hit in result.found] == ["hashcat!"]
+
+        # "c" is candidate 2, outside the second half of the keyspace
+        first, second = mask.split(2)
+        result = attack.run_mask(mask, range=second)
+        assert result.found == []
+        assert result.tested == 13
+        assert result.exhausted
+
+        with pytest.raises(ValueError):
+            attack.run_mask(mask, range=(0, 27))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,149 @@
+"""
+Tests for merging repeated observations of a network
+"""
+
+import pytest
+
+from frames import (
+    LINKTYPE_IEEE802_11_RADIOTAP,
+    beacon_frame,
+    probe_response_frame,
+    radiotap_header,
+    rsn_element,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:ff"
+
+
+def scan_one(tmp_path, frames, linktype=105):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "capture.pcap", frames, linktype)
+    (network,) = _wifite3.NetworkScanner.from_file(str(path)).scan()
+    return network
+
+
+def test_beacon_statistics(tmp_path):
+    """Test that beacons accumulate counts, timestamps and signal statistics"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [
+                (10.0, radiotap_header({5: (-60,)}) + beacon_frame(AP, "Office")),
+                (11.0, radiotap_header({5: (-40,)}) + beacon_frame(AP, "Office")),
+                (12.5, radiotap_header({5: (-50,)}) + beacon_frame(AP, "Office")),
+            ],
+            LINKTYPE_IEEE802_11_RADIOTAP,
+        )
+
+        assert network.beacon_count == 3
+        assert network.first_seen == pytest.approx(10.0)
+        assert network.last_seen == pytest.approx(12.5)
+        assert network.rssi == -50
+        assert network.rssi_min == -60
+        assert network.rssi_max == -40
+        assert network.rssi_avg == pytest.approx(-50.0)
+        assert network.changes == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_probe_responses_not_counted_as_beacons(tmp_path):
+    """Test that probe responses update the record without counting as beacons"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [beacon_frame(AP, "Office"), probe_response_frame(AP, "Office", CLIENT)],
+        )
+
+        assert network.beacon_count == 1
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_channel_change_reported(tmp_path):
+    """Test that a channel switch is recorded and both channels are kept"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [
+                (1.0, beacon_frame(AP, "Office", channel=1)),
+                (2.0, beacon_frame(AP, "Office", channel=11)),
+            ],
+        )
+
+        assert network.channel == 11
+        assert network.channels == [1, 11]
+        (change,) = network.changes
+        assert change.attribute == "channel"
+        assert (change.old_value, change.new_value) == ("1", "11")
+        assert change.timestamp == pytest.approx(2.0)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_encryption_change_reported(tmp_path):
+    """Test that a network dropping its encryption is reported"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [
+                beacon_frame(AP, "Office", elements=rsn_element(), privacy=True),
+                beacon_frame(AP, "Office"),
+            ],
+        )
+
+        assert network.encryption == "Open"
+        assert network.security is None
+        (change,) = network.changes
+        assert change.attribute == "encryption"
+        assert (change.old_value, change.new_value) == ("WPA2", "Open")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_security_label_change_reported(tmp_path):
+    """Test that an AKM change within the same encryption family is reported"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [
+                beacon_frame(AP, "Office", elements=rsn_element(akms=(2,)), privacy=True),
+                beacon_frame(AP, "Office", elements=rsn_element(akms=(2, 8)), privacy=True),
+            ],
+        )
+
+        (change,) = network.changes
+        assert change.attribute == "security"
+        assert (change.old_value, change.new_value) == ("WPA2-PSK", "WPA3-Transition")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_hidden_beacon_keeps_decloaked_name(tmp_path):
+    """Test that hidden beacons after a probe response keep the revealed SSID"""
+    try:
+        network = scan_one(
+            tmp_path,
+            [
+                beacon_frame(AP, b""),
+                probe_response_frame(AP, "Secret", CLIENT),
+                beacon_frame(AP, b""),
+                beacon_frame(AP, b"\x00\x00\x00"),
+            ],
+        )
+
+        assert network.ssid == "Secret"
+        assert network.revealed_by == "probe_response"
+        assert network.beacon_count == 3
+        assert network.changes == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
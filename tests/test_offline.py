@@ -0,0 +1,92 @@
+"""
+Tests for reading captures from pcap files
+"""
+
+import pytest
+
+from frames import beacon_frame, write_pcap
+
+
+def test_from_file_missing():
+    """Test that a missing capture file is reported"""
+    try:
+        import _wifite3
+
+        with pytest.raises(RuntimeError, match="not found"):
+            _wifite3.NetworkScanner.from_file("/nonexistent/capture.pcap")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_from_file_is_not_live(tmp_path):
+    """Test that a file-backed scanner reports itself as offline"""
+    try:
+        import _wifite3
+
+        path = write_pcap(tmp_path / "empty.pcap", [])
+        scanner = _wifite3.NetworkScanner.from_file(str(path))
+        assert not scanner.is_live
+        assert _wifite3.NetworkScanner("wlan0").is_live
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_scan_from_file(tmp_path):
+    """Test scanning beacons from a pcap file"""
+    try:
+        import _wifite3
+
+        path = write_pcap(
+            tmp_path / "beacons.pcap",
+            [
+                beacon_frame("00:11:22:33:44:55", "FirstNetwork", channel=1),
+                beacon_frame("66:77:88:99:aa:bb", "SecondNetwork", channel=11),
+            ],
+        )
+        scanner = _wifite3.NetworkScanner.from_file(str(path))
+        networks = {network.bssid: network for network in scanner.scan()}
+
+        assert len(networks) == 2
+        assert networks["00:11:22:33:44:55"].ssid == "FirstNetwork"
+        assert networks["00:11:22:33:44:55"].channel == 1
+        assert networks["66:77:88:99:aa:bb"].ssid == "SecondNetwork"
+        assert networks["66:77:88:99:aa:bb"].channel == 11
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_scan_from_file_reads_past_live_limit(tmp_path):
+    """Test that file scans are not cut short by the live packet limit"""
+    try:
+        import _wifite3
+
+        frames = [beacon_frame("00:11:22:33:44:55", "Filler")] * 150
+        frames.append(beacon_frame("66:77:88:99:aa:bb", "LastNetwork"))
+        path = write_pcap(tmp_path / "many.pcap", frames)
+
+        networks = _wifite3.NetworkScanner.from_file(str(path)).scan()
+        assert "LastNetwork" in {network.ssid for network in networks}
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_capture_pmkid_from_file(tmp_path):
+    """Test that PMKID capture accepts a file source"""
+    try:
+        import _wifite3
+
+        path = write_pcap(
+            tmp_path / "beacons.pcap", [beacon_frame("00:11:22:33:44:55", "Net")]
+        )
+        scanner = _wifite3.NetworkScanner.from_file(str(path))
+
+        assert scanner.capture_pmkid(0) == []
+        assert scanner.capture_pmkid_with_correlation(0) == []
+        assert scanner.get_ssid_cache() == {"00:11:22:33:44:55": "Net"}
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,127 @@
+"""
+Tests for the on-disk PMK database
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+ESSID = "hashcat-essid"
+
+
+def write_wordlist(tmp_path, words, name="words.txt"):
+    path = tmp_path / name
+    path.write_bytes(b"\n".join(words) + b"\n")
+    return str(path)
+
+
+def test_build_and_lookup(tmp_path):
+    """Test that a built database holds the PMK of every passphrase and ESSID"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(tmp_path, [b"password1", b"hashcat!", b"short"])
+        path = str(tmp_path / "isp.pmkdb")
+        db = _wifite3.PmkDatabase.build(path, [ESSID, b"Office"], wordlist)
+
+        assert len(db) == 4
+        assert db.essids() == [b"Office", ESSID.encode()]
+        assert db.count(ESSID) == 2
+        assert db.count("Unknown") == 0
+        assert db.lookup(ESSID, "hashcat!") == _wifite3.derive_pmk("hashcat!", ESSID)
+        assert db.lookup(b"Office", b"password1") == _wifite3.derive_pmk("password1", "Office")
+        assert db.lookup(ESSID, "short") is None
+
+        reopened = _wifite3.PmkDatabase(path)
+        assert len(reopened) == 4
+        assert reopened.lookup(ESSID, "password1") == db.lookup(ESSID, "password1")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_import_adds_only_new_records(tmp_path):
+    """Test that importing merges new passphrases and skips stored ones"""
+    try:
+        import _wifite3
+
+        db = _wifite3.PmkDatabase(str(tmp_path / "new.pmkdb"))
+        assert len(db) == 0
+
+        first = write_wordlist(tmp_path, [b"zzzzzzzz", b"password1", b"password1"], "a.txt")
+        assert db.import_wordlist(ESSID, first) == 2
+        second = write_wordlist(tmp_path, [b"password1", b"aaaaaaaa", b"hashcat!"], "b.txt")
+        assert db.import_wordlist(ESSID, second) == 2
+
+        assert len(db) == 4
+        for word in ["aaaaaaaa", "hashcat!", "password1", "zzzzzzzz"]:
+            assert db.lookup(ESSID, word) == _wifite3.derive_pmk(word, ESSID)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_non_utf8_essid_round_trip(tmp_path):
+    """Test that listed ESSIDs are bytes that can be passed back in"""
+    try:
+        import _wifite3
+
+        essid = b"\xffCaf\xe9"
+        wordlist = write_wordlist(tmp_path, [b"password1", b"hashcat!"])
+        db = _wifite3.PmkDatabase.build(str(tmp_path / "db.pmkdb"), [essid], wordlist)
+
+        (listed,) = db.essids()
+        assert listed == essid
+        assert db.count(listed) == 2
+        assert db.lookup(listed, "hashcat!") == _wifite3.derive_pmk(b"hashcat!", essid)
+        # Run files of the import are gone
+        assert sorted(path.name for path in tmp_path.iterdir()) == ["db.pmkdb", "words.txt"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_verify_against_captures(tmp_path):
+    """Test that stored PMKs crack hash lines and PMKID captures"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(tmp_path, [b"password1", b"hashcat!"])
+        db = _wifite3.PmkDatabase.build(str(tmp_path / "db.pmkdb"), [ESSID], wordlist)
+
+        hit = db.verify(PMKID_LINE)
+        assert hit.passphrase == "hashcat!"
+        assert hit.pmk == _wifite3.derive_pmk("hashcat!", ESSID).hex()
+
+        capture = _wifite3.HashLine.parse(PMKID_LINE).to_capture()
+        assert db.verify(capture).passphrase == "hashcat!"
+
+        other = PMKID_LINE.replace("686173686361742d6573736964", "4f6666696365")
+        assert db.verify(other) is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_invalid_input_rejected(tmp_path):
+    """Test that bad ESSIDs and foreign files are refused"""
+    try:
+        import _wifite3
+
+        wordlist = write_wordlist(tmp_path, [b"password1"])
+        db = _wifite3.PmkDatabase(str(tmp_path / "db.pmkdb"))
+        with pytest.raises(ValueError):
+            db.import_wordlist("", wordlist)
+        with pytest.raises(ValueError):
+            db.import_wordlist("x" * 33, wordlist)
+
+        foreign = tmp_path / "foreign.pmkdb"
+        foreign.write_bytes(b"not a database at all")
+        with pytest.raises(ValueError):
+            _wifite3.PmkDatabase(str(foreign))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -22,7 +22,7 @@ def test_pmkid_capture_creation():
         assert pmkid_capture.client_mac == "aa:bb:cc:dd:ee:ff"
         assert pmkid_capture.pmkid == "1234567890abcdef1234567890abcdef"
         assert "WPA*01*" in pmkid_capture.hashcat_format
-        assert "TestNetwork" in pmkid_capture.hashcat_format
+        assert "TestNetwork".encode().hex() in pmkid_capture.hashcat_format
 
     except ImportError:
         pytest.skip("Rust module not yet built")
@@ -45,7 +45,8 @@ def test_pmkid_hashcat_format():
         assert "1234567890abcdef1234567890abcdef" in hashcat_format
         assert "001122334455" in hashcat_format  # BSSID without colons
         assert "aabbccddeeff" in hashcat_format  # Client MAC without colons
-        assert "TestNetwork" in hashcat_format
+        assert "TestNetwork".encode().hex() in hashcat_format  # ESSID hex encoded
+        assert hashcat_format.endswith("***")  # No ANonce, EAPOL or message pair
 
     except ImportError:
         pytest.skip("Rust module not yet built")
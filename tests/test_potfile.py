@@ -0,0 +1,147 @@
+"""
+Tests for hashcat potfile interoperability
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+POT_22000 = "4d4fe7aac3a2cecab195321ceb99a7d0:fc690c158264:f4747f87f9f4:hashcat-essid:hashcat!"
+POT_16800 = (
+    "4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964:hashcat!"
+)
+AP = bytes.fromhex("001122334455")
+CLIENT = bytes.fromhex("aabbccddeeff")
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+
+
+def handshake_line(passphrase, essid=b"Office"):
+    import _wifite3
+
+    eapol = bytes.fromhex("0103005f02010a0010") + (1).to_bytes(8, "big") + SNONCE + bytes(50)
+    pmk = _wifite3.derive_pmk(passphrase, essid)
+    ptk = _wifite3.derive_ptk(pmk, AP, CLIENT, ANONCE, SNONCE)
+    mic = _wifite3.compute_mic(ptk[:16], eapol, 2)
+    return (
+        f"WPA*02*{mic.hex()}*{AP.hex()}*{CLIENT.hex()}*{essid.hex()}"
+        f"*{ANONCE.hex()}*{eapol.hex()}*00"
+    )
+
+
+def write_potfile(tmp_path, lines):
+    path = tmp_path / "hashcat.potfile"
+    path.write_text("".join(line + "\n" for line in lines))
+    return str(path)
+
+
+def test_read_entries(tmp_path):
+    """Test that WPA entries of every supported mode are read and others ignored"""
+    try:
+        import _wifite3
+
+        pmk = _wifite3.derive_pmk("sesame-open", "Office").hex()
+        path = write_potfile(
+            tmp_path,
+            [
+                POT_22000,
+                "8743b52063cd84097a65d1633f5c74f5:hashcat",
+                POT_16800,
+                PMKID_LINE + ":hashcat!",
+                f"00112233445566778899aabbccddeeff:001122334455:aabbccddeeff:Office:{pmk}",
+                "00112233445566778899aabbccddeeff:001122334455:aabbccddeeff:$HEX[4f3a41]:pa:ss$HEX",
+                "",
+            ],
+        )
+        potfile = _wifite3.Potfile(path)
+
+        assert len(potfile) == 5
+        assert potfile.ignored == 1
+        first = potfile.entries[0]
+        assert first.hash == "4d4fe7aac3a2cecab195321ceb99a7d0"
+        assert first.bssid == "fc:69:0c:15:82:64"
+        assert first.essid == "hashcat-essid"
+        assert first.passphrase == "hashcat!"
+        assert first.pmk is None
+        assert str(first) == POT_22000
+        assert str(potfile.entries[1]) == POT_22000
+        assert str(potfile.entries[2]) == POT_22000
+
+        assert potfile.entries[3].passphrase is None
+        assert potfile.entries[3].pmk == pmk
+        assert potfile.entries[4].essid == "O:A"
+        assert potfile.entries[4].passphrase == "pa:ss$HEX"
+        assert str(potfile.entries[4]).endswith(":$HEX[4f3a41]:pa:ss$HEX")
+
+        assert len(_wifite3.Potfile(str(tmp_path / "missing.potfile"))) == 0
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_match_captures(tmp_path):
+    """Test that captures are matched exactly or through their ESSID's passphrases"""
+    try:
+        import _wifite3
+
+        pmk = _wifite3.derive_pmk("sesame-open", "Office").hex()
+        path = write_potfile(
+            tmp_path,
+            [POT_22000, f"00112233445566778899aabbccddeeff:001122334455:aabbccddeeff:Office:{pmk}"],
+        )
+        potfile = _wifite3.Potfile(path)
+
+        capture = _wifite3.HashLine.parse(PMKID_LINE).to_capture()
+        hit = potfile.lookup(capture)
+        assert hit.passphrase == "hashcat!"
+        assert hit.pmk == _wifite3.derive_pmk("hashcat!", "hashcat-essid").hex()
+
+        # A new capture of a cracked network is matched by verifying its passphrase
+        recaptured = handshake_line("hashcat!", essid=b"hashcat-essid")
+        assert potfile.lookup(recaptured).passphrase == "hashcat!"
+
+        # 22001 entries crack with the stored PMK
+        office = potfile.lookup(handshake_line("sesame-open"))
+        assert office.pmk == pmk
+        assert office.passphrase_bytes == b""
+
+        unknown = handshake_line("letmein123", essid=b"Lab")
+        wrong = handshake_line("letmein123")
+        found, remaining = potfile.partition([PMKID_LINE, unknown, wrong])
+        assert [hit.target.essid for hit in found] == ["hashcat-essid"]
+        assert [str(line) for line in remaining] == [unknown, wrong]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_append_hits(tmp_path):
+    """Test that engine hits are appended in potfile format exactly once"""
+    try:
+        import _wifite3
+
+        wordlist = tmp_path / "words.txt"
+        wordlist.write_bytes(b"password1\nhashcat!\nsesame\xffopen\n")
+        targets = [PMKID_LINE, handshake_line(b"sesame\xffopen")]
+        result = _wifite3.DictionaryAttack(targets).run(str(wordlist))
+        assert len(result.found) == 2
+
+        path = str(tmp_path / "new.potfile")
+        potfile = _wifite3.Potfile(path)
+        assert potfile.append(result.found) == 2
+        assert potfile.append(result.found) == 0
+
+        lines = open(path).read().splitlines()
+        assert POT_22000 in lines
+        assert any(line.endswith(":Office:$HEX[736573616d65ff6f70656e]") for line in lines)
+
+        found, remaining = _wifite3.Potfile(path).partition(targets)
+        assert len(found) == 2
+        assert remaining == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,123 @@
+"""
+Tests for radiotap decoding of captured frames
+"""
+
+import pytest
+
+from frames import (
+    LINKTYPE_IEEE802_11_RADIOTAP,
+    beacon_frame,
+    radiotap_header,
+    write_pcap,
+)
+
+
+def scan_frames(tmp_path, frames):
+    import _wifite3
+
+    path = write_pcap(
+        tmp_path / "radiotap.pcap", frames, linktype=LINKTYPE_IEEE802_11_RADIOTAP
+    )
+    return _wifite3.NetworkScanner.from_file(str(path)).scan()
+
+
+def test_radiotap_signal_and_frequency(tmp_path):
+    """Test that RSSI and frequency come from the radiotap header"""
+    try:
+        header = radiotap_header(
+            {
+                0: (123456789,),
+                1: (0,),
+                2: (2,),
+                3: (2437, 0x00A0),
+                5: (-42,),
+                6: (-95,),
+            }
+        )
+        networks = scan_frames(
+            tmp_path, [header + beacon_frame("00:11:22:33:44:55", "Radio", channel=6)]
+        )
+
+        assert len(networks) == 1
+        assert networks[0].ssid == "Radio"
+        assert networks[0].rssi == -42
+        assert networks[0].frequency == 2437
+        assert networks[0].channel == 6
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_fhss_alignment(tmp_path):
+    """Test that FHSS after Flags is padded to 2 bytes before the fields following it"""
+    try:
+        header = radiotap_header({1: (0,), 4: (1, 2), 5: (-42,), 11: (1,)})
+        networks = scan_frames(
+            tmp_path, [header + beacon_frame("00:11:22:33:44:55", "Hopper", channel=1)]
+        )
+
+        assert networks[0].rssi == -42
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_channel_from_frequency_without_ds_parameter(tmp_path):
+    """Test that the receive frequency provides the channel when the beacon lacks one"""
+    try:
+        header = radiotap_header({3: (5180, 0x0140), 5: (-60,)})
+        frame = beacon_frame("00:11:22:33:44:55", "FiveGig")
+        # Strip the DS Parameter Set element from the end of the frame
+        frame = frame[:-3]
+        networks = scan_frames(tmp_path, [header + frame])
+
+        assert networks[0].channel == 36
+        assert networks[0].rssi == -60
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_trailing_fcs_is_removed(tmp_path):
+    """Test that a trailing FCS is not parsed as an information element"""
+    try:
+        header = radiotap_header({1: (0x10,), 5: (-30,)})
+        frame = beacon_frame("00:11:22:33:44:55", "WithFcs") + b"\x30\x02\xaa\xbb"
+        networks = scan_frames(tmp_path, [header + frame])
+
+        assert networks[0].ssid == "WithFcs"
+        assert networks[0].encryption == "Open"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_bad_fcs_frames_are_dropped(tmp_path):
+    """Test that frames flagged with a bad FCS are ignored"""
+    try:
+        header = radiotap_header({1: (0x50,)})
+        frame = beacon_frame("00:11:22:33:44:55", "Corrupt") + b"\x00" * 4
+        assert scan_frames(tmp_path, [header + frame]) == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_extended_presence_bitmap(tmp_path):
+    """Test headers with additional per-antenna radiotap namespaces"""
+    try:
+        header = radiotap_header(
+            {0: (1,), 1: (0,), 3: (2412, 0x00A0), 5: (-50,)},
+            {5: (-55,), 11: (0,)},
+            {5: (-58,), 11: (1,)},
+        )
+        networks = scan_frames(
+            tmp_path, [header + beacon_frame("00:11:22:33:44:55", "Chains", 1)]
+        )
+
+        assert networks[0].ssid == "Chains"
+        assert networks[0].rssi == -50
+        assert networks[0].frequency == 2412
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
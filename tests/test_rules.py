@@ -0,0 +1,161 @@
+"""
+Tests for the hashcat rule engine
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+
+# Examples from the hashcat rule documentation, applied to "p@ssW0rd"
+EXAMPLES = [
+    (":", b"p@ssW0rd"),
+    ("l", b"p@ssw0rd"),
+    ("u", b"P@SSW0RD"),
+    ("c", b"P@ssw0rd"),
+    ("C", b"p@SSW0RD"),
+    ("t", b"P@SSw0RD"),
+    ("T3", b"p@sSW0rd"),
+    ("r", b"dr0Wss@p"),
+    ("d", b"p@ssW0rdp@ssW0rd"),
+    ("p2", b"p@ssW0rdp@ssW0rdp@ssW0rd"),
+    ("f", b"p@ssW0rddr0Wss@p"),
+    ("{", b"@ssW0rdp"),
+    ("}", b"dp@ssW0r"),
+    ("$1", b"p@ssW0rd1"),
+    ("^1", b"1p@ssW0rd"),
+    ("[", b"@ssW0rd"),
+    ("]", b"p@ssW0r"),
+    ("D3", b"p@sW0rd"),
+    ("'6", b"p@ssW0"),
+    ("x04", b"p@ss"),
+    ("i4!", b"p@ss!W0rd"),
+    ("o3$", b"p@s$W0rd"),
+    ("ss$", b"p@$$W0rd"),
+    ("@s", b"p@W0rd"),
+]
+
+
+@pytest.mark.parametrize("rule,expected", EXAMPLES)
+def test_rule_functions(rule, expected):
+    """Test each supported function against hashcat's documented output"""
+    try:
+        import _wifite3
+
+        assert _wifite3.Rule(rule).apply("p@ssW0rd") == expected
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_rule_composition():
+    """Test that functions apply left to right and ignore spaces between them"""
+    try:
+        import _wifite3
+
+        assert _wifite3.Rule("c $2 $0 $2 $4").apply(b"summer") == b"Summer2024"
+        assert _wifite3.Rule("$ ").apply("a") == b"a "
+        assert _wifite3.Rule("").apply("word") == b"word"
+        # Positions past the end leave the word unchanged
+        assert _wifite3.Rule("DZ").apply("word") == b"word"
+        assert str(_wifite3.Rule("c $1")) == "c $1"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_rule_output_limit():
+    """Test that functions growing a word past hashcat's 256-byte buffer are skipped"""
+    try:
+        import _wifite3
+
+        word = b"a" * 63
+        assert _wifite3.Rule("p9p9p9p9").apply(word) == word
+        assert _wifite3.Rule("p2p9").apply(word) == word * 3
+        assert _wifite3.Rule("d d f").apply(word) == word * 4
+        assert _wifite3.Rule("d d d").apply(word) == word * 4
+
+        # 255 bytes is the longest output
+        word = b"a" * 127
+        assert _wifite3.Rule("d $1 $2").apply(word) == word * 2 + b"1"
+        assert _wifite3.Rule("d ^1 ^2").apply(word) == b"1" + word * 2
+        assert _wifite3.Rule("d i01 i02").apply(word) == b"1" + word * 2
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_extract_out_of_range():
+    """Test that extracting past the end of the word leaves it unchanged"""
+    try:
+        import _wifite3
+
+        assert _wifite3.Rule("x25").apply("word") == b"word"
+        assert _wifite3.Rule("x40").apply("word") == b"word"
+        assert _wifite3.Rule("x13").apply("word") == b"ord"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_invalid_rules():
+    """Test that unsupported opcodes and missing arguments are rejected"""
+    try:
+        import _wifite3
+
+        with pytest.raises(ValueError, match="'X' at column 3"):
+            _wifite3.Rule("c X")
+        with pytest.raises(ValueError, match="missing an argument"):
+            _wifite3.Rule("c $")
+        with pytest.raises(ValueError, match="Invalid position"):
+            _wifite3.Rule("T!")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_load_rule_file(tmp_path):
+    """Test that .rule files report unsupported opcodes by line number"""
+    try:
+        import _wifite3
+
+        path = tmp_path / "best.rule"
+        path.write_text("# comment\n:\nc\n\n$1 $2\nz5\nu\nk\n")
+
+        with pytest.raises(ValueError) as error:
+            _wifite3.RuleSet.load(str(path))
+        assert "line 6:" in str(error.value)
+        assert "line 8:" in str(error.value)
+
+        rules = _wifite3.RuleSet.load(str(path), skip_invalid=True)
+        assert [str(rule) for rule in rules.rules] == [":", "c", "$1 $2", "u"]
+        assert len(rules.skipped) == 2
+        assert rules.skipped[0].startswith("line 6: Unsupported rule opcode 'z'")
+        assert rules.apply("word") == [b"word", b"Word", b"word12", b"WORD"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_attack_with_rules(tmp_path):
+    """Test that rule mutations are fed into the dictionary attack"""
+    try:
+        import _wifite3
+
+        wordlist = tmp_path / "words.txt"
+        wordlist.write_bytes(b"password\nHASHCAT\n")
+        rules = _wifite3.RuleSet([":", "$1", "l $!"])
+
+        result = _wifite3.DictionaryAttack([PMKID_LINE]).run(str(wordlist), rules=rules)
+
+        assert [hit.passphrase for hit in result.found] == ["hashcat!"]
+        # ":" leaves "HASHCAT" one byte short
+        assert result.tested == 5
+        assert result.skipped == 1
+        assert result.exhausted
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -96,7 +96,8 @@ def test_scan_networks():
                 assert isinstance(network.ssid, str)
                 assert isinstance(network.bssid, str)
                 assert isinstance(network.channel, int)
-                assert isinstance(network.rssi, int)
+                # Captures without a radio header carry no signal strength
+                assert network.rssi is None or isinstance(network.rssi, int)
                 assert isinstance(network.encryption, str)
 
                 # Validate BSSID format (MAC address)
@@ -106,10 +107,11 @@ def test_scan_networks():
                 assert 1 <= network.channel <= 14
 
                 # Validate RSSI range (typically -100 to 0)
-                assert -100 <= network.rssi <= 0
+                if network.rssi is not None:
+                    assert -100 <= network.rssi <= 0
 
                 # Validate encryption type
-                assert network.encryption in ["Open", "WEP", "WPA", "WPA2", "WPA3"]
+                assert network.encryption in ["Open", "WEP", "WPA", "WPA2", "WPA3", "OWE"]
 
                 # If we have real data, SSID should not be mock format
                 assert not network.ssid.startswith("Network_")
@@ -0,0 +1,173 @@
+"""
+Tests for RSN information element decoding
+"""
+
+import pytest
+
+from frames import (
+    beacon_frame,
+    information_element,
+    rsn_element,
+    wpa_element,
+    write_pcap,
+)
+
+
+def scan_network(tmp_path, elements, privacy=False):
+    import _wifite3
+
+    path = write_pcap(
+        tmp_path / "security.pcap",
+        [
+            beacon_frame(
+                "00:11:22:33:44:55", "Secure", elements=elements, privacy=privacy
+            )
+        ],
+    )
+    networks = _wifite3.NetworkScanner.from_file(str(path)).scan()
+    assert len(networks) == 1
+    return networks[0]
+
+
+@pytest.mark.parametrize(
+    "akms, label, encryption",
+    [
+        ((2,), "WPA2-PSK", "WPA2"),
+        ((1,), "WPA2-Enterprise", "WPA2"),
+        ((8,), "WPA3-SAE", "WPA3"),
+        ((2, 8), "WPA3-Transition", "WPA2"),
+        ((18,), "OWE", "OWE"),
+        ((2, 4), "FT-PSK", "WPA2"),
+        ((8, 9), "FT-SAE", "WPA3"),
+        ((12,), "WPA3-Enterprise-192", "WPA3"),
+        ((14,), "FILS", "WPA2"),
+    ],
+)
+def test_security_labels(tmp_path, akms, label, encryption):
+    """Test that AKM suites map to the expected security labels"""
+    try:
+        network = scan_network(tmp_path, rsn_element(akms=akms))
+
+        assert network.security.label == label
+        assert network.encryption == encryption
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_security_profile_fields(tmp_path):
+    """Test decoding of ciphers, capabilities, PMKIDs and management cipher"""
+    try:
+        pmkid = bytes(range(16))
+        network = scan_network(
+            tmp_path,
+            rsn_element(
+                group=2,
+                pairwise=(4, 2),
+                akms=(2, 6),
+                capabilities=0x00C8,
+                pmkids=(pmkid,),
+                group_mgmt=6,
+            ),
+        )
+        profile = network.security
+
+        assert profile.group_cipher == "TKIP"
+        assert profile.pairwise_ciphers == ["CCMP-128", "TKIP"]
+        assert profile.akm_suites == ["PSK", "PSK-SHA256"]
+        assert profile.mfp_capable
+        assert profile.mfp_required
+        assert profile.ptksa_replay_counters == 4
+        assert profile.gtksa_replay_counters == 1
+        assert profile.pmkids == [pmkid.hex()]
+        assert profile.group_management_cipher == "BIP-CMAC-128"
+        assert "WPA2-PSK" in profile.get_summary()
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_truncated_rsn_uses_defaults(tmp_path):
+    """Test that an RSN element with only a version uses the standard defaults"""
+    try:
+        network = scan_network(tmp_path, information_element(48, b"\x01\x00"))
+        profile = network.security
+
+        assert profile.group_cipher == "CCMP-128"
+        assert profile.pairwise_ciphers == ["CCMP-128"]
+        assert profile.akm_suites == ["802.1X"]
+        assert profile.label == "WPA2-Enterprise"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_open_network_has_no_profile(tmp_path):
+    """Test that networks without an RSN element have no security profile"""
+    try:
+        network = scan_network(tmp_path, b"")
+
+        assert network.security is None
+        assert network.encryption == "Open"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_wep_privacy_bit(tmp_path):
+    """Test that the privacy bit without RSN or WPA elements means WEP"""
+    try:
+        network = scan_network(tmp_path, b"", privacy=True)
+
+        assert network.encryption == "WEP"
+        assert network.security.label == "WEP"
+        assert network.security.protocols == ["WEP"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_wpa_vendor_element(tmp_path):
+    """Test decoding of the legacy WPA vendor element"""
+    try:
+        network = scan_network(tmp_path, wpa_element(), privacy=True)
+
+        assert network.encryption == "WPA"
+        assert network.security.label == "WPA-PSK"
+        assert network.security.protocols == ["WPA"]
+        assert network.security.group_cipher == "TKIP"
+        assert network.security.pairwise_ciphers == ["TKIP"]
+
+        enterprise = scan_network(tmp_path, wpa_element(akms=(1,)), privacy=True)
+        assert enterprise.security.label == "WPA-Enterprise"
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_wpa_wpa2_mixed_mode(tmp_path):
+    """Test that networks advertising both WPA and RSN are flagged as mixed mode"""
+    try:
+        elements = rsn_element(group=2, pairwise=(4,)) + wpa_element()
+        network = scan_network(tmp_path, elements, privacy=True)
+
+        assert network.encryption == "WPA"
+        assert network.security.label == "WPA/WPA2-PSK"
+        assert network.security.protocols == ["WPA", "RSN"]
+        assert network.security.pairwise_ciphers == ["CCMP-128", "TKIP"]
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_other_vendor_elements_are_ignored(tmp_path):
+    """Test that non-WPA vendor elements do not affect the security profile"""
+    try:
+        wps = information_element(221, b"\x00\x50\xf2\x04\x10\x4a\x00\x01\x10")
+        network = scan_network(tmp_path, wps)
+
+        assert network.encryption == "Open"
+        assert network.security is None
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
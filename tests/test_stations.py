@@ -0,0 +1,144 @@
+"""
+Tests for client station tracking
+"""
+
+import pytest
+
+from frames import (
+    LINKTYPE_IEEE802_11_RADIOTAP,
+    association_request_frame,
+    association_response_frame,
+    beacon_frame,
+    data_frame,
+    probe_request_frame,
+    radiotap_header,
+    write_pcap,
+)
+
+AP = "00:11:22:33:44:55"
+CLIENT = "aa:bb:cc:dd:ee:01"
+OTHER_CLIENT = "aa:bb:cc:dd:ee:02"
+
+
+def open_capture(tmp_path, frames, linktype=105):
+    import _wifite3
+
+    path = write_pcap(tmp_path / "capture.pcap", frames, linktype)
+    return _wifite3.NetworkScanner.from_file(str(path))
+
+
+def test_station_class_exported():
+    """Test that the Station class is exposed to Python"""
+    try:
+        import _wifite3
+
+        assert hasattr(_wifite3, "Station")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_data_frames_associate_clients(tmp_path):
+    """Test that uplink and downlink data frames attach stations to the AP"""
+    try:
+        scanner = open_capture(
+            tmp_path,
+            [
+                (100.0, beacon_frame(AP, "Office")),
+                (101.0, data_frame(AP, CLIENT, to_ds=True)),
+                (102.5, data_frame(AP, CLIENT, to_ds=False, subtype=8)),
+                (103.0, data_frame(AP, OTHER_CLIENT, to_ds=True, subtype=4)),
+            ],
+        )
+        networks = scanner.scan()
+        clients = {station.mac: station for station in networks[0].clients}
+
+        assert set(clients) == {CLIENT, OTHER_CLIENT}
+        client = clients[CLIENT]
+        assert client.bssid == AP
+        assert client.frame_count == 2
+        assert client.data_frame_count == 2
+        assert client.first_seen == pytest.approx(101.0)
+        assert client.last_seen == pytest.approx(102.5)
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_broadcast_data_ignored(tmp_path):
+    """Test that group-addressed downlink frames do not create stations"""
+    try:
+        scanner = open_capture(
+            tmp_path, [data_frame(AP, "ff:ff:ff:ff:ff:ff", to_ds=False)]
+        )
+
+        assert scanner.scan_stations() == []
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_rssi_only_from_station_transmissions(tmp_path):
+    """Test that RSSI comes from frames the station sent, not the AP's"""
+    try:
+        scanner = open_capture(
+            tmp_path,
+            [
+                radiotap_header({5: (-40,)}) + data_frame(AP, CLIENT, to_ds=True),
+                radiotap_header({5: (-20,)}) + data_frame(AP, CLIENT, to_ds=False),
+            ],
+            LINKTYPE_IEEE802_11_RADIOTAP,
+        )
+        (station,) = scanner.scan_stations()
+
+        assert station.rssi == -40
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_probe_requests_record_ssids(tmp_path):
+    """Test that probing stations are tracked without an association"""
+    try:
+        scanner = open_capture(
+            tmp_path,
+            [
+                probe_request_frame(CLIENT, "Home"),
+                probe_request_frame(CLIENT, "Cafe"),
+                probe_request_frame(CLIENT, "Home"),
+                probe_request_frame(CLIENT, ""),
+            ],
+        )
+        (station,) = scanner.scan_stations()
+
+        assert station.mac == CLIENT
+        assert station.bssid is None
+        assert station.probed_ssids == ["Home", "Cafe"]
+        assert station.frame_count == 4
+        assert station.data_frame_count == 0
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_association_frames(tmp_path):
+    """Test that association requests and successful responses associate stations"""
+    try:
+        scanner = open_capture(
+            tmp_path,
+            [
+                association_request_frame(AP, "Office", CLIENT),
+                association_response_frame(AP, OTHER_CLIENT, status=17),
+            ],
+        )
+        stations = {station.mac: station for station in scanner.scan_stations()}
+
+        assert stations[CLIENT].bssid == AP
+        assert stations[OTHER_CLIENT].bssid is None
+
+        scanner = open_capture(tmp_path, [association_response_frame(AP, CLIENT)])
+        (station,) = scanner.scan_stations()
+        assert station.bssid == AP
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -0,0 +1,110 @@
+"""
+Tests for verifying candidate passphrases and PMKs against captured hashes
+"""
+
+import pytest
+
+# hashcat's 22000 example PMKID, passphrase "hashcat!"
+PMKID_LINE = (
+    "WPA*01*4d4fe7aac3a2cecab195321ceb99a7d0*fc690c158264*f4747f87f9f4"
+    "*686173686361742d6573736964***"
+)
+AP = bytes.fromhex("001122334455")
+CLIENT = bytes.fromhex("aabbccddeeff")
+ANONCE = bytes(range(32))
+SNONCE = bytes(range(32, 64))
+
+
+def handshake_line(passphrase, key_version, essid=b"Office"):
+    """Build a WPA*02* line whose MIC matches `passphrase`"""
+    import _wifite3
+
+    key_info = 0x0108 | key_version
+    eapol = (
+        bytes.fromhex("0103005f02")
+        + key_info.to_bytes(2, "big")
+        + bytes.fromhex("0010")
+        + (1).to_bytes(8, "big")
+        + SNONCE
+        + bytes(48)  # IV, RSC, key ID, zeroed MIC
+        + bytes(2)  # key data length
+    )
+    pmk = _wifite3.derive_pmk(passphrase, essid)
+    ptk = _wifite3.derive_ptk(pmk, AP, CLIENT, ANONCE, SNONCE, sha256=key_version == 3)
+    mic = _wifite3.compute_mic(ptk[:16], eapol, key_version)
+    return (
+        f"WPA*02*{mic.hex()}*{AP.hex()}*{CLIENT.hex()}*{essid.hex()}"
+        f"*{ANONCE.hex()}*{eapol.hex()}*00"
+    )
+
+
+def test_pmkid_verify():
+    """Test PMKID verification with a passphrase and with a raw PMK"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.HashLine.parse(PMKID_LINE).to_capture()
+        assert isinstance(capture, _wifite3.PMKIDCapture)
+
+        assert capture.verify("hashcat!")
+        assert capture.verify(b"hashcat!")
+        assert not capture.verify("hashcat?")
+
+        pmk = _wifite3.derive_pmk("hashcat!", "hashcat-essid")
+        assert capture.verify(pmk=pmk)
+        assert not capture.verify(pmk=bytes(32))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_pmkid_verify_constructed_capture():
+    """Test verification of a capture built from its fields"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.PMKIDCapture(
+            "hashcat-essid", "fc:69:0c:15:82:64", "f4:74:7f:87:f9:f4", "4d4fe7aac3a2cecab195321ceb99a7d0"
+        )
+        assert capture.verify("hashcat!")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+@pytest.mark.parametrize("key_version", [1, 2, 3])
+def test_handshake_verify(key_version):
+    """Test handshake verification for HMAC-MD5, HMAC-SHA1 and AES-CMAC MICs"""
+    try:
+        import _wifite3
+
+        line = _wifite3.HashLine.parse(handshake_line("correct horse", key_version))
+        capture = line.to_capture()
+        assert isinstance(capture, _wifite3.HandshakeCapture)
+        assert capture.key_version == key_version
+
+        assert capture.verify("correct horse")
+        assert not capture.verify("wrong horse")
+        assert capture.verify(pmk=_wifite3.derive_pmk("correct horse", "Office"))
+        assert line.verify("correct horse")
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
+
+
+def test_verify_arguments():
+    """Test that exactly one 32-byte PMK or passphrase must be given"""
+    try:
+        import _wifite3
+
+        capture = _wifite3.HashLine.parse(PMKID_LINE).to_capture()
+
+        with pytest.raises(ValueError):
+            capture.verify()
+        with pytest.raises(ValueError):
+            capture.verify("hashcat!", pmk=bytes(32))
+        with pytest.raises(ValueError):
+            capture.verify(pmk=bytes(31))
+
+    except ImportError:
+        pytest.skip("Rust module not yet built")
@@ -10,17 +10,114 @@ from rich.text import Text
 console = Console()
 
 
+def open_scanner(_wifite3, interface, capture_file):
+    """Create a scanner for the capture file or the selected interface"""
+    if capture_file:
+        console.print(f"[blue]Reading capture file: {capture_file}[/blue]")
+        return _wifite3.NetworkScanner.from_file(capture_file)
+
+    # Get available interfaces
+    interfaces = _wifite3.NetworkScanner.get_interfaces()
+    console.print(f"[blue]Available interfaces: {', '.join(interfaces)}[/blue]")
+
+    # Use specified interface or first available
+    target_interface = (
+        interface if interface else (interfaces[0] if interfaces else "wlan0")
+    )
+    console.print(f"[blue]Using interface: {target_interface}[/blue]")
+
+    return _wifite3.NetworkScanner(target_interface)
+
+
 @click.command()
 @click.option("--interface", "-i", help="Network interface to use")
+@click.option(
+    "--read",
+    "-r",
+    "capture_file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="Read frames from a pcap/pcapng file instead of an interface",
+)
 @click.option("--verbose", "-v", is_flag=True, help="Verbose output")
 @click.option("--scan", is_flag=True, help="Scan for networks")
 @click.option("--pmkid", is_flag=True, help="Capture PMKID from EAPOL frames")
+@click.option(
+    "--handshake", is_flag=True, help="Capture WPA 4-way handshakes from EAPOL frames"
+)
 @click.option(
     "--duration", "-d", default=30, help="Capture duration in seconds (default: 30)"
 )
-def main(interface, verbose, scan, pmkid, duration):
+@click.option(
+    "--crack",
+    "hash_file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="Crack the hashes of a hashcat 22000 file on the CPU",
+)
+@click.option(
+    "--wordlist",
+    "-w",
+    type=click.Path(exists=True, dir_okay=False),
+    help="Wordlist for --crack",
+)
+@click.option(
+    "--rules",
+    "rule_file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="hashcat .rule file applied to the --crack wordlist",
+)
+@click.option(
+    "--mask",
+    "-m",
+    help="hashcat mask for --crack instead of a wordlist, e.g. ?u?u?d?d?d?d?d?d",
+)
+@click.option(
+    "--custom-charset",
+    multiple=True,
+    help="Custom charset for ?1 to ?4 in --mask; repeat in order",
+)
+@click.option(
+    "--increment", is_flag=True, help="Also try every shorter prefix of --mask"
+)
+@click.option(
+    "--checkpoint",
+    type=click.Path(dir_okay=False),
+    help="Save --crack progress to this file and resume from it if it exists; "
+    "not with --hashcat",
+)
+@click.option(
+    "--potfile",
+    type=click.Path(dir_okay=False),
+    help="hashcat potfile to skip already cracked hashes and record new ones",
+)
+@click.option(
+    "--hashcat",
+    "hashcat_binary",
+    help="Run --crack with this hashcat binary instead of the CPU engine",
+)
+def main(
+    interface,
+    capture_file,
+    verbose,
+    scan,
+    pmkid,
+    handshake,
+    duration,
+    hash_file,
+    wordlist,
+    rule_file,
+    mask,
+    custom_charset,
+    increment,
+    checkpoint,
+    potfile,
+    hashcat_binary,
+):
     """Wifite3 - Modern WiFi penetration testing tool"""
 
+    if checkpoint and hashcat_binary:
+        # hashcat keeps its own restore files, which the runner disables
+        raise click.UsageError("--checkpoint cannot be used with --hashcat")
+
     # Display banner
     banner = Text("Wifite3", style="bold blue")
     banner.append("\nModern WiFi Penetration Testing Tool", style="dim")
@@ -35,26 +132,33 @@ def main(interface, verbose, scan, pmkid, duration):
         try:
             import _wifite3
 
-            # Get available interfaces
-            interfaces = _wifite3.NetworkScanner.get_interfaces()
-            console.print(f"[blue]Available interfaces: {', '.join(interfaces)}[/blue]")
-
-            # Use specified interface or first available
-            target_interface = (
-                interface if interface else (interfaces[0] if interfaces else "wlan0")
-            )
-            console.print(f"[blue]Using interface: {target_interface}[/blue]")
-
             # Perform scan
-            scanner = _wifite3.NetworkScanner(target_interface)
+            scanner = open_scanner(_wifite3, interface, capture_file)
             networks = scanner.scan()
 
             if networks:
                 console.print(f"[green]Found {len(networks)} networks:[/green]")
                 for network in networks:
+                    security = (
+                        network.security.label
+                        if network.security
+                        else network.encryption
+                    )
+                    hidden = (
+                        f" [dim](hidden, revealed by {network.revealed_by})[/dim]"
+                        if network.revealed_by
+                        else ""
+                    )
+                    clients = (
+                        f" - {len(network.clients)} clients" if network.clients else ""
+                    )
                     console.print(
-                        f"  • {network.ssid} ({network.bssid}) - {network.encryption}"
+                        f"  • {network.ssid} ({network.bssid}) - {security}{clients}{hidden}"
                     )
+                    for change in network.changes:
+                        console.print(
+                            f"    [yellow]! {change.get_summary()}[/yellow]"
+                        )
             else:
                 console.print("[yellow]No networks found[/yellow]")
 
@@ -68,18 +172,8 @@ def main(interface, verbose, scan, pmkid, duration):
         try:
             import _wifite3
 
-            # Get available interfaces
-            interfaces = _wifite3.NetworkScanner.get_interfaces()
-            console.print(f"[blue]Available interfaces: {', '.join(interfaces)}[/blue]")
-
-            # Use specified interface or first available
-            target_interface = (
-                interface if interface else (interfaces[0] if interfaces else "wlan0")
-            )
-            console.print(f"[blue]Using interface: {target_interface}[/blue]")
-
             # Perform PMKID capture with SSID correlation
-            scanner = _wifite3.NetworkScanner(target_interface)
+            scanner = open_scanner(_wifite3, interface, capture_file)
             pmkid_captures = scanner.capture_pmkid_with_correlation(duration)
 
             if pmkid_captures:
@@ -97,7 +191,134 @@ def main(interface, verbose, scan, pmkid, duration):
         except Exception as e:
             console.print(f"[red]PMKID capture failed: {e}[/red]")
 
-    if not interface:
+    if handshake:
+        console.print(f"[green]Capturing handshakes for {duration} seconds...[/green]")
+        try:
+            import _wifite3
+
+            scanner = open_scanner(_wifite3, interface, capture_file)
+            handshakes = scanner.capture_handshakes(duration)
+
+            if handshakes:
+                console.print(
+                    f"[green]Captured {len(handshakes)} handshake(s):[/green]"
+                )
+                for capture in handshakes:
+                    console.print(f"  • {capture.get_summary()}")
+                    console.print(f"    Hashcat format: {capture.hashcat_format}")
+            else:
+                console.print("[yellow]No handshake captured[/yellow]")
+
+        except ImportError:
+            console.print("[red]Rust module not available[/red]")
+        except Exception as e:
+            console.print(f"[red]Handshake capture failed: {e}[/red]")
+
+    if hash_file:
+        if not wordlist and not mask:
+            console.print("[red]--crack requires --wordlist or --mask[/red]")
+        else:
+            console.print(
+                f"[green]Cracking {hash_file} with {wordlist or mask}...[/green]"
+            )
+            try:
+                import _wifite3
+
+                hashes = _wifite3.HashFile.read(hash_file)
+                targets = hashes.lines
+                pot = None
+                if potfile:
+                    pot = _wifite3.Potfile(potfile)
+                    known, targets = pot.partition(targets)
+                    if known:
+                        console.print(
+                            f"[green]{len(known)} hash(es) already in "
+                            f"{potfile}:[/green]"
+                        )
+                        for hit in known:
+                            console.print(f"  • {hit.get_summary()}")
+
+                if not targets:
+                    console.print("[green]Every hash is already cracked[/green]")
+                else:
+                    if hashcat_binary:
+                        attack = _wifite3.HashcatRunner(targets, binary=hashcat_binary)
+                        options = {}
+                    else:
+                        attack = _wifite3.DictionaryAttack(targets)
+                        options = {"checkpoint": checkpoint}
+
+                    def report(progress):
+                        console.print(f"[dim]{progress.get_summary()}[/dim]")
+
+                    if wordlist:
+                        rules = None
+                        if rule_file:
+                            rules = _wifite3.RuleSet.load(
+                                rule_file, skip_invalid=True
+                            )
+                            for skipped in rules.skipped:
+                                console.print(
+                                    f"[yellow]Skipping rule at {skipped}[/yellow]"
+                                )
+                        result = attack.run(
+                            wordlist,
+                            progress=report,
+                            rules=rules,
+                            **options,
+                        )
+                    else:
+                        generator = _wifite3.Mask(
+                            mask,
+                            custom_charsets=list(custom_charset),
+                            increment=increment,
+                        )
+                        keyspace = generator.keyspace
+                        console.print(f"[dim]Keyspace: {keyspace} candidates[/dim]")
+
+                        def report_with_eta(progress):
+                            done = progress.tested + progress.skipped
+                            eta = ""
+                            if progress.rate > 0:
+                                remaining = (keyspace - done) / progress.rate
+                                eta = f", about {remaining / 60:.0f} min left"
+                            console.print(
+                                f"[dim]{progress.get_summary()}{eta}[/dim]"
+                            )
+
+                        result = attack.run_mask(
+                            generator,
+                            progress=report_with_eta,
+                            **options,
+                        )
+                    if result.resumed:
+                        console.print(f"[dim]Resumed from {checkpoint}[/dim]")
+                    console.print(
+                        f"[dim]{result.essid_groups} ESSID group(s), "
+                        f"{result.pmks_computed} PMKs for "
+                        f"{result.targets_checked} checks "
+                        f"({result.pmk_reuse_factor:.1f} checks per PMK)[/dim]"
+                    )
+
+                    if result.found:
+                        console.print(
+                            f"[green]Cracked {len(result.found)} of "
+                            f"{len(targets)} hash(es):[/green]"
+                        )
+                        for hit in result.found:
+                            console.print(f"  • {hit.get_summary()}")
+                        if pot is not None:
+                            added = pot.append(result.found)
+                            console.print(f"[dim]Added {added} to {potfile}[/dim]")
+                    else:
+                        console.print("[yellow]No passphrase found[/yellow]")
+
+            except ImportError:
+                console.print("[red]Rust module not available[/red]")
+            except Exception as e:
+                console.print(f"[red]Cracking failed: {e}[/red]")
+
+    if (scan or pmkid or handshake) and not interface and not capture_file:
         console.print(
             "[yellow]No interface specified. Use -i to specify interface.[/yellow]"
         )